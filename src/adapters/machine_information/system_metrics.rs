@@ -9,6 +9,12 @@ use crate::domain::models::machine_information::{
     CpuInfo,
 };
 
+impl Default for SystemMetrics {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl SystemMetrics {
     pub fn new() -> Self {
         let mut sys = System::new_all();
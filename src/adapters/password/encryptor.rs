@@ -13,7 +13,7 @@ pub trait PasswordEncryptorPort: Send + Sync {
 }
 
 // Implementação usando Argon2
-#[derive(Clone)] // Permite clonagem do adaptador
+#[derive(Clone, Default)] // Permite clonagem do adaptador
 pub struct Argon2PasswordEncryptor;
 
 impl Argon2PasswordEncryptor {
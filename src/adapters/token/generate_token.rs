@@ -4,6 +4,7 @@ use crate::domain::models::auth::Claims;
 
 // Interface para geração de tokens
 pub trait TokenGeneratorPort: Send + Sync {
+    #[allow(clippy::too_many_arguments)]
     fn generate_token(
         &self, 
         user_id: String, 
@@ -17,7 +18,7 @@ pub trait TokenGeneratorPort: Send + Sync {
 }
 
 // Implementação usando JWT
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct JwtTokenGenerator;
 
 impl JwtTokenGenerator {
@@ -0,0 +1,209 @@
+use actix_web::{web, HttpResponse};
+use log::{error, info};
+use serde_json::json;
+
+use crate::domain::models::audit::Pagination;
+use crate::domain::models::classification_review::{
+    ClassificationCorrection,
+    ClassificationReviewFilter,
+    ClassificationReviewQuery,
+    CorrectionExportQuery,
+    CreateClassificationCorrectionDto,
+};
+use crate::domain::repositories::classification_review::ClassificationReviewRepository;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::infrastructure::repositories::classification_review_repository::PgClassificationReviewRepository;
+use crate::infrastructure::repositories::data_upa_repository::PgDataRepository;
+use crate::utils::competencia::{competencia_variants, validate_competencia};
+use crate::utils::process_data::list_of_most_common_diseases;
+use crate::utils::response::ApiResponse;
+use crate::AppError;
+
+pub struct ClassificationReviewService {
+    repo: web::Data<PgClassificationReviewRepository>,
+    data_repo: web::Data<PgDataRepository>,
+}
+
+impl ClassificationReviewService {
+    pub fn new(repo: web::Data<PgClassificationReviewRepository>, data_repo: web::Data<PgDataRepository>) -> Self {
+        Self { repo, data_repo }
+    }
+
+    pub async fn classified_records(&self, page: i32, query: ClassificationReviewQuery) -> Result<HttpResponse, AppError> {
+        if page < 1 {
+            return Err(AppError::BadRequest("Error: 'page' must be greater than or equal to 1".to_string()));
+        }
+
+        let filter = build_review_filter(query)?;
+
+        let (records, total_records) = self.repo.fetch_classified_records(page, &filter).await
+            .map_err(|e| {
+                error!("Error retrieving classified records: {}", e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        if records.is_empty() {
+            info!("No classified records found.");
+            return Err(AppError::NotFound("No classified records found".into()));
+        }
+
+        // Calcular a paginação
+        let records_per_page = 15;
+        let total_pages = (total_records as f64 / records_per_page as f64).ceil() as i32;
+
+        let pagination = Pagination {
+            current_page: page,
+            total_pages,
+            total_records,
+            records_per_page,
+        };
+
+        Ok(ApiResponse::success(json!({
+            "records": records,
+            "pagination": pagination,
+        })).into_response())
+    }
+
+    pub async fn create_correction(&self, mut correction: CreateClassificationCorrectionDto, corrected_by: String) -> Result<HttpResponse, AppError> {
+        correction.queixa_corrigida = correction.queixa_corrigida.trim().to_lowercase();
+
+        // A correção deve usar uma das doenças conhecidas pelo classificador
+        let known_diseases: Vec<&str> = list_of_most_common_diseases().iter().map(|d| d.trim()).collect();
+        if !known_diseases.contains(&correction.queixa_corrigida.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Error: '{}' is not a valid value for 'queixa_corrigida'",
+                correction.queixa_corrigida
+            )));
+        }
+
+        let record = match self.repo.find_classified_record(correction.unidade_id, &correction.ifroid).await {
+            Ok(Some(record)) => record,
+            Ok(None) => {
+                return Err(AppError::NotFound(format!(
+                    "Record {} not found for unit {}",
+                    correction.ifroid, correction.unidade_id
+                )));
+            },
+            Err(e) => {
+                error!("Error fetching classified record {}: {}", correction.ifroid, e);
+                return Err(AppError::DatabaseError(e.to_string()));
+            }
+        };
+
+        if record.queixa_principal == correction.queixa_corrigida {
+            return Err(AppError::BadRequest(format!(
+                "Record {} is already classified as '{}'",
+                record.ifroid, record.queixa_principal
+            )));
+        }
+
+        let created = self.repo.create_correction(&record, &correction, &corrected_by).await
+            .map_err(|e| {
+                error!("Error creating classification correction: {}", e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        // A competência corrigida será recalculada na próxima atualização incremental dos gráficos
+        if let Err(e) = self.data_repo.mark_competencias_pending(created.ifrounidadeid, std::slice::from_ref(&created.ifrocompetencia)).await {
+            error!("Error marking competencia {} as pending for unit {}: {}", created.ifrocompetencia, created.ifrounidadeid, e);
+        }
+
+        Ok(ApiResponse::created(created).into_response())
+    }
+
+    pub async fn export_corrections(&self, query: CorrectionExportQuery) -> Result<HttpResponse, AppError> {
+        let format = query.format.unwrap_or_else(|| "json".to_string()).to_lowercase();
+
+        if format != "json" && format != "csv" {
+            return Err(AppError::BadRequest(format!(
+                "Error: '{}' is not a valid value for 'format'. Expected: json or csv",
+                format
+            )));
+        }
+
+        let corrections = self.repo.fetch_corrections(query.unidade_id).await
+            .map_err(|e| {
+                error!("Error retrieving classification corrections: {}", e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        if format == "csv" {
+            return Ok(HttpResponse::Ok()
+                .content_type("text/csv; charset=utf-8")
+                .insert_header(("Content-Disposition", "attachment; filename=\"classification_corrections.csv\""))
+                .body(corrections_to_csv(&corrections)));
+        }
+
+        Ok(ApiResponse::success(corrections).into_response())
+    }
+}
+
+fn build_review_filter(query: ClassificationReviewQuery) -> Result<ClassificationReviewFilter, AppError> {
+    for (field, value) in [("confianca_min", query.confianca_min), ("confianca_max", query.confianca_max)] {
+        if let Some(value) = value {
+            if !(0.0..=100.0).contains(&value) {
+                return Err(AppError::BadRequest(format!(
+                    "Error: '{}' must be between 0 and 100", field
+                )));
+            }
+        }
+    }
+
+    if let (Some(min), Some(max)) = (query.confianca_min, query.confianca_max) {
+        if min > max {
+            return Err(AppError::BadRequest(
+                "Error: 'confianca_min' must be less than or equal to 'confianca_max'".to_string()
+            ));
+        }
+    }
+
+    let competencias = match query.competencia {
+        Some(competencia) => Some(competencia_variants(validate_competencia("competencia", &competencia)?)),
+        None => None,
+    };
+
+    Ok(ClassificationReviewFilter {
+        unidade_id: query.unidade_id,
+        doenca: query.doenca.map(|d| d.trim().to_lowercase()),
+        confianca_min: query.confianca_min,
+        confianca_max: query.confianca_max,
+        competencias,
+    })
+}
+
+// Escapa um campo para CSV quando necessário
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn corrections_to_csv(corrections: &[ClassificationCorrection]) -> String {
+    let mut csv = String::from(
+        "id,ifroid,ifrounidadeid,ifrocompetencia,conduta,queixa_anterior,queixa_corrigida,confianca,observacao,corrected_by,created_at\n"
+    );
+
+    for correction in corrections {
+        let fields = [
+            correction.id.to_string(),
+            correction.ifroid.clone(),
+            correction.ifrounidadeid.to_string(),
+            correction.ifrocompetencia.clone(),
+            correction.conduta.clone(),
+            correction.queixa_anterior.clone(),
+            correction.queixa_corrigida.clone(),
+            correction.confianca.map(|c| c.to_string()).unwrap_or_default(),
+            correction.observacao.clone().unwrap_or_default(),
+            correction.corrected_by.clone(),
+            correction.created_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        ];
+
+        let line: Vec<String> = fields.iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&line.join(","));
+        csv.push('\n');
+    }
+
+    csv
+}
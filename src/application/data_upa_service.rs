@@ -1,15 +1,13 @@
-use std::collections::HashMap;
-
 use actix_web::{web, HttpResponse};
 use log::{error, info};
-use serde_json::{json, Value};
+use serde_json::json;
 use polars::prelude::*;
 
 use crate::domain::{
-    repositories::data_upa::DataRepository,
+    repositories::{data_upa::DataRepository, health_unit::HealthUnitRepository},
     models::data_upa::HealthUnit,
 };
-use crate::infrastructure::repositories::data_upa_repository::PgDataRepository;
+use crate::infrastructure::repositories::{data_upa_repository::PgDataRepository, health_unit_repository::PgHealthUnitRepository};
 use crate::utils::response::ApiResponse;
 use crate::AppError;
 
@@ -28,19 +26,32 @@ use crate::utils::process_data::{
     get_unique_values,
     create_dataframe,
     read_df_from_bytes,
+    extract_conduta_records,
+    get_competencias_by_unit,
+    add_visit_key_column,
 };
 
 pub struct DataUpaService {
     repo: web::Data<PgDataRepository>,
+    health_unit_repo: web::Data<PgHealthUnitRepository>,
+    visit_key_secret: Option<String>,
 }
 
 impl DataUpaService {
-    pub fn new(repo: web::Data<PgDataRepository>) -> Self {
-        Self { repo }
+    pub fn new(repo: web::Data<PgDataRepository>, health_unit_repo: web::Data<PgHealthUnitRepository>, visit_key_secret: Option<String>) -> Self {
+        Self { repo, health_unit_repo, visit_key_secret }
     }
     
     pub async fn add_data(&self, file_content: web::Bytes) -> Result<HttpResponse, AppError> {
         info!("Iniciando processamento do arquivo CSV");
+
+        // Sem o segredo, a chave de visita seria um hash reversível do paciente
+        let Some(visit_key_secret) = self.visit_key_secret.as_deref() else {
+            error!("VISIT_KEY_SECRET não configurado; importação recusada");
+            return Err(AppError::DataProcessingError(
+                "VISIT_KEY_SECRET must be set to import data".to_string()
+            ));
+        };
                
         // Lê o DataFrame diretamente dos bytes do arquivo
         let df = match read_df_from_bytes(&file_content) {
@@ -54,6 +65,15 @@ impl DataUpaService {
         let (rows_before, cols_before) = df.shape();
         info!("Arquivo lido com sucesso: {} linhas, {} colunas", rows_before, cols_before);
     
+        // Liga os registros da mesma visita antes de remover o identificador do paciente
+        let df = match add_visit_key_column(df, visit_key_secret) {
+            Ok(df) => df,
+            Err(e) => {
+                error!("Erro ao gerar a chave das visitas: {:?}", e);
+                return Err(AppError::InternalServerError);
+            }
+        };
+    
         // Remove as colunas desnecessárias
         let colunas_desnecessarias = columns_names();
         let df_reduzido = match remove_unnecessary_columns(df.clone(), &colunas_desnecessarias) {
@@ -93,12 +113,22 @@ impl DataUpaService {
             }
         };
     
-        // Remover a coluna "IfroConsultaConduta"
-        let colunas_remover = ["IfroConsultaConduta"];
+        // Guarda o texto da conduta e a confiança da classificação para revisões e reclassificações futuras
+        let df_conduta = match extract_conduta_records(&df_com_queixas)
+            .and_then(normalize_column_names_of_the_df_to_lower_case) {
+            Ok(df) => df,
+            Err(e) => {
+                error!("Erro ao separar registros de conduta: {:?}", e);
+                return Err(AppError::InternalServerError);
+            }
+        };
+
+        // Remover as colunas "IfroConsultaConduta" e "IfroClassificacaoConfianca"
+        let colunas_remover = ["IfroConsultaConduta", "IfroClassificacaoConfianca"];
         let df_com_queixas = match remove_unnecessary_columns(df_com_queixas, &colunas_remover) {
             Ok(df) => df,
             Err(e) => {
-                error!("Erro ao remover coluna IfroConsultaConduta: {:?}", e);
+                error!("Erro ao remover colunas IfroConsultaConduta e IfroClassificacaoConfianca: {:?}", e);
                 return Err(AppError::InternalServerError);
             }
         };
@@ -207,6 +237,32 @@ impl DataUpaService {
             Ok(true) => {
                 info!("Dados inseridos com sucesso na tabela {}.", table_name);
                 let (rows, cols) = df_final_normalizado.shape();
+
+                // Marca as competências importadas para a próxima atualização incremental dos gráficos
+                match get_competencias_by_unit(&df_final_normalizado) {
+                    Ok(competencias_por_unidade) => {
+                        // Unidades novas entram no cadastro com o nome do arquivo
+                        let unidades: Vec<i32> = competencias_por_unidade.keys().copied().collect();
+                        match self.health_unit_repo.register_imported_units(&unidades).await {
+                            Ok(0) => {},
+                            Ok(registered) => info!("{} unidades de saúde cadastradas a partir da importação", registered),
+                            Err(e) => error!("Erro ao cadastrar unidades importadas: {:?}", e),
+                        }
+
+                        for (unidade_id, competencias) in competencias_por_unidade {
+                            if let Err(e) = self.repo.mark_competencias_pending(unidade_id, &competencias).await {
+                                error!("Erro ao marcar competências pendentes da unidade {}: {:?}", unidade_id, e);
+                            }
+                        }
+                    },
+                    Err(e) => error!("Erro ao obter competências por unidade: {:?}", e),
+                }
+
+                // Sem as condutas, os atendimentos importados não podem ser revisados nem reclassificados
+                if let Err(e) = self.repo.insert_conduta_records(&df_conduta).await {
+                    error!("Erro ao inserir dados na tabela bpa_conduta: {:?}", e);
+                    return Err(AppError::DatabaseError(format!("Error saving the conduct records of the imported rows: {}", e)));
+                }
                 
                 Ok(ApiResponse::created(json!({
                     "message": "Dados processados e importados com sucesso",
@@ -226,73 +282,18 @@ impl DataUpaService {
         }
     }
 
+    // Unidades ativas do cadastro de unidades de saúde
     pub async fn get_available_health_units(&self) -> Result<HttpResponse, AppError> {
-        let table_name = "bpa";
-        let columns = vec![
-            "ifrounidadeid".to_string(),
-            "ifrounidadenome".to_string()
-        ];
+        match self.health_unit_repo.fetch_units(Some(true)).await {
+            Ok(units) => {
+                let result: Vec<HealthUnit> = units.into_iter()
+                    .map(|unit| HealthUnit {
+                        id: unit.ifrounidadeid as i64,
+                        name: unit.nome,
+                    })
+                    .collect();
 
-        match self.repo.fetch_distinct_health_units(table_name, &columns).await {
-            Ok(data) => {
-                let mut units = Vec::new();
-                
-                info!("Raw data from database: {:?}", data);
-                
-                if let Some(ids) = data.get("ifrounidadeid") {
-                    if let Some(names) = data.get("ifrounidadenome") {
-                        info!("IDs found: {:?}", ids);
-                        info!("Names found: {:?}", names);
-                        
-                        for (id, name) in ids.iter().zip(names.iter()) {
-                            info!("Processing: id={:?}, name={:?}", id, name);
-                            
-                            // Tenta converter id para i64
-                            let id_value = match id {
-                                Value::Number(n) => {
-                                    if let Some(i) = n.as_i64() {
-                                        Some(i)
-                                    } else if let Some(f) = n.as_f64() {
-                                        Some(f as i64)  // Converter float para int
-                                    } else {
-                                        None
-                                    }
-                                },
-                                _ => None
-                            };
-                            
-                            // Tenta converter name para string
-                            let name_value = match name {
-                                Value::String(s) => Some(s.clone()),
-                                _ => None
-                            };
-                            
-                            if let (Some(id), Some(name)) = (id_value, name_value) {
-                                info!("Adding unit: id={}, name={}", id, name);
-                                units.push(HealthUnit {
-                                    id,
-                                    name,
-                                });
-                            } else {
-                                info!("Failed to convert: id={:?}, name={:?}", id, name);
-                            }
-                        }
-                    } else {
-                        info!("No names found in data");
-                    }
-                } else {
-                    info!("No ids found in data");
-                }
-                
-                // Remove duplicatas baseado no id
-                let mut unique_units: HashMap<i64, HealthUnit> = HashMap::new();
-                for unit in units {
-                    unique_units.insert(unit.id, unit);
-                }
-                
-                let result: Vec<HealthUnit> = unique_units.into_values().collect();
-                info!("Found {} unique health units", result.len());
-                
+                info!("Found {} active health units", result.len());
                 Ok(ApiResponse::success(result).into_response())
             },
             Err(e) => {
@@ -0,0 +1,59 @@
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, ChartContext, ChartData, ChartQuery, ChartView, GraphAggregation};
+use crate::domain::models::visualization_data_graph::AgeBandQuery;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::age_bands::{group_ages_into_bands, AgeBands, AGE_BANDS_DEFAULT};
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::AppError;
+
+/// Tabela cruzada de faixa etária e queixa classificada, a partir das contagens por idade simples
+pub struct AgeBandByDisease;
+
+impl AgeBandByDisease {
+    pub const DEFAULT_BANDS: &'static str = AGE_BANDS_DEFAULT;
+
+    /// Agrupa as idades nas faixas informadas ({faixas, doencas: {doença: {período: [n, ...]}}})
+    pub fn present_with_bands(data: &Map<String, Value>, bands: &AgeBands) -> Value {
+        json!({
+            "faixas": bands.labels(),
+            "doencas": group_ages_into_bands(data, bands),
+        })
+    }
+}
+
+#[async_trait]
+impl GraphAggregation for AgeBandByDisease {
+    fn name(&self) -> &'static str {
+        "age_band_by_disease"
+    }
+
+    fn slug(&self) -> &'static str {
+        "age-band-by-disease"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifropacientequeixaprincipal", "ifropacienteidade"]
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_complaint_and_age(ctx.unidade_id, ctx.competencias).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_age_by_disease(counts))
+    }
+
+    fn present(&self, data: Map<String, Value>) -> Value {
+        let bands = AgeBands::from_scheme(Self::DEFAULT_BANDS).unwrap_or_else(AgeBands::default_bands);
+        Self::present_with_bands(&data, &bands)
+    }
+
+    // Faixas etárias da consulta (`faixas` ou `limites`)
+    async fn present_query(&self, data: Map<String, Value>, query: &ChartQuery<'_>) -> Result<ChartView, AppError> {
+        let params: AgeBandQuery = query.parse()?;
+        let bands = AgeBands::from_query(params.faixas.as_deref(), params.limites.as_deref(), Self::DEFAULT_BANDS)?;
+
+        Ok(query.suppressed(self.cell_level(), Self::present_with_bands(&data, &bands)))
+    }
+}
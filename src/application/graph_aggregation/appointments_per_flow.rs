@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+use std::error::Error;
+
+use super::{slices_from_counts, ChartContext, ChartData, GraphAggregation};
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+
+/// Número de atendimentos por fluxo (tabela de origem)
+pub struct AppointmentsPerFlow;
+
+#[async_trait]
+impl GraphAggregation for AppointmentsPerFlow {
+    fn name(&self) -> &'static str {
+        "number_of_appointments_per_flow"
+    }
+
+    fn slug(&self) -> &'static str {
+        "number-of-appointments-per-flow"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifrotabelanome"]
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_and_column("ifrotabelanome", ctx.unidade_id, ctx.competencias).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_number_of_appointments_per_flow(counts))
+    }
+}
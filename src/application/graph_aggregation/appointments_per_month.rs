@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+use std::error::Error;
+
+use super::{slices_from_counts, ChartContext, ChartData, GraphAggregation};
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+
+/// Número de atendimentos por competência
+pub struct AppointmentsPerMonth;
+
+#[async_trait]
+impl GraphAggregation for AppointmentsPerMonth {
+    fn name(&self) -> &'static str {
+        "number_of_appointments_per_month"
+    }
+
+    fn slug(&self) -> &'static str {
+        "number-of-appointments-per-month"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia"]
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia(ctx.unidade_id, ctx.competencias).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_number_of_appointments_per_month(counts))
+    }
+}
@@ -0,0 +1,101 @@
+use async_trait::async_trait;
+use chrono::{Duration, NaiveDate};
+use serde_json::json;
+use std::collections::HashMap;
+use std::error::Error;
+
+use super::{ChartContext, ChartData, GraphAggregation};
+use crate::domain::models::time_series::DailyCount;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::competencia::competencias_date_range;
+use crate::utils::forecast::forecast_daily_counts;
+
+/// Queixas principais consideradas respiratórias na previsão
+pub const RESPIRATORY_COMPLAINTS: [&str; 10] = [
+    "covid-19",
+    "sars-cov-2",
+    "gripe",
+    "influenza",
+    "pneumonia",
+    "tuberculose",
+    "sinusite",
+    "rinite",
+    "faringite",
+    "laringite",
+];
+
+/// Previsão de atendimentos diários para as próximas quatro semanas, no total e para
+/// queixas respiratórias. O modelo é ajustado sobre todo o histórico, por isso o gráfico
+/// é sempre recalculado por completo
+pub struct AttendanceForecast;
+
+// Contagens diárias entre start e end, com zero nos dias sem atendimento
+fn daily_values(counts: &HashMap<NaiveDate, i64>, start: NaiveDate, end: NaiveDate) -> Vec<f64> {
+    start.iter_days()
+        .take_while(|day| *day <= end)
+        .map(|day| counts.get(&day).copied().unwrap_or(0) as f64)
+        .collect()
+}
+
+#[async_trait]
+impl GraphAggregation for AttendanceForecast {
+    fn name(&self) -> &'static str {
+        "attendance_forecast"
+    }
+
+    fn slug(&self) -> &'static str {
+        "attendance-forecast"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrodataatendimento", "ifropacientequeixaprincipal"]
+    }
+
+    fn mergeable(&self) -> bool {
+        false
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        // Com competências informadas, o histórico é limitado aos dias dessas competências
+        let (from, to) = match ctx.competencias.and_then(competencias_date_range) {
+            Some((from, to)) => (Some(from), Some(to)),
+            None => (None, None),
+        };
+
+        let counts: Vec<DailyCount> = ctx.repo.fetch_daily_counts(ctx.unidade_id, from, to, None).await?;
+
+        let (start, end) = match (counts.iter().map(|c| c.data).min(), counts.iter().map(|c| c.data).max()) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Err(format!("Dados vazios para {} na unidade {}", self.name(), ctx.unidade_id).into()),
+        };
+
+        let mut total: HashMap<NaiveDate, i64> = HashMap::new();
+        let mut respiratory: HashMap<NaiveDate, i64> = HashMap::new();
+
+        for count in &counts {
+            *total.entry(count.data).or_insert(0) += count.quantidade;
+
+            if RESPIRATORY_COMPLAINTS.contains(&count.queixa.as_str()) {
+                *respiratory.entry(count.data).or_insert(0) += count.quantidade;
+            }
+        }
+
+        let total_forecast = forecast_daily_counts(start, &daily_values(&total, start, end))
+            .ok_or_else(|| format!(
+                "Histórico insuficiente para {} na unidade {}: {} dias",
+                self.name(), ctx.unidade_id, (end - start + Duration::days(1)).num_days()
+            ))?;
+
+        // Sem queixas respiratórias no período, apenas a previsão total é retornada
+        let respiratory_forecast = if respiratory.is_empty() {
+            None
+        } else {
+            forecast_daily_counts(start, &daily_values(&respiratory, start, end))
+        };
+
+        Ok(ChartData::Complete(json!({
+            "total": total_forecast,
+            "respiratorio": respiratory_forecast,
+        })))
+    }
+}
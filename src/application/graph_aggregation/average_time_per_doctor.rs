@@ -0,0 +1,46 @@
+use async_trait::async_trait;
+use std::error::Error;
+
+use super::{ChartContext, ChartData, GraphAggregation};
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::utils::process_data::create_dataframe_from_dict;
+
+/// Tempo médio de atendimento por médico. Médias não são somadas entre competências,
+/// por isso o gráfico é sempre recalculado por completo
+pub struct AverageTimePerDoctor;
+
+#[async_trait]
+impl GraphAggregation for AverageTimePerDoctor {
+    fn name(&self) -> &'static str {
+        "average_time_per_doctor"
+    }
+
+    fn slug(&self) -> &'static str {
+        "average-time-in-minutes-per-doctor"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifrohoraatendimento", "ifroprofissionalid", "ifroprofissionalcbods", "ifroprofissionalnome", "ifrotabelanome"]
+    }
+
+    fn mergeable(&self) -> bool {
+        false
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let raw_data = ctx.fetch_columns(self.required_columns()).await?;
+        if raw_data.is_empty() {
+            return Err(format!("Dados vazios para {} na unidade {}", self.name(), ctx.unidade_id).into());
+        }
+
+        let df = create_dataframe_from_dict(&raw_data)?;
+        let df_non_doctors = create_dataframe_from_dict(&ctx.repo.fetch_all_data("non_doctors").await?)?;
+
+        let data = DataProcessingForGraphPlotting
+            .create_dict_to_average_time_in_minutes_per_doctor(&df, &df_non_doctors)
+            .await?;
+
+        Ok(ChartData::Complete(data))
+    }
+}
@@ -0,0 +1,53 @@
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, ChartContext, ChartData, GraphAggregation};
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+
+/// Número de atendimentos por dia da semana
+pub struct CallsPerDayOfTheWeek;
+
+#[async_trait]
+impl GraphAggregation for CallsPerDayOfTheWeek {
+    fn name(&self) -> &'static str {
+        "number_of_calls_per_day_of_the_week"
+    }
+
+    fn slug(&self) -> &'static str {
+        "number-of-calls-per-day-of-the-week"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifrodiasemana"]
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_and_column("ifrodiasemana", ctx.unidade_id, ctx.competencias).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_number_of_calls_per_day_of_the_week(counts))
+    }
+
+    // Retorna os dias como lista, preservando a ordem da semana
+    fn present(&self, data: Map<String, Value>) -> Value {
+        let days_order = [
+            "segunda-feira",
+            "terça-feira",
+            "quarta-feira",
+            "quinta-feira",
+            "sexta-feira",
+            "sábado",
+            "domingo",
+        ];
+
+        let ordered_data: Vec<Value> = days_order.iter()
+            .filter_map(|day| data.get(*day).map(|value| json!({
+                "day_of_the_week": day,
+                "day_of_the_week_data": value.clone()
+            })))
+            .collect();
+
+        Value::Array(ordered_data)
+    }
+}
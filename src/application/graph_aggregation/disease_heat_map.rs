@@ -0,0 +1,85 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, CellLevel, ChartContext, ChartData, ChartQuery, ChartView, GraphAggregation};
+use crate::domain::models::time_series::NeighborhoodTrendQuery;
+use crate::domain::models::visualization_data_graph::HeatMapOutputQuery;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::competencia::validate_period;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::utils::incidence::rate_requested;
+use crate::utils::time_series::{neighborhood_disease_trend, trend_requested, TrendOptions};
+use crate::AppError;
+
+/// Mapa de calor com a quantidade de atendimentos por queixa principal e bairro
+pub struct DiseaseHeatMap;
+
+impl DiseaseHeatMap {
+    /// Nomes das chaves de cada nível até o bairro nas propriedades dos pontos GeoJSON
+    pub const GEOJSON_PROPERTIES: &'static [&'static str] = &["queixa", "periodo", "bairro"];
+
+    // As contagens pequenas são anuladas na própria série, antes da média móvel e do acumulado
+    fn present_trend(data: &Map<String, Value>, trend: &NeighborhoodTrendQuery, query: &ChartQuery<'_>) -> Result<ChartView, AppError> {
+        let options = TrendOptions::from_query(trend)?;
+        let window = validate_period(trend.from.as_deref(), trend.to.as_deref())?;
+
+        let (data, suppressed) = neighborhood_disease_trend(data, &options, window, query.min_cell_size)
+            .ok_or_else(|| AppError::NotFound(format!(
+                "No cases of {} found for unit {} in the selected period", options.doenca, query.unidade_id
+            )))?;
+
+        Ok(ChartView { data, level: CellLevel::Neighborhood, suppressed, geojson: None })
+    }
+}
+
+#[async_trait]
+impl GraphAggregation for DiseaseHeatMap {
+    fn name(&self) -> &'static str {
+        "heat_map_with_disease_indication"
+    }
+
+    fn slug(&self) -> &'static str {
+        "heat-map-with-disease-indication"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifropacienteendereco", "ifropacientebairro", "ifropacientequeixaprincipal", "ifropacientelatitude", "ifropacientelongitude"]
+    }
+
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Neighborhood
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_and_location(
+            Some("ifropacientequeixaprincipal"),
+            &ctx.rules.enderecos_excluidos,
+            &ctx.rules.bairros_genericos,
+            ctx.unidade_id,
+            ctx.competencias
+        ).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dictionary_with_location_and_number_per_disease(counts))
+    }
+
+    // `format=geojson` (ou `Accept: application/geo+json`) retorna uma FeatureCollection com um ponto por bairro,
+    // e `medida=taxa` acrescenta a taxa por mil habitantes de cada bairro. `visao=tendencia` retorna a série
+    // mensal de uma doença por bairro
+    async fn present_query(&self, data: Map<String, Value>, query: &ChartQuery<'_>) -> Result<ChartView, AppError> {
+        let output: HeatMapOutputQuery = query.parse()?;
+        let geojson = query.wants_geojson(output.format.as_deref())?;
+
+        let trend: NeighborhoodTrendQuery = query.parse()?;
+        if trend_requested(&trend)? {
+            if geojson || rate_requested(output.medida.as_deref())? {
+                return Err(AppError::BadRequest("Error: 'visao=tendencia' cannot be combined with 'format=geojson' or 'medida=taxa'".to_string()));
+            }
+            return Self::present_trend(&data, &trend, query);
+        }
+
+        let data = query.with_measure(self.present(data), output.medida.as_deref()).await?;
+
+        Ok(query.suppressed(self.cell_level(), data).with_geojson(geojson, Self::GEOJSON_PROPERTIES))
+    }
+}
@@ -0,0 +1,69 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, CellLevel, ChartContext, ChartData, ChartQuery, ChartView, GraphAggregation};
+use crate::domain::models::hotspots::HotspotQuery;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::utils::hotspots::{disease_hotspots, HotspotParams};
+use crate::AppError;
+
+/// Aglomerados espaciais (DBSCAN) e pontos quentes (Gi* de Getis-Ord) por queixa principal,
+/// a partir dos casos contados por local
+pub struct DiseaseHotspots;
+
+impl DiseaseHotspots {
+    /// Aglomerados e significância por queixa e período, com as envoltórias limitadas aos locais
+    /// com ao menos `min_cell_size` casos
+    pub fn present_with_params(data: &Map<String, Value>, params: &HotspotParams, min_cell_size: i64) -> Value {
+        disease_hotspots(data, params, min_cell_size)
+    }
+}
+
+#[async_trait]
+impl GraphAggregation for DiseaseHotspots {
+    fn name(&self) -> &'static str {
+        "disease_hotspots"
+    }
+
+    fn slug(&self) -> &'static str {
+        "disease-hotspots"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifropacienteendereco", "ifropacientequeixaprincipal", "ifropacientelatitude", "ifropacientelongitude"]
+    }
+
+    // Os aglomerados dependem das contagens absolutas de cada local
+    fn normalizable(&self) -> bool {
+        false
+    }
+
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Neighborhood
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_complaint_and_point(
+            &ctx.rules.enderecos_excluidos,
+            &ctx.rules.bairros_genericos,
+            ctx.unidade_id,
+            ctx.competencias
+        ).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_disease_hotspots(counts))
+    }
+
+    // Sem o tamanho mínimo de célula da consulta, nenhum local entra nas envoltórias
+    fn present(&self, data: Map<String, Value>) -> Value {
+        Self::present_with_params(&data, &HotspotParams::default_params(), i64::MAX)
+    }
+
+    // Raio, mínimo de casos e distância do Gi* da consulta
+    async fn present_query(&self, data: Map<String, Value>, query: &ChartQuery<'_>) -> Result<ChartView, AppError> {
+        let params = HotspotParams::from_query(&query.parse::<HotspotQuery>()?)?;
+
+        Ok(query.suppressed(self.cell_level(), Self::present_with_params(&data, &params, query.min_cell_size)))
+    }
+}
@@ -0,0 +1,66 @@
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::error::Error;
+
+use super::{ChartContext, ChartData, GraphAggregation};
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::staffing::{staffing_by_weekday, WEEKDAYS};
+
+// Mesmos profissionais e tabela usados no tempo médio por médico
+const DOCTOR_CBOS: [&str; 2] = ["MEDICO CLINICO", "MEDICO CIRURGIAO GERAL"];
+const CONSULTATION_TABLE: &str = "ConsultaMedica";
+
+/// Recomendação de médicos por dia da semana e faixa de duas horas (modelo Erlang C),
+/// combinando a taxa de chegada das consultas com o tempo médio de atendimento.
+/// Taxas e médias não são somadas entre competências, por isso o gráfico é sempre recalculado por completo
+pub struct DoctorStaffing;
+
+#[async_trait]
+impl GraphAggregation for DoctorStaffing {
+    fn name(&self) -> &'static str {
+        "doctor_staffing_recommendation"
+    }
+
+    fn slug(&self) -> &'static str {
+        "doctor-staffing-recommendation"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifrodataatendimento", "ifrohoraatendimento", "ifroprofissionalcbods", "ifroprofissionalnome", "ifrotabelanome"]
+    }
+
+    fn mergeable(&self) -> bool {
+        false
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let arrivals = ctx.repo.fetch_hourly_arrivals(&DOCTOR_CBOS, CONSULTATION_TABLE, ctx.unidade_id, ctx.competencias).await?;
+        if arrivals.is_empty() {
+            return Err(format!("Dados vazios para {} na unidade {}", self.name(), ctx.unidade_id).into());
+        }
+
+        let excluded_names = ctx.excluded_names("non_doctors").await?;
+        let service_minutes = ctx.repo.average_service_minutes(&DOCTOR_CBOS, CONSULTATION_TABLE, &excluded_names, ctx.unidade_id, ctx.competencias).await?
+            .ok_or_else(|| format!("Tempo médio de atendimento indisponível para {} na unidade {}", self.name(), ctx.unidade_id))?;
+
+        Ok(ChartData::Complete(staffing_by_weekday(&arrivals, service_minutes)))
+    }
+
+    // Retorna os dias como lista, preservando a ordem da semana
+    fn present(&self, mut data: Map<String, Value>) -> Value {
+        let days = match data.remove("dias") {
+            Some(Value::Object(days)) => days,
+            _ => Map::new(),
+        };
+
+        let ordered_days: Vec<Value> = WEEKDAYS.iter()
+            .filter_map(|(_, day)| days.get(*day).map(|slots| json!({
+                "day_of_the_week": day,
+                "slots": slots.clone()
+            })))
+            .collect();
+
+        data.insert("dias".to_string(), Value::Array(ordered_days));
+        Value::Object(data)
+    }
+}
@@ -0,0 +1,63 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, CellLevel, ChartContext, ChartData, ChartQuery, ChartView, GraphAggregation};
+use crate::domain::models::visualization_data_graph::FlowFunnelQuery;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::flow_funnel::{flow_funnel, stages_from_query, DEFAULT_FUNNEL_STAGES};
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::AppError;
+
+/// Funil das visitas entre os fluxos (`ifrotabelanome`), ligando os registros da mesma visita pela chave
+/// gerada na importação. As visitas são contadas pela combinação de fluxos por que passaram
+pub struct FlowFunnel;
+
+impl FlowFunnel {
+    /// Funil de cada período pelas etapas informadas, no geral, por dia da semana e por hora de chegada
+    pub fn present_with_stages(data: &Map<String, Value>, stages: &[String]) -> Value {
+        flow_funnel(data, stages)
+    }
+}
+
+#[async_trait]
+impl GraphAggregation for FlowFunnel {
+    fn name(&self) -> &'static str {
+        "flow_funnel"
+    }
+
+    fn slug(&self) -> &'static str {
+        "flow-funnel"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifrotabelanome", "ifrovisitachave", "ifrodiasemana", "ifrohoraatendimento"]
+    }
+
+    // Conversões e perdas dependem das contagens absolutas de visitas
+    fn normalizable(&self) -> bool {
+        false
+    }
+
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Stage
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_visits_by_competencia_stages_and_arrival(ctx.unidade_id, ctx.competencias).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_flow_funnel(counts))
+    }
+
+    fn present(&self, data: Map<String, Value>) -> Value {
+        let stages: Vec<String> = DEFAULT_FUNNEL_STAGES.iter().map(|stage| stage.to_string()).collect();
+        Self::present_with_stages(&data, &stages)
+    }
+
+    // Etapas escolhidas na consulta (`etapas`)
+    async fn present_query(&self, data: Map<String, Value>, query: &ChartQuery<'_>) -> Result<ChartView, AppError> {
+        let stages = stages_from_query(&query.parse::<FlowFunnelQuery>()?)?;
+
+        Ok(query.suppressed(self.cell_level(), Self::present_with_stages(&data, &stages)))
+    }
+}
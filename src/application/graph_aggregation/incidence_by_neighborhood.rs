@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, CellLevel, ChartContext, ChartData, ChartQuery, ChartView, GraphAggregation};
+use crate::domain::models::incidence::IncidenceQuery;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::utils::incidence::incidence_rates;
+use crate::AppError;
+
+/// Atendimentos por bairro, queixa principal e idade simples, base das taxas de incidência
+/// (brutas e padronizadas por idade) calculadas com a população de referência dos bairros
+pub struct IncidenceByNeighborhood;
+
+#[async_trait]
+impl GraphAggregation for IncidenceByNeighborhood {
+    fn name(&self) -> &'static str {
+        "incidence_by_neighborhood"
+    }
+
+    fn slug(&self) -> &'static str {
+        "incidence-rates-by-neighborhood"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifropacientebairro", "ifropacientequeixaprincipal", "ifropacienteidade"]
+    }
+
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Neighborhood
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_neighborhood_complaint_and_age(
+            &ctx.rules.enderecos_excluidos,
+            &ctx.rules.bairros_genericos,
+            ctx.unidade_id,
+            ctx.competencias
+        ).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_incidence_by_neighborhood(counts))
+    }
+
+    // Taxas brutas e padronizadas por idade da doença da consulta (`doenca`), com a população por bairro cadastrada
+    async fn present_query(&self, data: Map<String, Value>, query: &ChartQuery<'_>) -> Result<ChartView, AppError> {
+        let params: IncidenceQuery = query.parse()?;
+        let populations = query.neighborhood_populations().await?;
+
+        let rates = incidence_rates(&data, &populations, params.doenca.as_deref())
+            .map_err(|e| AppError::BadRequest(format!("Error: invalid neighborhood population for unit {}: {}", query.unidade_id, e)))?;
+
+        Ok(query.suppressed(self.cell_level(), rates))
+    }
+}
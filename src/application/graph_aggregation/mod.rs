@@ -0,0 +1,262 @@
+pub mod registry;
+pub mod appointments_per_month;
+pub mod appointments_per_flow;
+pub mod patients_ages;
+pub mod calls_per_day_of_the_week;
+pub mod services_by_hour_group;
+pub mod visits_per_nurse;
+pub mod visits_per_doctor;
+pub mod average_time_per_doctor;
+pub mod disease_heat_map;
+pub mod neighborhood_heat_map;
+pub mod attendance_forecast;
+pub mod doctor_staffing;
+pub mod weekday_hour_matrix;
+pub mod age_pyramid;
+pub mod age_band_by_disease;
+pub mod service_time_per_doctor;
+pub mod disease_hotspots;
+pub mod incidence_by_neighborhood;
+pub mod procedure_ranking;
+pub mod municipality_of_origin;
+pub mod flow_funnel;
+
+use actix_web::web;
+use async_trait::async_trait;
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::error::Error;
+
+use crate::domain::models::data_upa::GroupedCount;
+use crate::domain::models::incidence::NeighborhoodPopulation;
+use crate::domain::models::unit_chart_rules::UnitChartRules;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::domain::repositories::unit_population::UnitPopulationRepository;
+use crate::infrastructure::repositories::data_upa_repository::PgDataRepository;
+use crate::infrastructure::repositories::unit_population_repository::PgUnitPopulationRepository;
+use crate::utils::geojson::wants_geojson;
+use crate::utils::graph_slices::split_counts_by_competencia;
+use crate::utils::incidence::{neighborhood_totals, rate_requested, with_rates};
+use crate::utils::small_cells::{merge_small_professionals, suppress_small_cells, suppress_small_stages};
+use crate::AppError;
+
+pub use registry::GraphRegistry;
+
+/// Resultado do cálculo de um gráfico
+pub enum ChartData {
+    /// Dados separados por competência, combinados depois com as fatias já armazenadas
+    Slices(HashMap<String, Value>),
+    /// Dados completos do gráfico, para valores que não podem ser somados (médias)
+    Complete(Value),
+}
+
+/// Nível de detalhe das contagens apresentadas, que define a supressão de células pequenas
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CellLevel {
+    /// Contagens agregadas da unidade, sem supressão
+    Aggregate,
+    /// Contagens por bairro, local ou categoria (município, procedimento), removidas abaixo do mínimo
+    Neighborhood,
+    /// Contagens por profissional, somadas em "outros" abaixo do mínimo
+    Professional,
+    /// Visitas por etapa de um funil, ocultadas a partir da primeira etapa abaixo do mínimo
+    Stage,
+}
+
+impl CellLevel {
+    /// Suprime as células abaixo de `min_cell_size`, retornando quantas foram suprimidas
+    pub fn suppress(self, data: &mut Value, min_cell_size: i64) -> usize {
+        match self {
+            CellLevel::Aggregate => 0,
+            CellLevel::Neighborhood => suppress_small_cells(data, min_cell_size),
+            CellLevel::Professional => merge_small_professionals(data, min_cell_size),
+            CellLevel::Stage => suppress_small_stages(data, min_cell_size),
+        }
+    }
+}
+
+/// Dados disponíveis para o cálculo de um gráfico em uma unidade
+pub struct ChartContext<'a> {
+    pub repo: &'a PgDataRepository,
+    pub unidade_id: i32,
+    /// Competências a recalcular. `None` recalcula todas
+    pub competencias: Option<&'a [String]>,
+    /// Regras cadastradas da unidade (endereços e bairros excluídos dos mapas)
+    pub rules: &'a UnitChartRules,
+}
+
+impl ChartContext<'_> {
+    // Busca as colunas da tabela bpa da unidade, limitadas às competências do cálculo
+    pub async fn fetch_columns(&self, columns: &[&str]) -> Result<HashMap<String, Vec<Value>>, Box<dyn Error + Send + Sync>> {
+        let columns: Vec<String> = columns.iter().map(|c| c.to_string()).collect();
+
+        match self.competencias {
+            Some(competencias) => self.repo.fetch_columns_by_name_with_filter_and_competencias(
+                "bpa",
+                &columns,
+                "ifrounidadeid",
+                self.unidade_id,
+                competencias
+            ).await,
+            None => self.repo.fetch_columns_by_name_with_filter("bpa", &columns, "ifrounidadeid", self.unidade_id).await,
+        }
+    }
+
+    // Nomes de profissionais que não devem aparecer nos gráficos
+    pub async fn excluded_names(&self, table: &str) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        let data = self.repo.fetch_all_data(table).await?;
+
+        Ok(data.get("ifroprofissionalnome")
+            .map(|values| values.iter()
+                .filter_map(|v| v.as_str().map(String::from))
+                .collect())
+            .unwrap_or_default())
+    }
+}
+
+/// Parâmetros da URL de um gráfico na rota genérica, interpretados conforme o gráfico consultado
+pub fn parse_chart_query<T: DeserializeOwned>(query_string: &str) -> Result<T, AppError> {
+    web::Query::<T>::from_query(query_string)
+        .map(web::Query::into_inner)
+        .map_err(|e| AppError::BadRequest(format!("Error: invalid query parameters: {}", e)))
+}
+
+/// Consulta de um gráfico na rota genérica
+pub struct ChartQuery<'a> {
+    /// Parâmetros da URL (`from`, `to` e os próprios do gráfico)
+    pub query_string: &'a str,
+    /// Cabeçalho `Accept` da requisição
+    pub accept: Option<&'a str>,
+    /// Contagens entre 1 e `min_cell_size - 1` são suprimidas
+    pub min_cell_size: i64,
+    pub unidade_id: i32,
+    /// População por bairro cadastrada, usada nas taxas por habitante
+    pub population_repo: &'a PgUnitPopulationRepository,
+}
+
+impl ChartQuery<'_> {
+    /// Parâmetros próprios do gráfico, no formato de consulta informado
+    pub fn parse<T: DeserializeOwned>(&self) -> Result<T, AppError> {
+        parse_chart_query(self.query_string)
+    }
+
+    /// Indica se a resposta deve ser GeoJSON, pelo parâmetro `format` ou pelo cabeçalho `Accept`
+    pub fn wants_geojson(&self, format: Option<&str>) -> Result<bool, AppError> {
+        wants_geojson(format, self.accept)
+    }
+
+    /// População por bairro e faixa etária cadastrada para a unidade
+    pub async fn neighborhood_populations(&self) -> Result<Vec<NeighborhoodPopulation>, AppError> {
+        let populations = self.population_repo.fetch_neighborhood_populations(self.unidade_id).await
+            .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+        if populations.is_empty() {
+            return Err(AppError::NotFound(format!("Neighborhood population not registered for unit {}", self.unidade_id)));
+        }
+
+        Ok(populations)
+    }
+
+    /// Mapa de calor com a taxa por mil habitantes de cada bairro quando `medida=taxa` (padrão: casos)
+    pub async fn with_measure(&self, data: Value, medida: Option<&str>) -> Result<Value, AppError> {
+        if !rate_requested(medida)? {
+            return Ok(data);
+        }
+
+        let populations = self.neighborhood_populations().await?;
+        Ok(with_rates(&data, &neighborhood_totals(&populations)))
+    }
+
+    /// Suprime as células pequenas dos dados formatados, conforme o nível das contagens
+    pub fn suppressed(&self, level: CellLevel, mut data: Value) -> ChartView {
+        let suppressed = level.suppress(&mut data, self.min_cell_size);
+        ChartView { data, level, suppressed, geojson: None }
+    }
+}
+
+/// Dados de um gráfico formatados para a resposta, com as células pequenas já suprimidas
+pub struct ChartView {
+    pub data: Value,
+    pub level: CellLevel,
+    pub suppressed: usize,
+    /// Nomes das chaves de cada nível até o bairro, quando a resposta é uma FeatureCollection GeoJSON
+    pub geojson: Option<&'static [&'static str]>,
+}
+
+impl ChartView {
+    /// Resposta em GeoJSON quando `geojson` é verdadeiro, com as propriedades de cada nível
+    pub fn with_geojson(self, geojson: bool, properties: &'static [&'static str]) -> Self {
+        Self { geojson: geojson.then_some(properties), ..self }
+    }
+}
+
+/// Gráfico calculado a partir da tabela bpa.
+///
+/// Cada implementação registrada em [`GraphRegistry`] é recalculada pelo
+/// `UpdateGraphDataService`, armazenada com o identificador retornado por `name`
+/// e exposta na rota `/api/data/user/{user_id}/unit/{unit_id}/chart/{slug}`.
+#[async_trait]
+pub trait GraphAggregation: Send + Sync {
+    /// Identificador usado no armazenamento dos dados processados
+    fn name(&self) -> &'static str;
+
+    /// Nome do gráfico na rota genérica
+    fn slug(&self) -> &'static str;
+
+    /// Colunas da tabela bpa usadas no cálculo
+    fn required_columns(&self) -> &'static [&'static str];
+
+    /// Indica se a unidade suporta o gráfico, conforme as regras cadastradas
+    fn applies_to_unit(&self, rules: &UnitChartRules) -> bool {
+        rules.supports(self.slug())
+    }
+
+    /// Indica se o gráfico pode ser combinado a partir de fatias por competência. Gráficos combináveis
+    /// armazenam apenas contagens (por idade simples, minuto, local, código), que podem ser somadas
+    /// entre competências para qualquer período consultado
+    fn mergeable(&self) -> bool {
+        true
+    }
+
+    /// Indica se os valores armazenados podem ser multiplicados por uma constante (participação ou
+    /// taxa por habitante) antes da apresentação, como na comparação entre unidades
+    fn normalizable(&self) -> bool {
+        self.mergeable()
+    }
+
+    /// Nível das contagens na resposta, usado na supressão de células pequenas
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Aggregate
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>>;
+
+    /// Formata os dados armazenados para a resposta da rota. Valores derivados das contagens (taxas,
+    /// percentuais, rankings, faixas e estatísticas) são calculados aqui, sobre as contagens já somadas
+    /// do período, assim como os que dependem de parâmetros da consulta (faixas etárias, tamanho do
+    /// ranking, etapas do funil, remoção de valores extremos)
+    fn present(&self, data: Map<String, Value>) -> Value {
+        Value::Object(data)
+    }
+
+    /// Formata os dados para a rota genérica com os parâmetros próprios do gráfico na consulta e suprime
+    /// as células pequenas. Gráficos sem parâmetros próprios usam `present` e o nível de `cell_level`
+    async fn present_query(&self, data: Map<String, Value>, query: &ChartQuery<'_>) -> Result<ChartView, AppError> {
+        Ok(query.suppressed(self.cell_level(), self.present(data)))
+    }
+}
+
+// Monta as fatias por competência a partir das contagens agregadas no banco
+pub fn slices_from_counts(
+    counts: Vec<GroupedCount>,
+    build: impl Fn(&[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>>,
+) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+    let mut slices = HashMap::new();
+
+    for (competencia, slice_counts) in split_counts_by_competencia(counts) {
+        slices.insert(competencia, build(&slice_counts)?);
+    }
+
+    Ok(ChartData::Slices(slices))
+}
@@ -0,0 +1,42 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, CellLevel, ChartContext, ChartData, GraphAggregation};
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::utils::municipalities::attendances_by_municipality;
+
+/// Atendimentos por município de residência do paciente, para dimensionar a demanda regional da unidade.
+/// Os municípios são armazenados pelo código IBGE de 6 dígitos e os nomes resolvidos na consulta
+pub struct MunicipalityOfOrigin;
+
+#[async_trait]
+impl GraphAggregation for MunicipalityOfOrigin {
+    fn name(&self) -> &'static str {
+        "attendances_by_municipality_of_origin"
+    }
+
+    fn slug(&self) -> &'static str {
+        "attendances-by-municipality-of-origin"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifromunicipioibge"]
+    }
+
+    // Municípios vizinhos com poucos pacientes identificam pessoas como os bairros
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Neighborhood
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_and_municipality(ctx.unidade_id, ctx.competencias).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_attendances_by_municipality(counts))
+    }
+
+    fn present(&self, data: Map<String, Value>) -> Value {
+        attendances_by_municipality(&data)
+    }
+}
@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, CellLevel, ChartContext, ChartData, ChartQuery, ChartView, GraphAggregation};
+use crate::domain::models::visualization_data_graph::HeatMapOutputQuery;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::AppError;
+
+/// Mapa de calor com a quantidade de atendimentos por bairro
+pub struct NeighborhoodHeatMap;
+
+impl NeighborhoodHeatMap {
+    /// Nomes das chaves de cada nível até o bairro nas propriedades dos pontos GeoJSON
+    pub const GEOJSON_PROPERTIES: &'static [&'static str] = &["bairro"];
+}
+
+#[async_trait]
+impl GraphAggregation for NeighborhoodHeatMap {
+    fn name(&self) -> &'static str {
+        "heat_map_with_the_number_of_medical_appointments_by_neighborhood"
+    }
+
+    fn slug(&self) -> &'static str {
+        "heat-map-with-the-number-of-medical-appointments-by-neighborhood"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifropacienteendereco", "ifropacientebairro", "ifropacientelatitude", "ifropacientelongitude"]
+    }
+
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Neighborhood
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_and_location(
+            None,
+            &ctx.rules.enderecos_excluidos,
+            &ctx.rules.bairros_genericos,
+            ctx.unidade_id,
+            ctx.competencias
+        ).await?;
+
+        slices_from_counts(counts, |counts| {
+            DataProcessingForGraphPlotting.create_dict_to_heat_map_with_the_number_of_medical_appointments_by_neighborhood(counts)
+        })
+    }
+
+    // `format=geojson` (ou `Accept: application/geo+json`) retorna uma FeatureCollection com um ponto por bairro,
+    // e `medida=taxa` acrescenta a taxa por mil habitantes de cada bairro
+    async fn present_query(&self, data: Map<String, Value>, query: &ChartQuery<'_>) -> Result<ChartView, AppError> {
+        let output: HeatMapOutputQuery = query.parse()?;
+        let geojson = query.wants_geojson(output.format.as_deref())?;
+        let data = query.with_measure(self.present(data), output.medida.as_deref()).await?;
+
+        Ok(query.suppressed(self.cell_level(), data).with_geojson(geojson, Self::GEOJSON_PROPERTIES))
+    }
+}
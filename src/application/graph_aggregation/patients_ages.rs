@@ -0,0 +1,39 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::error::Error;
+
+use super::{ChartContext, ChartData, GraphAggregation};
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::utils::graph_slices::split_by_competencia;
+
+/// Distribuição das idades dos pacientes por faixa etária
+pub struct PatientsAges;
+
+#[async_trait]
+impl GraphAggregation for PatientsAges {
+    fn name(&self) -> &'static str {
+        "distribuition_of_patients_ages"
+    }
+
+    fn slug(&self) -> &'static str {
+        "distribuition-of-patients-ages"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifropacienteidade"]
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let raw_data = ctx.fetch_columns(self.required_columns()).await?;
+
+        let mut slices = HashMap::new();
+        for (competencia, slice_data) in split_by_competencia(&raw_data) {
+            let data = DataProcessingForGraphPlotting
+                .create_dict_to_distribuition_of_patients_ages_from_raw(&slice_data)
+                .await?;
+            slices.insert(competencia, data);
+        }
+
+        Ok(ChartData::Slices(slices))
+    }
+}
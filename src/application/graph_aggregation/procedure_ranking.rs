@@ -0,0 +1,57 @@
+use async_trait::async_trait;
+use serde_json::{Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, CellLevel, ChartContext, ChartData, ChartQuery, ChartView, GraphAggregation};
+use crate::domain::models::visualization_data_graph::ProcedureRankingQuery;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::utils::procedures::{procedure_ranking, top_from_query, DEFAULT_TOP_PROCEDURES};
+use crate::AppError;
+
+/// Procedimentos SUS mais realizados por competência, para a conferência da produção com o SIA
+pub struct ProcedureRanking;
+
+impl ProcedureRanking {
+    /// Os `top` procedimentos de cada período, com quantidade e participação
+    pub fn present_with_top(data: &Map<String, Value>, top: usize) -> Value {
+        procedure_ranking(data, top)
+    }
+}
+
+#[async_trait]
+impl GraphAggregation for ProcedureRanking {
+    fn name(&self) -> &'static str {
+        "procedure_ranking"
+    }
+
+    fn slug(&self) -> &'static str {
+        "procedure-ranking"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifroprocedimentosuscd", "ifroprocedimentonome"]
+    }
+
+    // Procedimentos raros podem identificar o paciente
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Neighborhood
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_and_procedure(ctx.unidade_id, ctx.competencias).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_procedure_ranking(counts))
+    }
+
+    fn present(&self, data: Map<String, Value>) -> Value {
+        Self::present_with_top(&data, DEFAULT_TOP_PROCEDURES)
+    }
+
+    // Quantidade de procedimentos da consulta (`top`)
+    async fn present_query(&self, data: Map<String, Value>, query: &ChartQuery<'_>) -> Result<ChartView, AppError> {
+        let top = top_from_query(&query.parse::<ProcedureRankingQuery>()?)?;
+
+        Ok(query.suppressed(self.cell_level(), Self::present_with_top(&data, top)))
+    }
+}
@@ -0,0 +1,71 @@
+use super::age_band_by_disease::AgeBandByDisease;
+use super::age_pyramid::AgePyramid;
+use super::appointments_per_flow::AppointmentsPerFlow;
+use super::appointments_per_month::AppointmentsPerMonth;
+use super::attendance_forecast::AttendanceForecast;
+use super::average_time_per_doctor::AverageTimePerDoctor;
+use super::calls_per_day_of_the_week::CallsPerDayOfTheWeek;
+use super::disease_heat_map::DiseaseHeatMap;
+use super::disease_hotspots::DiseaseHotspots;
+use super::doctor_staffing::DoctorStaffing;
+use super::flow_funnel::FlowFunnel;
+use super::incidence_by_neighborhood::IncidenceByNeighborhood;
+use super::municipality_of_origin::MunicipalityOfOrigin;
+use super::neighborhood_heat_map::NeighborhoodHeatMap;
+use super::patients_ages::PatientsAges;
+use super::procedure_ranking::ProcedureRanking;
+use super::service_time_per_doctor::ServiceTimePerDoctor;
+use super::services_by_hour_group::ServicesByHourGroup;
+use super::visits_per_doctor::VisitsPerDoctor;
+use super::visits_per_nurse::VisitsPerNurse;
+use super::weekday_hour_matrix::WeekdayHourMatrix;
+use super::GraphAggregation;
+
+/// Gráficos disponíveis para recálculo, armazenamento e consulta
+pub struct GraphRegistry {
+    charts: Vec<Box<dyn GraphAggregation>>,
+}
+
+impl GraphRegistry {
+    pub fn new() -> Self {
+        Self {
+            charts: vec![
+                Box::new(AppointmentsPerMonth),
+                Box::new(AppointmentsPerFlow),
+                Box::new(PatientsAges),
+                Box::new(CallsPerDayOfTheWeek),
+                Box::new(ServicesByHourGroup),
+                Box::new(VisitsPerNurse),
+                Box::new(VisitsPerDoctor),
+                Box::new(AverageTimePerDoctor),
+                Box::new(DiseaseHeatMap),
+                Box::new(NeighborhoodHeatMap),
+                Box::new(AttendanceForecast),
+                Box::new(DoctorStaffing),
+                Box::new(WeekdayHourMatrix),
+                Box::new(AgePyramid),
+                Box::new(AgeBandByDisease),
+                Box::new(ServiceTimePerDoctor),
+                Box::new(DiseaseHotspots),
+                Box::new(IncidenceByNeighborhood),
+                Box::new(ProcedureRanking),
+                Box::new(MunicipalityOfOrigin),
+                Box::new(FlowFunnel),
+            ],
+        }
+    }
+
+    pub fn charts(&self) -> impl Iterator<Item = &dyn GraphAggregation> {
+        self.charts.iter().map(|chart| chart.as_ref())
+    }
+
+    pub fn find_by_slug(&self, slug: &str) -> Option<&dyn GraphAggregation> {
+        self.charts().find(|chart| chart.slug() == slug)
+    }
+}
+
+impl Default for GraphRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
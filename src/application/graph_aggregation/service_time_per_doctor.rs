@@ -0,0 +1,83 @@
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, CellLevel, ChartContext, ChartData, ChartQuery, ChartView, GraphAggregation};
+use crate::domain::models::service_time::ServiceTimeQuery;
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+use crate::utils::service_time::{bin_width_from_query, histogram_requested, service_time_histograms, service_time_summaries, OutlierRule};
+use crate::AppError;
+
+/// Distribuição dos tempos de atendimento por médico, com as durações contadas por minuto
+pub struct ServiceTimePerDoctor;
+
+impl ServiceTimePerDoctor {
+    /// Média, mediana, p75, p90 e quantidade por médico e período
+    pub fn present_summaries(data: &Map<String, Value>, rule: &OutlierRule) -> Value {
+        service_time_summaries(data, rule)
+    }
+}
+
+#[async_trait]
+impl GraphAggregation for ServiceTimePerDoctor {
+    fn name(&self) -> &'static str {
+        "service_time_per_doctor"
+    }
+
+    fn slug(&self) -> &'static str {
+        "service-time-stats-per-doctor"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifrohoraatendimento", "ifroprofissionalcbods", "ifroprofissionalnome", "ifrotabelanome"]
+    }
+
+    // As estatísticas são calculadas sobre contagens inteiras por minuto
+    fn normalizable(&self) -> bool {
+        false
+    }
+
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Professional
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let non_doctors = ctx.excluded_names("non_doctors").await?;
+        let counts = ctx.repo.count_by_competencia_professional_and_duration(
+            &["MEDICO CLINICO", "MEDICO CIRURGIAO GERAL"],
+            "ConsultaMedica",
+            &non_doctors,
+            ctx.unidade_id,
+            ctx.competencias
+        ).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_service_time_distribution_per_doctor(counts))
+    }
+
+    fn present(&self, data: Map<String, Value>) -> Value {
+        Self::present_summaries(&data, &OutlierRule::default_rule())
+    }
+
+    // Remoção de valores extremos da consulta; `visao=histograma` retorna os histogramas dos mesmos dados
+    async fn present_query(&self, data: Map<String, Value>, query: &ChartQuery<'_>) -> Result<ChartView, AppError> {
+        let params: ServiceTimeQuery = query.parse()?;
+        let rule = OutlierRule::from_query(&params)?;
+
+        if !histogram_requested(&params)? {
+            return Ok(query.suppressed(self.cell_level(), Self::present_summaries(&data, &rule)));
+        }
+
+        // A supressão é aplicada aos histogramas de cada médico, fora da largura das faixas
+        let width = bin_width_from_query(&params)?;
+        let histograms = query.suppressed(self.cell_level(), service_time_histograms(&data, &rule, width));
+
+        Ok(ChartView {
+            data: json!({
+                "largura_faixa": width,
+                "medicos": histograms.data,
+            }),
+            ..histograms
+        })
+    }
+}
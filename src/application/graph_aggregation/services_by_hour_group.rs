@@ -0,0 +1,30 @@
+use async_trait::async_trait;
+use std::error::Error;
+
+use super::{slices_from_counts, ChartContext, ChartData, GraphAggregation};
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+
+/// Atendimentos de médicos clínicos por grupo de duas horas
+pub struct ServicesByHourGroup;
+
+#[async_trait]
+impl GraphAggregation for ServicesByHourGroup {
+    fn name(&self) -> &'static str {
+        "distribution_of_services_by_hour_group"
+    }
+
+    fn slug(&self) -> &'static str {
+        "distribution-of-services-by-hour-group"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifrohoraatendimento", "ifroprofissionalcbods"]
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_and_hour("MEDICO CLINICO", ctx.unidade_id, ctx.competencias).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_distribution_of_services_by_hour_group(counts))
+    }
+}
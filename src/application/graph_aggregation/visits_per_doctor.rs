@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use std::error::Error;
+
+use super::{slices_from_counts, CellLevel, ChartContext, ChartData, GraphAggregation};
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+
+/// Consultas médicas por médico clínico, exceto os nomes da tabela non_doctors
+pub struct VisitsPerDoctor;
+
+#[async_trait]
+impl GraphAggregation for VisitsPerDoctor {
+    fn name(&self) -> &'static str {
+        "number_of_visits_per_doctor"
+    }
+
+    fn slug(&self) -> &'static str {
+        "number-of-visits-per-doctor"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifroprofissionalcbods", "ifroprofissionalnome", "ifrotabelanome"]
+    }
+
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Professional
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let non_doctors = ctx.excluded_names("non_doctors").await?;
+        let counts = ctx.repo.count_by_competencia_and_professional(
+            "MEDICO CLINICO",
+            "ConsultaMedica",
+            &non_doctors,
+            ctx.unidade_id,
+            ctx.competencias
+        ).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_number_of_visits_per_doctor(counts))
+    }
+}
@@ -0,0 +1,41 @@
+use async_trait::async_trait;
+use std::error::Error;
+
+use super::{slices_from_counts, CellLevel, ChartContext, ChartData, GraphAggregation};
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+
+/// Acolhimentos realizados por enfermeiro, exceto os nomes da tabela non_nurse
+pub struct VisitsPerNurse;
+
+#[async_trait]
+impl GraphAggregation for VisitsPerNurse {
+    fn name(&self) -> &'static str {
+        "number_of_visits_per_nurse"
+    }
+
+    fn slug(&self) -> &'static str {
+        "number-of-visits-per-nurse"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifroprofissionalcbods", "ifroprofissionalnome", "ifrotabelanome"]
+    }
+
+    fn cell_level(&self) -> CellLevel {
+        CellLevel::Professional
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let non_nurse = ctx.excluded_names("non_nurse").await?;
+        let counts = ctx.repo.count_by_competencia_and_professional(
+            "ENFERMEIRO",
+            "Acolhimento",
+            &non_nurse,
+            ctx.unidade_id,
+            ctx.competencias
+        ).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_number_of_visits_per_nurse(counts))
+    }
+}
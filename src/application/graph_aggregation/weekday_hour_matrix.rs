@@ -0,0 +1,40 @@
+use async_trait::async_trait;
+use serde_json::{json, Map, Value};
+use std::error::Error;
+
+use super::{slices_from_counts, ChartContext, ChartData, GraphAggregation};
+use crate::domain::repositories::data_upa::DataRepository;
+use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
+
+/// Matriz de atendimentos por dia da semana e hora, para cada categoria profissional
+pub struct WeekdayHourMatrix;
+
+#[async_trait]
+impl GraphAggregation for WeekdayHourMatrix {
+    fn name(&self) -> &'static str {
+        "weekday_hour_matrix"
+    }
+
+    fn slug(&self) -> &'static str {
+        "attendances-by-weekday-and-hour"
+    }
+
+    fn required_columns(&self) -> &'static [&'static str] {
+        &["ifrocompetencia", "ifrodiasemana", "ifrohoraatendimento", "ifroprofissionalcbods"]
+    }
+
+    async fn compute(&self, ctx: &ChartContext<'_>) -> Result<ChartData, Box<dyn Error + Send + Sync>> {
+        let counts = ctx.repo.count_by_competencia_weekday_and_hour(ctx.unidade_id, ctx.competencias).await?;
+
+        slices_from_counts(counts, |counts| DataProcessingForGraphPlotting.create_dict_to_weekday_hour_matrix(counts))
+    }
+
+    // Acrescenta os rótulos das linhas (dias) e colunas (horas) das matrizes
+    fn present(&self, data: Map<String, Value>) -> Value {
+        json!({
+            "dias": ["segunda-feira", "terça-feira", "quarta-feira", "quinta-feira", "sexta-feira", "sábado", "domingo"],
+            "horas": (0..24).collect::<Vec<u32>>(),
+            "categorias": data,
+        })
+    }
+}
@@ -0,0 +1,166 @@
+use actix_web::{web, HttpResponse};
+use log::{error, info};
+
+use crate::domain::models::health_unit::{
+    HealthUnitQuery,
+    HealthUnitRequest,
+    UNIT_TYPE_HOSPITAL,
+    UNIT_TYPE_UBS,
+    UNIT_TYPE_UPA,
+};
+use crate::domain::repositories::health_unit::HealthUnitRepository;
+use crate::infrastructure::repositories::health_unit_repository::PgHealthUnitRepository;
+use crate::utils::response::ApiResponse;
+use crate::AppError;
+
+const ALLOWED_UNIT_TYPES: [&str; 3] = [UNIT_TYPE_UPA, UNIT_TYPE_UBS, UNIT_TYPE_HOSPITAL];
+
+pub struct HealthUnitService {
+    repo: web::Data<PgHealthUnitRepository>,
+}
+
+impl HealthUnitService {
+    pub fn new(repo: web::Data<PgHealthUnitRepository>) -> Self {
+        Self { repo }
+    }
+
+    pub async fn units(&self, query: HealthUnitQuery) -> Result<HttpResponse, AppError> {
+        let units = self.repo.fetch_units(query.ativo).await
+            .map_err(|e| {
+                error!("Error fetching health units: {}", e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        Ok(ApiResponse::success(units).into_response())
+    }
+
+    pub async fn unit(&self, unidade_id: i32) -> Result<HttpResponse, AppError> {
+        let unit = self.repo.find_unit(unidade_id).await
+            .map_err(|e| {
+                error!("Error fetching health unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?
+            .ok_or_else(|| AppError::NotFound(format!("Health unit {} not found", unidade_id)))?;
+
+        Ok(ApiResponse::success(unit).into_response())
+    }
+
+    pub async fn create_unit(&self, unit: HealthUnitRequest) -> Result<HttpResponse, AppError> {
+        let unidade_id = unit.ifrounidadeid
+            .ok_or_else(|| AppError::BadRequest("Error: 'ifrounidadeid' is required".to_string()))?;
+        let unit = validate_unit(unit)?;
+
+        let existing = self.repo.find_unit(unidade_id).await
+            .map_err(|e| {
+                error!("Error fetching health unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        if existing.is_some() {
+            return Err(AppError::BadRequest(format!("Error: health unit {} already exists", unidade_id)));
+        }
+
+        let created = self.repo.create_unit(unidade_id, &unit).await
+            .map_err(|e| {
+                error!("Error creating health unit {}: {}", unidade_id, e);
+                unit_write_error(e)
+            })?;
+
+        info!("Health unit {} created", unidade_id);
+        Ok(ApiResponse::created(created).into_response())
+    }
+
+    pub async fn update_unit(&self, unidade_id: i32, unit: HealthUnitRequest) -> Result<HttpResponse, AppError> {
+        let unit = validate_unit(unit)?;
+
+        let updated = self.repo.update_unit(unidade_id, &unit).await
+            .map_err(|e| {
+                error!("Error updating health unit {}: {}", unidade_id, e);
+                unit_write_error(e)
+            })?
+            .ok_or_else(|| AppError::NotFound(format!("Health unit {} not found", unidade_id)))?;
+
+        info!("Health unit {} updated", unidade_id);
+        Ok(ApiResponse::updated(updated).into_response())
+    }
+
+    pub async fn delete_unit(&self, unidade_id: i32) -> Result<HttpResponse, AppError> {
+        let deleted = self.repo.delete_unit(unidade_id).await
+            .map_err(|e| {
+                error!("Error deleting health unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        if !deleted {
+            return Err(AppError::NotFound(format!("Health unit {} not found", unidade_id)));
+        }
+
+        info!("Health unit {} deleted", unidade_id);
+        Ok(ApiResponse::<()>::deleted().into_response())
+    }
+}
+
+// CNES repetido em outra unidade
+fn unit_write_error(e: sqlx::Error) -> AppError {
+    match &e {
+        sqlx::Error::Database(db) if db.code().as_deref() == Some("23505") => {
+            AppError::BadRequest("Error: 'cnes' is already registered for another health unit".to_string())
+        },
+        _ => AppError::DatabaseError(e.to_string()),
+    }
+}
+
+// Normaliza os campos do cadastro: textos sem espaços nas pontas, tipo em maiúsculas e capacidades sem repetição
+fn validate_unit(unit: HealthUnitRequest) -> Result<HealthUnitRequest, AppError> {
+    let trimmed = |value: Option<String>| value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty());
+
+    let nome = unit.nome.trim().to_string();
+    if nome.is_empty() {
+        return Err(AppError::BadRequest("Error: 'nome' cannot be empty".to_string()));
+    }
+
+    let cnes = trimmed(unit.cnes);
+    if let Some(cnes) = &cnes {
+        if cnes.len() != 7 || !cnes.chars().all(|c| c.is_ascii_digit()) {
+            return Err(AppError::BadRequest(format!("Error: '{}' is not a valid CNES (7 digits)", cnes)));
+        }
+    }
+
+    let tipo = trimmed(unit.tipo).map(|t| t.to_uppercase());
+    if let Some(tipo) = &tipo {
+        if !ALLOWED_UNIT_TYPES.contains(&tipo.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Error: '{}' is not a valid value for 'tipo'. Allowed values are: {}",
+                tipo, ALLOWED_UNIT_TYPES.join(", ")
+            )));
+        }
+    }
+
+    if unit.latitude.is_some() != unit.longitude.is_some() {
+        return Err(AppError::BadRequest("Error: 'latitude' and 'longitude' must be informed together".to_string()));
+    }
+    if unit.latitude.map(|lat| !(-90.0..=90.0).contains(&lat)).unwrap_or(false)
+        || unit.longitude.map(|lon| !(-180.0..=180.0).contains(&lon)).unwrap_or(false) {
+        return Err(AppError::BadRequest("Error: invalid coordinates".to_string()));
+    }
+
+    let mut capacidades: Vec<String> = Vec::new();
+    for capacidade in unit.capacidades {
+        let capacidade = capacidade.trim().to_lowercase();
+        if !capacidade.is_empty() && !capacidades.contains(&capacidade) {
+            capacidades.push(capacidade);
+        }
+    }
+
+    Ok(HealthUnitRequest {
+        ifrounidadeid: unit.ifrounidadeid,
+        cnes,
+        nome,
+        municipio: trimmed(unit.municipio),
+        tipo,
+        latitude: unit.latitude,
+        longitude: unit.longitude,
+        ativo: unit.ativo,
+        capacidades,
+    })
+}
@@ -7,3 +7,8 @@ pub mod update_graph_data_service;
 pub mod visualization_data_service;
 pub mod prediction_service;
 pub mod information_service;
+pub mod reclassification_service;
+pub mod classification_review_service;
+pub mod graph_aggregation;
+pub mod surveillance_service;
+pub mod health_unit_service;
@@ -0,0 +1,228 @@
+use std::collections::HashMap;
+
+use actix_web::{web, HttpResponse};
+use log::{error, info};
+use serde_json::{json, Value};
+use uuid::Uuid;
+
+use crate::application::update_graph_data_service::UpdateGraphDataService;
+use crate::domain::models::reclassification::{
+    CondutaRecord,
+    CreateReclassificationJobDto,
+    RECLASSIFICATION_STATUS_FAILED,
+    RECLASSIFICATION_STATUS_FINISHED,
+    RECLASSIFICATION_STATUS_RUNNING,
+};
+use crate::domain::repositories::reclassification::ReclassificationRepository;
+use crate::infrastructure::repositories::reclassification_repository::PgReclassificationRepository;
+use crate::utils::competencia::{competencia_in_range, validate_competencia};
+use crate::utils::process_data::classify_condutas;
+use crate::utils::response::ApiResponse;
+use crate::AppError;
+
+// Gráficos que dependem da queixa principal classificada
+const CHARTS_AFFECTED_BY_CLASSIFICATION: [&str; 1] = ["heat_map_with_disease_indication"];
+
+// A unidade precisa estar em allowed_health_units de quem fez a requisição
+fn validate_unit_access(unidade_id: i32, allowed_units: &[i64]) -> Result<(), AppError> {
+    if !allowed_units.contains(&(unidade_id as i64)) {
+        return Err(AppError::Forbidden(format!("User does not have access to unit {}", unidade_id)));
+    }
+    Ok(())
+}
+
+pub struct ReclassificationService {
+    repo: web::Data<PgReclassificationRepository>,
+    update_graph_data_service: web::Data<UpdateGraphDataService>,
+}
+
+impl ReclassificationService {
+    pub fn new(
+        repo: web::Data<PgReclassificationRepository>,
+        update_graph_data_service: web::Data<UpdateGraphDataService>,
+    ) -> Self {
+        Self { repo, update_graph_data_service }
+    }
+
+    pub async fn start_job(&self, job: CreateReclassificationJobDto, requested_by: String, allowed_units: &[i64]) -> Result<HttpResponse, AppError> {
+        info!("Starting reclassification job for unit {} ({} - {})", job.unidade_id, job.competencia_inicio, job.competencia_fim);
+
+        validate_unit_access(job.unidade_id, allowed_units)?;
+
+        let start = validate_competencia("competencia_inicio", &job.competencia_inicio)?;
+        let end = validate_competencia("competencia_fim", &job.competencia_fim)?;
+
+        if start > end {
+            return Err(AppError::BadRequest(
+                "Error: 'competencia_inicio' must be before or equal to 'competencia_fim'".to_string()
+            ));
+        }
+
+        // Verifica se a unidade possui dados no intervalo solicitado
+        let competencias = self.repo.fetch_competencias(job.unidade_id).await
+            .map_err(|e| {
+                error!("Error fetching competencias for unit {}: {}", job.unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        if !competencias.iter().any(|c| competencia_in_range(c, start, end)) {
+            return Err(AppError::NotFound(format!(
+                "No data found for unit {} between {} and {}",
+                job.unidade_id, job.competencia_inicio, job.competencia_fim
+            )));
+        }
+
+        let job_id = self.repo.create_job(&job, &requested_by).await
+            .map_err(|e| {
+                error!("Error creating reclassification job: {}", e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        // Executa a reclassificação em segundo plano
+        let repo = self.repo.clone();
+        let update_graph_data_service = self.update_graph_data_service.clone();
+
+        actix_web::rt::spawn(async move {
+            run_reclassification_job(repo, update_graph_data_service, job_id, job, competencias).await;
+        });
+
+        Ok(ApiResponse::created(json!({
+            "job_id": job_id,
+            "status": "pendente"
+        })).into_response())
+    }
+
+    pub async fn get_job(&self, id: Uuid, allowed_units: &[i64]) -> Result<HttpResponse, AppError> {
+        match self.repo.find_job(id).await {
+            Ok(Some(job)) => {
+                validate_unit_access(job.ifrounidadeid, allowed_units)?;
+                Ok(ApiResponse::success(job).into_response())
+            },
+            Ok(None) => Err(AppError::NotFound(format!("Reclassification job {} not found", id))),
+            Err(e) => {
+                error!("Error fetching reclassification job {}: {}", id, e);
+                Err(AppError::DatabaseError(e.to_string()))
+            }
+        }
+    }
+}
+
+async fn run_reclassification_job(
+    repo: web::Data<PgReclassificationRepository>,
+    update_graph_data_service: web::Data<UpdateGraphDataService>,
+    job_id: Uuid,
+    job: CreateReclassificationJobDto,
+    competencias: Vec<String>,
+) {
+    if let Err(e) = repo.update_job_status(job_id, RECLASSIFICATION_STATUS_RUNNING).await {
+        error!("Error updating reclassification job {}: {}", job_id, e);
+    }
+
+    let result = reclassify(&repo, &update_graph_data_service, &job, competencias).await;
+
+    let finished = match result {
+        Ok(summary) => {
+            info!("Reclassification job {} finished", job_id);
+            repo.finish_job(job_id, RECLASSIFICATION_STATUS_FINISHED, Some(summary), None).await
+        },
+        Err(e) => {
+            error!("Reclassification job {} failed: {}", job_id, e);
+            repo.finish_job(job_id, RECLASSIFICATION_STATUS_FAILED, None, Some(e.to_string())).await
+        }
+    };
+
+    if let Err(e) = finished {
+        error!("Error saving result of reclassification job {}: {}", job_id, e);
+    }
+}
+
+async fn reclassify(
+    repo: &PgReclassificationRepository,
+    update_graph_data_service: &UpdateGraphDataService,
+    job: &CreateReclassificationJobDto,
+    competencias: Vec<String>,
+) -> Result<Value, AppError> {
+    // Os valores já foram validados ao criar o job
+    let start = validate_competencia("competencia_inicio", &job.competencia_inicio)?;
+    let end = validate_competencia("competencia_fim", &job.competencia_fim)?;
+
+    let competencias_no_intervalo: Vec<String> = competencias
+        .into_iter()
+        .filter(|c| competencia_in_range(c, start, end))
+        .collect();
+
+    let records = repo.fetch_conduta_records(job.unidade_id, &competencias_no_intervalo).await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+    info!("{} records found for reclassification in unit {}", records.len(), job.unidade_id);
+
+    // A classificação é custosa, então roda fora do executor assíncrono
+    let condutas: Vec<String> = records.iter().map(|r| r.conduta.clone()).collect();
+    let new_complaints = web::block(move || classify_condutas(condutas))
+        .await
+        .map_err(|e| AppError::DataProcessingError(e.to_string()))?
+        .map_err(|e| AppError::DataProcessingError(e.to_string()))?;
+
+    let summary = build_summary(&records, &new_complaints, &competencias_no_intervalo);
+
+    // A confiança é atualizada para todos os registros, a queixa apenas quando muda
+    let classifications: Vec<(String, String, f64)> = records.iter()
+        .zip(new_complaints.iter())
+        .map(|(record, (new_complaint, confidence))| (record.ifroid.clone(), new_complaint.clone(), *confidence))
+        .collect();
+
+    let updated = repo.update_complaints(job.unidade_id, &classifications).await
+        .map_err(|e| AppError::DatabaseError(e.to_string()))?;
+
+    info!("{} records updated in unit {}", updated, job.unidade_id);
+
+    if updated > 0 {
+        // Recalcula os mapas de calor de doenças com a nova classificação
+        update_graph_data_service
+            .update_charts_for_unit(job.unidade_id, &CHARTS_AFFECTED_BY_CLASSIFICATION, &competencias_no_intervalo)
+            .await?;
+    }
+
+    Ok(summary)
+}
+
+// Monta o resumo antes/depois da reclassificação
+fn build_summary(records: &[CondutaRecord], new_complaints: &[(String, f64)], competencias: &[String]) -> Value {
+    let mut before: HashMap<String, i64> = HashMap::new();
+    let mut after: HashMap<String, i64> = HashMap::new();
+    let mut transitions: HashMap<(String, String), i64> = HashMap::new();
+
+    for (record, (new_complaint, _)) in records.iter().zip(new_complaints.iter()) {
+        *before.entry(record.current_complaint.clone()).or_insert(0) += 1;
+        *after.entry(new_complaint.clone()).or_insert(0) += 1;
+
+        if record.current_complaint != *new_complaint {
+            *transitions
+                .entry((record.current_complaint.clone(), new_complaint.clone()))
+                .or_insert(0) += 1;
+        }
+    }
+
+    let changed: i64 = transitions.values().sum();
+
+    let mut transitions_vec: Vec<Value> = transitions
+        .into_iter()
+        .map(|((from, to), quantity)| json!({
+            "de": from,
+            "para": to,
+            "quantidade": quantity
+        }))
+        .collect();
+
+    transitions_vec.sort_by(|a, b| b["quantidade"].as_i64().cmp(&a["quantidade"].as_i64()));
+
+    json!({
+        "competencias": competencias,
+        "total_registros": records.len(),
+        "alterados": changed,
+        "inalterados": records.len() as i64 - changed,
+        "antes": before,
+        "depois": after,
+        "transicoes": transitions_vec
+    })
+}
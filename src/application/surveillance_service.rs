@@ -0,0 +1,117 @@
+use actix_web::{web, HttpResponse};
+use log::{error, info};
+use uuid::Uuid;
+
+use crate::domain::models::surveillance::{
+    AlertQuery,
+    ALERT_SEVERITY_HIGH,
+    ALERT_SEVERITY_LOW,
+    ALERT_SEVERITY_MEDIUM,
+    ALERT_STATUS_ACKNOWLEDGED,
+    ALERT_STATUS_DISMISSED,
+    ALERT_STATUS_OPEN,
+};
+use crate::domain::repositories::surveillance::SurveillanceRepository;
+use crate::infrastructure::repositories::surveillance_repository::PgSurveillanceRepository;
+use crate::utils::competencia::competencias_date_range;
+use crate::utils::response::ApiResponse;
+use crate::utils::surveillance::{detect_signals, SurveillanceWindow};
+use crate::AppError;
+
+const ALLOWED_ALERT_STATUSES: [&str; 3] = [ALERT_STATUS_OPEN, ALERT_STATUS_ACKNOWLEDGED, ALERT_STATUS_DISMISSED];
+const ALLOWED_ALERT_SEVERITIES: [&str; 3] = [ALERT_SEVERITY_LOW, ALERT_SEVERITY_MEDIUM, ALERT_SEVERITY_HIGH];
+
+pub struct SurveillanceService {
+    repo: web::Data<PgSurveillanceRepository>,
+}
+
+impl SurveillanceService {
+    pub fn new(repo: web::Data<PgSurveillanceRepository>) -> Self {
+        Self { repo }
+    }
+
+    // Recalcula os alertas da unidade a partir das contagens semanais por doença e bairro
+    // Com as competências alteradas, apenas as semanas que dependem delas são recalculadas
+    pub async fn run_for_unit(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<u64, AppError> {
+        let mut window = competencias
+            .and_then(competencias_date_range)
+            .map(|(start, end)| SurveillanceWindow::for_dates(start, end));
+
+        // O recálculo parcial não usa semanas anteriores ao histórico da unidade, que o cálculo completo não tem
+        if let Some(partial) = window {
+            let first_day = self.repo.fetch_first_attendance_day(unidade_id).await
+                .map_err(|e| {
+                    error!("Error fetching the first attendance day for unit {}: {}", unidade_id, e);
+                    AppError::DatabaseError(e.to_string())
+                })?;
+
+            window = first_day.map(|first_day| partial.starting_from(first_day));
+        }
+
+        info!("Running surveillance for unit {} ({:?})", unidade_id, window);
+
+        let counts = self.repo.fetch_daily_counts_by_neighborhood(unidade_id, window.map(|w| (w.counts_start, w.end))).await
+            .map_err(|e| {
+                error!("Error fetching surveillance counts for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        let signals = detect_signals(&counts, window.as_ref());
+
+        self.repo.sync_alerts(unidade_id, &signals, window.map(|w| (w.alerts_start, w.end))).await
+            .map_err(|e| {
+                error!("Error saving surveillance alerts for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })
+    }
+
+    pub async fn alerts(&self, unidade_id: i32, mut query: AlertQuery) -> Result<HttpResponse, AppError> {
+        query.status = query.status.map(|s| s.trim().to_lowercase());
+        query.severidade = query.severidade.map(|s| s.trim().to_lowercase());
+        query.doenca = query.doenca.map(|d| d.trim().to_lowercase());
+
+        for (field, value, allowed) in [
+            ("status", &query.status, &ALLOWED_ALERT_STATUSES),
+            ("severidade", &query.severidade, &ALLOWED_ALERT_SEVERITIES),
+        ] {
+            if let Some(value) = value {
+                if !allowed.contains(&value.as_str()) {
+                    return Err(AppError::BadRequest(format!(
+                        "Error: '{}' is not a valid value for '{}'. Allowed values are: {}",
+                        value, field, allowed.join(", ")
+                    )));
+                }
+            }
+        }
+
+        let alerts = self.repo.fetch_alerts(unidade_id, &query).await
+            .map_err(|e| {
+                error!("Error retrieving surveillance alerts for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        Ok(ApiResponse::success(alerts).into_response())
+    }
+
+    pub async fn acknowledge_alert(&self, unidade_id: i32, alert_id: Uuid, updated_by: String) -> Result<HttpResponse, AppError> {
+        self.update_alert_status(unidade_id, alert_id, ALERT_STATUS_ACKNOWLEDGED, updated_by).await
+    }
+
+    pub async fn dismiss_alert(&self, unidade_id: i32, alert_id: Uuid, updated_by: String) -> Result<HttpResponse, AppError> {
+        self.update_alert_status(unidade_id, alert_id, ALERT_STATUS_DISMISSED, updated_by).await
+    }
+
+    async fn update_alert_status(&self, unidade_id: i32, alert_id: Uuid, status: &str, updated_by: String) -> Result<HttpResponse, AppError> {
+        let alert = self.repo.update_alert_status(unidade_id, alert_id, status, &updated_by).await
+            .map_err(|e| {
+                error!("Error updating surveillance alert {}: {}", alert_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?
+            .ok_or_else(|| AppError::NotFound(format!(
+                "Alert {} not found for unit {}", alert_id, unidade_id
+            )))?;
+
+        info!("Surveillance alert {} marked as {} by {}", alert_id, status, updated_by);
+        Ok(ApiResponse::updated(alert).into_response())
+    }
+}
@@ -1,296 +1,265 @@
+use crate::application::graph_aggregation::{ChartContext, ChartData, GraphRegistry};
+use crate::application::surveillance_service::SurveillanceService;
 use crate::domain::repositories::data_upa::DataRepository;
+use crate::domain::repositories::health_unit::HealthUnitRepository;
+use crate::domain::repositories::unit_chart_rules::UnitChartRulesRepository;
 use crate::infrastructure::repositories::data_upa_repository::PgDataRepository;
-use crate::utils::graph_data_processing::DataProcessingForGraphPlotting;
-use crate::utils::process_data::create_dataframe_from_dict;
+use crate::infrastructure::repositories::health_unit_repository::PgHealthUnitRepository;
+use crate::infrastructure::repositories::unit_chart_rules_repository::PgUnitChartRulesRepository;
+use crate::utils::graph_slices::merge_graph_data;
 use crate::{ApiResponse, AppError};
 use actix_web::{web, HttpResponse};
 use log::{info, error};
-use polars::frame::DataFrame;
 use serde_json::{json, Value};
 use std::collections::HashMap;
 
 pub struct UpdateGraphDataService {
     repo: web::Data<PgDataRepository>,
-    data_processing: DataProcessingForGraphPlotting,
+    registry: web::Data<GraphRegistry>,
+    surveillance: web::Data<SurveillanceService>,
+    rules_repo: web::Data<PgUnitChartRulesRepository>,
+    health_unit_repo: web::Data<PgHealthUnitRepository>,
 }
 
 impl UpdateGraphDataService {
-    pub fn new(repo: web::Data<PgDataRepository>) -> Self {
-        Self { 
-            repo, 
-            data_processing: DataProcessingForGraphPlotting {},
-        }
+    pub fn new(
+        repo: web::Data<PgDataRepository>,
+        registry: web::Data<GraphRegistry>,
+        surveillance: web::Data<SurveillanceService>,
+        rules_repo: web::Data<PgUnitChartRulesRepository>,
+        health_unit_repo: web::Data<PgHealthUnitRepository>,
+    ) -> Self {
+        Self { repo, registry, surveillance, rules_repo, health_unit_repo }
     }
     
-    pub async fn update_data(&self) -> Result<HttpResponse, AppError> {
+    pub async fn update_data(&self, full: bool) -> Result<HttpResponse, AppError> {
+        if full {
+            return self.rebuild_all().await;
+        }
+
+        info!("Iniciando atualização incremental de dados para gráficos");
+
+        // Competências importadas ou alteradas desde a última atualização
+        let pending = self.repo.fetch_pending_competencias().await
+            .map_err(|e| {
+                error!("Erro ao buscar competências pendentes: {}", e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        if pending.is_empty() {
+            info!("Nenhuma competência pendente de atualização");
+        }
+
+        for (unidade_id, competencias) in &pending {
+            info!("Unidade {}: atualizando competências {:?}", unidade_id, competencias);
+            self.process_unit(*unidade_id, Some(competencias.as_slice()), None).await?;
+            self.clear_pending(*unidade_id, Some(competencias.as_slice())).await?;
+        }
+
+        Ok(ApiResponse::updated(json!({
+            "modo": "incremental",
+            "competencias_atualizadas": pending
+        })).into_response())
+    }
+
+    // Reconstrói todos os gráficos de todas as unidades
+    async fn rebuild_all(&self) -> Result<HttpResponse, AppError> {
         info!("Iniciando atualização de dados para gráficos para todas unidades");
         
-        // Buscar todas as unidades disponíveis na tabela bpa
-        let unidades = match self.repo.fetch_distinct_values("bpa", "ifrounidadeid").await {
-            Ok(unidades) => {
-                if unidades.is_empty() {
-                    info!("Nenhuma unidade encontrada, usando unidade padrão (2)");
-                    vec![2] // Unidade padrão (UPA Ariquemes)
-                } else {
-                    unidades
-                }
-            },
-            Err(e) => {
-                error!("Erro ao buscar unidades disponíveis: {}", e);
-                // Fallback para unidade padrão
-                vec![2]
-            }
-        };
+        // Unidades ativas do cadastro de unidades de saúde
+        let unidades: Vec<i32> = self.health_unit_repo.fetch_units(Some(true)).await
+            .map_err(|e| {
+                error!("Erro ao buscar unidades de saúde cadastradas: {}", e);
+                AppError::DatabaseError(e.to_string())
+            })?
+            .iter()
+            .map(|unit| unit.ifrounidadeid)
+            .collect();
+
+        if unidades.is_empty() {
+            info!("Nenhuma unidade de saúde ativa cadastrada");
+        }
         
         info!("Encontradas {} unidades para processamento: {:?}", unidades.len(), unidades);
         
         // Para cada unidade, processar todos os gráficos
-        for unidade_id in unidades {
-            info!("Processando dados para unidade {}", unidade_id);
-            
-            // Lista base de parâmetros
-            let mut list_params = vec![
-                // Agendamentos por mês
-                HashMap::from([
-                    ("table", Value::String("bpa".to_string())),
-                    ("column", json!(["ifrocompetencia"])),
-                    ("identifier", Value::String("number_of_appointments_per_month".to_string())),
-                    ("table_json", Value::String("number_of_appointments_per_month".to_string())),
-                    ("method", Value::String("create_dict_to_number_of_appointments_per_month".to_string()))
-                ]),
-                // Agendamentos por fluxo
-                HashMap::from([
-                    ("table", Value::String("bpa".to_string())),
-                    ("column", json!(["ifrocompetencia", "ifrotabelanome"])),
-                    ("identifier", Value::String("number_of_appointments_per_flow".to_string())),
-                    ("table_json", Value::String("number_of_appointments_per_flow".to_string())),
-                    ("method", Value::String("create_dict_to_number_of_appointments_per_flow".to_string()))
-                ]),
-                // Distribuição de idades
-                HashMap::from([
-                    ("table", Value::String("bpa".to_string())),
-                    ("column", json!(["ifrocompetencia", "ifropacienteidade"])),
-                    ("identifier", Value::String("distribuition_of_patients_ages".to_string())),
-                    ("table_json", Value::String("distribuition_of_patients_ages".to_string())),
-                    ("method", Value::String("create_dict_to_distribuition_of_patients_ages".to_string()))
-                ]),
-                // Chamadas por dia da semana
-                HashMap::from([
-                    ("table", Value::String("bpa".to_string())),
-                    ("column", json!(["ifrocompetencia", "ifrodiasemana"])),
-                    ("identifier", Value::String("number_of_calls_per_day_of_the_week".to_string())),
-                    ("table_json", Value::String("number_of_calls_per_day_of_the_week".to_string())),
-                    ("method", Value::String("create_dict_to_number_of_calls_per_day_of_the_week".to_string()))
-                ]),
-                // Serviços por grupo horário
-                HashMap::from([
-                    ("table", Value::String("bpa".to_string())),
-                    ("column", json!(["ifrocompetencia", "ifrohoraatendimento", "ifroprofissionalcbods"])),
-                    ("identifier", Value::String("distribution_of_services_by_hour_group".to_string())),
-                    ("table_json", Value::String("distribution_of_services_by_hour_group".to_string())),
-                    ("method", Value::String("create_dict_to_distribution_of_services_by_hour_group".to_string()))
-                ]),
-                // Visitas por enfermeiro
-                HashMap::from([
-                    ("table", Value::String("bpa".to_string())),
-                    ("column", json!(["ifrocompetencia", "ifroprofissionalid", "ifroprofissionalcbods", "ifroprofissionalnome", "ifrotabelanome"])),
-                    ("identifier", Value::String("number_of_visits_per_nurse".to_string())),
-                    ("table_json", Value::String("number_of_visits_per_nurse".to_string())),
-                    ("method", Value::String("create_dict_to_number_of_visits_per_nurse".to_string()))
-                ]),
-                // Atendimentos por médico
-                HashMap::from([
-                    ("table", Value::String("bpa".to_string())),
-                    ("column", json!(["ifrocompetencia", "ifroprofissionalid", "ifroprofissionalcbods", "ifroprofissionalnome", "ifrotabelanome"])),
-                    ("identifier", Value::String("number_of_visits_per_doctor".to_string())),
-                    ("table_json", Value::String("number_of_visits_per_doctor".to_string())),
-                    ("method", Value::String("create_dict_to_number_of_visits_per_doctor".to_string()))
-                ]),
-                // Tempo médio por médico
-                HashMap::from([
-                    ("table", Value::String("bpa".to_string())),
-                    ("column", json!(["ifrocompetencia", "ifrohoraatendimento", "ifroprofissionalid", "ifroprofissionalcbods", "ifroprofissionalnome", "ifrotabelanome"])),
-                    ("identifier", Value::String("average_time_per_doctor".to_string())),
-                    ("table_json", Value::String("average_time_per_doctor".to_string())),
-                    ("method", Value::String("create_dict_to_average_time_in_minutes_per_doctor".to_string()))
-                ]),
-            ];
-            
-            // Adiciona os parâmetros de mapas de calor se a unidade for diferente de 3
-            if unidade_id != 3 {
-                // Mapa de calor por doença
-                list_params.push(HashMap::from([
-                    ("table", Value::String("bpa".to_string())),
-                    ("column", json!(["ifrocompetencia", "ifropacienteendereco", "ifropacientebairro", "ifropacientequeixaprincipal", "ifropacientelatitude", "ifropacientelongitude"])),
-                    ("identifier", Value::String("heat_map_with_disease_indication".to_string())),
-                    ("table_json", Value::String("heat_map_with_disease_indication".to_string())),
-                    ("method", Value::String("create_dictionary_with_location_and_number_per_disease".to_string()))
-                ]));
-                
-                // Mapa de calor por bairro
-                list_params.push(HashMap::from([
-                    ("table", Value::String("bpa".to_string())),
-                    ("column", json!(["ifrocompetencia", "ifropacienteendereco", "ifropacientebairro", "ifropacientelatitude", "ifropacientelongitude"])),
-                    ("identifier", Value::String("heat_map_with_the_number_of_medical_appointments_by_neighborhood".to_string())),
-                    ("table_json", Value::String("heat_map_with_the_number_of_medical_appointments_by_neighborhood".to_string())),
-                    ("method", Value::String("create_dict_to_heat_map_with_the_number_of_medical_appointments_by_neighborhood".to_string()))
-                ]));
-                
-                info!("Unidade 2 (UPA Ariquemes): adicionados parâmetros para mapas de calor");
-            } else {
-                info!("Unidade {}: mapas de calor não serão processados", unidade_id);
-            }
+        for unidade_id in &unidades {
+            self.process_unit(*unidade_id, None, None).await?;
+            self.clear_pending(*unidade_id, None).await?;
+        }
     
-            // Processa cada parâmetro para a unidade atual
-            for params in &list_params {
-                let table = params["table"].as_str().unwrap();
-                let columns = params["column"].as_array().unwrap()
-                    .iter()
-                    .map(|v| v.as_str().unwrap().to_string())
-                    .collect::<Vec<String>>();
-                let identifier = params["identifier"].as_str().unwrap();
-                let table_json = params["table_json"].as_str().unwrap();
-                let method_name = params["method"].as_str().unwrap();
-                
-                // Busca dados específicos para esta unidade
-                let result_dict = match self.repo.fetch_columns_by_name_with_filter(
-                    table, 
-                    &columns, 
-                    "ifrounidadeid", 
-                    unidade_id
-                ).await {
-                    Ok(data) => data,
-                    Err(e) => {
-                        error!("Erro ao buscar {} em {} para unidade {}: {}", 
-                               identifier, table, unidade_id, e);
-                        // Continua com o próximo parâmetro em vez de falhar completamente
-                        continue;
+        Ok(ApiResponse::updated(json!({
+            "modo": "completo",
+            "unidades": unidades
+        })).into_response())
+    }
+
+    // Recalcula apenas os gráficos informados para uma unidade, limitando às competências alteradas
+    pub async fn update_charts_for_unit(&self, unidade_id: i32, identifiers: &[&str], competencias: &[String]) -> Result<(), AppError> {
+        info!("Atualizando gráficos {:?} para unidade {} nas competências {:?}", identifiers, unidade_id, competencias);
+        self.process_unit(unidade_id, Some(competencias), Some(identifiers)).await
+    }
+
+    async fn clear_pending(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<(), AppError> {
+        self.repo.clear_pending_competencias(unidade_id, competencias).await
+            .map_err(|e| {
+                error!("Erro ao limpar competências pendentes da unidade {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })
+    }
+
+    // Sem competências informadas, todos os gráficos da unidade são reconstruídos por completo
+    async fn process_unit(&self, unidade_id: i32, competencias: Option<&[String]>, only_identifiers: Option<&[&str]>) -> Result<(), AppError> {
+        info!("Processando dados para unidade {}", unidade_id);
+
+        let rules = self.rules_repo.fetch_rules(unidade_id).await
+            .map_err(|e| {
+                error!("Erro ao buscar regras de gráficos da unidade {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        for chart in self.registry.charts() {
+            let identifier = chart.name();
+
+            // Ignora gráficos fora da seleção, quando informada
+            if let Some(only) = only_identifiers {
+                if !only.contains(&identifier) {
+                    continue;
+                }
+            }
+
+            if !chart.applies_to_unit(&rules) {
+                info!("Unidade {}: gráfico {} não será processado", unidade_id, identifier);
+                continue;
+            }
+
+            // Gráficos sem fatias armazenadas são reconstruídos por completo
+            let scope = match competencias {
+                Some(competencias) if chart.mergeable() => {
+                    match self.repo.check_graph_data_slices_exist(identifier, unidade_id).await {
+                        Ok(true) => Some(competencias),
+                        Ok(false) => {
+                            info!("Sem fatias armazenadas de {} para unidade {}. Reconstruindo por completo", identifier, unidade_id);
+                            None
+                        },
+                        Err(e) => {
+                            error!("Erro ao verificar fatias de {} para unidade {}: {}", identifier, unidade_id, e);
+                            continue;
+                        }
                     }
-                };
-    
-                if result_dict.is_empty() {
-                    error!("Dados vazios para {} na unidade {}", identifier, unidade_id);
-                    continue; // Passa para o próximo parâmetro
+                },
+                _ => None,
+            };
+
+            let ctx = ChartContext {
+                repo: self.repo.get_ref(),
+                unidade_id,
+                competencias: scope,
+                rules: &rules,
+            };
+
+            let computed = match chart.compute(&ctx).await {
+                Ok(computed) => computed,
+                Err(e) => {
+                    error!("Erro ao calcular {} para unidade {}: {}", identifier, unidade_id, e);
+                    // Continua com o próximo gráfico em vez de falhar completamente
+                    continue;
                 }
-    
-                
-                if identifier == "distribuition_of_patients_ages" {
-                    let organized_data = self.data_processing.create_dict_to_distribuition_of_patients_ages_from_raw(
-                        &result_dict
-                    ).await.map_err(|e| {
-                        error!("Erro no método create_dict_to_distribuition_of_patients_ages_from_raw: {}", e);
-                        AppError::DataProcessingError(e.to_string())
-                    })?;
-                    
-                    if let Err(e) = self.save_processed_data_with_unit(
-                        organized_data, 
-                        table_json, 
-                        identifier, 
-                        unidade_id
-                    ).await {
-                        error!("Falha ao salvar {} para unidade {}: {}", 
-                               identifier, unidade_id, e);
-                        // Continua com o próximo parâmetro
+            };
+
+            let organized_data = match computed {
+                ChartData::Slices(slices) => {
+                    if slices.is_empty() && scope.is_none() {
+                        error!("Dados vazios para {} na unidade {}", identifier, unidade_id);
                         continue;
                     }
-                } else {
-                    // Criar DataFrame e processar outros casos normalmente
-                    let df = match create_dataframe_from_dict(&result_dict) {
-                        Ok(df) => df,
-                        Err(e) => {
-                            error!("Erro ao criar DataFrame para {} na unidade {}: {}", 
-                                   identifier, unidade_id, e);
+
+                    match self.update_slices(identifier, unidade_id, slices, scope).await? {
+                        Some(data) => data,
+                        None => {
+                            error!("Nenhuma fatia armazenada para {} na unidade {}", identifier, unidade_id);
                             continue;
                         }
-                    };
-                  
-                    
-                    // Processamento condicional
-                    let organized_data = match identifier {
-                        "number_of_visits_per_doctor" | "average_time_per_doctor" => {
-                            let non_doctors = self.get_additional_data("non_doctors").await?;
-                            self.call_processing_method(method_name, &df, Some(&non_doctors)).await?
-                        },
-                        "number_of_visits_per_nurse" => {
-                            let non_nurse = self.get_additional_data("non_nurse").await?;
-                            self.call_processing_method(method_name, &df, Some(&non_nurse)).await?
-                        },
-                        _ => self.call_processing_method(method_name, &df, None).await?,
-                    };
-                
-                    // Salva dados incluindo o id da unidade
-                    if let Err(e) = self.save_processed_data_with_unit(
-                        organized_data, 
-                        table_json, 
-                        identifier, 
-                        unidade_id
-                    ).await {
-                        error!("Falha ao salvar {} para unidade {}: {}", 
-                               identifier, unidade_id, e);
-                        // Continua com o próximo parâmetro
-                        continue;
                     }
+                },
+                ChartData::Complete(data) => data,
+            };
 
-                    info!("Dados de {} para unidade {} salvos com sucesso", 
-                          identifier, unidade_id);
-                }
-            } 
+            self.save_chart(organized_data, identifier, unidade_id).await;
+        }
 
+        // Os alertas de vigilância acompanham cada atualização dos dados agregados
+        if let Err(e) = self.surveillance.run_for_unit(unidade_id, competencias).await {
+            error!("Erro ao atualizar alertas de vigilância da unidade {}: {}", unidade_id, e);
         }
-    
-        Ok(ApiResponse::updated(()).into_response())
+
+        Ok(())
     }
 
-    // Funções Auxiliares 
-    async fn get_additional_data(&self, table: &str) -> Result<DataFrame, AppError> {
-        let data = self.repo.fetch_all_data(table)
-            .await
+    // Salva o JSON combinado do gráfico. Falhas são registradas sem interromper os demais gráficos
+    async fn save_chart(&self, organized_data: Value, identifier: &str, unidade_id: i32) {
+        // Salva dados incluindo o id da unidade
+        if let Err(e) = self.save_processed_data_with_unit(
+            organized_data, 
+            identifier, 
+            identifier, 
+            unidade_id
+        ).await {
+            error!("Falha ao salvar {} para unidade {}: {}", 
+                   identifier, unidade_id, e);
+            return;
+        }
+
+        info!("Dados de {} para unidade {} salvos com sucesso", 
+              identifier, unidade_id);
+    }
+
+    // Armazena as fatias por competência e retorna o JSON combinado de todas as fatias da unidade
+    async fn update_slices(
+        &self,
+        identifier: &str,
+        unidade_id: i32,
+        slices: HashMap<String, Value>,
+        scope: Option<&[String]>,
+    ) -> Result<Option<Value>, AppError> {
+        // Na reconstrução completa as fatias antigas são descartadas. Na incremental,
+        // apenas as competências sem dados restantes
+        let stale: Option<Vec<String>> = scope.map(|competencias| competencias
+            .iter()
+            .filter(|c| !slices.contains_key(*c))
+            .cloned()
+            .collect());
+
+        if scope.is_none() || stale.as_ref().map(|c| !c.is_empty()).unwrap_or(false) {
+            self.repo.delete_graph_data_slices(identifier, unidade_id, stale.as_deref()).await
+                .map_err(|e| {
+                    error!("Erro ao remover fatias de {} para unidade {}: {}", identifier, unidade_id, e);
+                    AppError::DatabaseError(e.to_string())
+                })?;
+        }
+
+        let total_slices = slices.len();
+        for (competencia, data) in slices {
+            self.repo.insert_graph_data_slice(data, identifier, unidade_id, &competencia).await
+                .map_err(|e| {
+                    error!("Erro ao salvar fatia {} de {} para unidade {}: {}", competencia, identifier, unidade_id, e);
+                    AppError::DatabaseError(e.to_string())
+                })?;
+        }
+
+        info!("{} fatias de {} atualizadas para unidade {}", total_slices, identifier, unidade_id);
+
+        let stored = self.repo.fetch_graph_data_slices(identifier, unidade_id).await
             .map_err(|e| {
-                error!("Erro ao buscar dados auxiliares da tabela {}: {}", table, e);
+                error!("Erro ao buscar fatias de {} para unidade {}: {}", identifier, unidade_id, e);
                 AppError::DatabaseError(e.to_string())
             })?;
 
-        create_dataframe_from_dict(&data)
-            .map_err(|e| {
-                error!("Erro ao criar DataFrame para dados auxiliares da tabela {}: {}", table, e);
-                AppError::DataProcessingError(e.to_string())
-            })
-    }
-
-    async fn call_processing_method(&self, method: &str, main_df: &DataFrame, additional_df: Option<&DataFrame>) -> Result<Value, AppError> {
-        match (method, additional_df) {
-            ("create_dict_to_number_of_appointments_per_month", None) =>
-                self.data_processing.create_dict_to_number_of_appointments_per_month(main_df).await
-                    .map_err(|e| { error!("Erro no método {}: {}", method, e); AppError::DataProcessingError(e.to_string()) }),
-            ("create_dict_to_number_of_appointments_per_flow", None) =>
-                self.data_processing.create_dict_to_number_of_appointments_per_flow(main_df).await
-                    .map_err(|e| { error!("Erro no método {}: {}", method, e); AppError::DataProcessingError(e.to_string()) }),
-            // ("create_dict_to_distribuition_of_patients_ages", None) =>
-            //     self.data_processing.create_dict_to_distribuition_of_patients_ages_from_raw(main_df).await
-            //         .map_err(|e| { error!("Erro no método {}: {}", method, e); AppError::DataProcessingError(e.to_string()) }),
-            ("create_dict_to_number_of_calls_per_day_of_the_week", None) =>
-                self.data_processing.create_dict_to_number_of_calls_per_day_of_the_week(main_df).await
-                    .map_err(|e| { error!("Erro no método {}: {}", method, e); AppError::DataProcessingError(e.to_string()) }),
-            ("create_dict_to_distribution_of_services_by_hour_group", None) =>
-                self.data_processing.create_dict_to_distribution_of_services_by_hour_group(main_df).await
-                    .map_err(|e| { error!("Erro no método {}: {}", method, e); AppError::DataProcessingError(e.to_string()) }),
-            ("create_dict_to_number_of_visits_per_nurse", Some(df)) =>
-                self.data_processing.create_dict_to_number_of_visits_per_nurse(main_df, df).await
-                    .map_err(|e| { error!("Erro no método {}: {}", method, e); AppError::DataProcessingError(e.to_string()) }),
-            ("create_dict_to_number_of_visits_per_doctor", Some(df)) =>
-                self.data_processing.create_dict_to_number_of_visits_per_doctor(main_df, df).await
-                    .map_err(|e| { error!("Erro no método {}: {}", method, e); AppError::DataProcessingError(e.to_string()) }),
-            ("create_dict_to_average_time_in_minutes_per_doctor", Some(df)) =>
-                self.data_processing.create_dict_to_average_time_in_minutes_per_doctor(main_df, df).await
-                    .map_err(|e| { error!("Erro no método {}: {}", method, e); AppError::DataProcessingError(e.to_string()) }),
-            ("create_dictionary_with_location_and_number_per_disease", None) =>
-                self.data_processing.create_dictionary_with_location_and_number_per_disease(main_df).await
-                    .map_err(|e| { error!("Erro no método {}: {}", method, e); AppError::DataProcessingError(e.to_string()) }),
-            ("create_dict_to_heat_map_with_the_number_of_medical_appointments_by_neighborhood", None) =>
-                self.data_processing.create_dict_to_heat_map_with_the_number_of_medical_appointments_by_neighborhood(main_df).await
-                    .map_err(|e| { error!("Erro no método {}: {}", method, e); AppError::DataProcessingError(e.to_string()) }),
-            _ => Err(AppError::InvalidMethodError(format!("Método '{}' inválido ou dados adicionais incorretos", method)))
+        if stored.is_empty() {
+            return Ok(None);
         }
+
+        Ok(Some(merge_graph_data(stored.iter().map(|(_, data)| data))))
     }
 
     // Novo método para salvar com unidade
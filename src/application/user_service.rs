@@ -4,7 +4,7 @@ use uuid::Uuid;
 
 use crate::adapters::password::PasswordEncryptorPort;
 
-use crate::domain::repositories::data_upa::DataRepository;
+use crate::domain::repositories::health_unit::HealthUnitRepository;
 use crate::domain::{
     email::email_service::EmailService,
     models::user::{
@@ -31,7 +31,7 @@ use crate::infrastructure::{
     email::email_service::SmtpEmailService,
     repositories::{
         user_repository::PgUserRepository,
-        data_upa_repository::PgDataRepository,
+        health_unit_repository::PgHealthUnitRepository,
     },
 };
 
@@ -62,7 +62,7 @@ pub struct UserService {
     repo: web::Data<PgUserRepository>,
     password_encryptor: Box<dyn PasswordEncryptorPort>,
     config: web::Data<Config>,
-    data_repo: web::Data<PgDataRepository>,
+    health_unit_repo: web::Data<PgHealthUnitRepository>,
 }
 
 impl UserService {
@@ -70,9 +70,20 @@ impl UserService {
         repo: web::Data<PgUserRepository>, 
         password_encryptor: Box<dyn PasswordEncryptorPort>, 
         config: web::Data<Config>,
-        data_repo: web::Data<PgDataRepository>,
+        health_unit_repo: web::Data<PgHealthUnitRepository>,
     ) -> Self {
-        Self { repo, password_encryptor, config, data_repo }
+        Self { repo, password_encryptor, config, health_unit_repo }
+    }
+
+    // Identificadores das unidades ativas no cadastro de unidades de saúde
+    async fn available_health_units(&self) -> Result<Vec<i64>, AppError> {
+        match self.health_unit_repo.fetch_units(Some(true)).await {
+            Ok(units) => Ok(units.iter().map(|unit| unit.ifrounidadeid as i64).collect()),
+            Err(e) => {
+                error!("Error fetching health units: {:?}", e);
+                Err(AppError::InternalServerError)
+            }
+        }
     }
 
     pub async fn get_users(&self) -> Result<HttpResponse, AppError> {
@@ -133,7 +144,7 @@ impl UserService {
         }
 
         // Verifica se o usuário já existe
-        if let Some(_) = self.repo.find_by_email(&user.email).await.unwrap() {
+        if self.repo.find_by_email(&user.email).await.unwrap().is_some() {
             return Err(AppError::BadRequest(
                 format!("Error adding user: email '{}' already exists", user.email)
             ));
@@ -153,30 +164,7 @@ impl UserService {
         }
 
         // Buscar todas as unidades disponíveis
-        let table_name = "bpa";
-        let columns = vec![
-            "ifrounidadeid".to_string(),
-            "ifrounidadenome".to_string()
-        ];
-
-        let available_health_units = match self.data_repo.fetch_distinct_health_units(table_name, &columns).await {
-            Ok(data) => {
-                let mut units = Vec::new();
-                
-                if let Some(ids) = data.get("ifrounidadeid") {
-                    for id in ids {
-                        if let Some(id_value) = id.as_i64() {
-                            units.push(id_value);
-                        }
-                    }
-                }
-                units
-            },
-            Err(e) => {
-                error!("Error fetching health units: {:?}", e);
-                return Err(AppError::InternalServerError);
-            }
-        };
+        let available_health_units = self.available_health_units().await?;
 
         // Validar cada unidade enviada
         for unit_id in &user.allowed_health_units {
@@ -252,30 +240,7 @@ impl UserService {
         }
 
         // Buscar todas as unidades disponíveis
-        let table_name = "bpa";
-        let columns = vec![
-            "ifrounidadeid".to_string(),
-            "ifrounidadenome".to_string()
-        ];
-
-        let available_health_units = match self.data_repo.fetch_distinct_health_units(table_name, &columns).await {
-            Ok(data) => {
-                let mut units = Vec::new();
-                
-                if let Some(ids) = data.get("ifrounidadeid") {
-                    for id in ids {
-                        if let Some(id_value) = id.as_i64() {
-                            units.push(id_value);
-                        }
-                    }
-                }
-                units
-            },
-            Err(e) => {
-                error!("Error fetching health units: {:?}", e);
-                return Err(AppError::InternalServerError);
-            }
-        };
+        let available_health_units = self.available_health_units().await?;
 
         // Validar cada unidade enviada
         for unit_id in &user.allowed_health_units {
@@ -464,7 +429,7 @@ impl UserService {
         }
 
         // Validação de aplicações permitidas
-        validate_applications(&[application_name.clone()])?;
+        validate_applications(std::slice::from_ref(&application_name))?;
 
         // Verifica se existe mais de uma aplicação permitida
         if user.unwrap().allowed_applications.len() == 1 {
@@ -782,21 +747,21 @@ impl UserService {
             Ok(updated_used_code) => Ok(ApiResponse::success(updated_used_code).into_response()),
             Err(e) => {
                 error!("Error updating verification code: {:?}", e);
-                return Err(AppError::BadRequest(format!("Failed to update verification code: {:?}", e)));
+                Err(AppError::BadRequest(format!("Failed to update verification code: {:?}", e)))
             }
         }
     }
 
     pub async fn update_password_for_forgetting_user(&self, user_id: Uuid, data: UpdatePasswordForgettingUserDto) -> Result<HttpResponse, AppError> {
         //Verifica se o usuário existe
-        match self.repo.find_by_id(user_id.clone()).await {
+        match self.repo.find_by_id(user_id).await {
             Ok(Some(_)) => (),
             Ok(None) => return Err(AppError::BadRequest("User not found".to_string())),
             Err(_) => return Err(AppError::InternalServerError)
         };
 
         //Verifica se existe codigo para o id verification enviado
-        let code_exist = match self.repo.verify_code_exist(data.id_verification.clone()).await {
+        let code_exist = match self.repo.verify_code_exist(data.id_verification).await {
             Ok(code) => code,
             Err(e) => {
                 error!("Database error: {:?}", e);
@@ -839,30 +804,7 @@ impl UserService {
         }
 
         // Buscar todas as unidades disponíveis
-        let table_name = "bpa";
-        let columns = vec![
-            "ifrounidadeid".to_string(),
-            "ifrounidadenome".to_string()
-        ];
-
-        let available_health_units = match self.data_repo.fetch_distinct_health_units(table_name, &columns).await {
-            Ok(data) => {
-                let mut units = Vec::new();
-                
-                if let Some(ids) = data.get("ifrounidadeid") {
-                    for id in ids {
-                        if let Some(id_value) = id.as_i64() {
-                            units.push(id_value);
-                        }
-                    }
-                }
-                units
-            },
-            Err(e) => {
-                error!("Error fetching health units: {:?}", e);
-                return Err(AppError::InternalServerError);
-            }
-        };
+        let available_health_units = self.available_health_units().await?;
 
         // Obter o usuário para verificar duplicidades
         let user = self.repo.find_by_id(id).await.unwrap().unwrap();
@@ -1,440 +1,743 @@
 use log::{error, info};
-use serde_json::json;
 use std::collections::HashSet;
 use actix_web::{web, HttpResponse};
 use uuid::Uuid;
 use std::str::FromStr;
+use serde_json::json;
+use crate::application::graph_aggregation::disease_heat_map::DiseaseHeatMap;
+use crate::application::graph_aggregation::{parse_chart_query, CellLevel, ChartContext, ChartData, ChartQuery, ChartView, GraphAggregation, GraphRegistry};
+use crate::domain::models::incidence::{NeighborhoodPopulation, PopulationImportQuery};
+use crate::domain::models::time_series::{TimeSeriesQuery, GRANULARITY_EPI_WEEK};
+use crate::domain::models::unit_chart_rules::{UnitChartRules, UnitChartRulesRequest};
+use crate::domain::models::unit_comparison::{
+    UnitComparisonQuery,
+    UnitPopulationRequest,
+    NORMALIZATION_NONE,
+    NORMALIZATION_PER_CAPITA,
+    NORMALIZATION_SHARE,
+    PER_CAPITA_BASE,
+};
+use crate::domain::models::visualization_data_graph::{ChartComparisonQuery, ChartPeriodQuery, ChartRequest, ComparisonQuery, HeatMapOutputQuery};
 use crate::domain::repositories::data_upa::DataRepository;
+use crate::domain::repositories::health_unit::HealthUnitRepository;
+use crate::domain::repositories::unit_chart_rules::UnitChartRulesRepository;
+use crate::domain::repositories::unit_population::UnitPopulationRepository;
 use crate::domain::repositories::user::UserRepository;
 use crate::infrastructure::repositories::data_upa_repository::PgDataRepository;
+use crate::infrastructure::repositories::health_unit_repository::PgHealthUnitRepository;
+use crate::infrastructure::repositories::unit_chart_rules_repository::PgUnitChartRulesRepository;
+use crate::infrastructure::repositories::unit_population_repository::PgUnitPopulationRepository;
 use crate::infrastructure::repositories::user_repository::PgUserRepository;
+use crate::utils::competencia::{competencia_in_range, validate_period};
+use crate::utils::geojson::{feature_collection, wants_geojson, GEOJSON_CONTENT_TYPE};
+use crate::utils::graph_slices::{merge_graph_data, scale_graph_data};
+use crate::utils::incidence::{parse_population_csv, rate_requested};
+use crate::utils::period_comparison::{with_comparison, ComparisonMode};
 use crate::utils::response::ApiResponse;
+use crate::utils::small_cells::CellSuppression;
+use crate::utils::time_series::{build_time_series, validate_date};
 use crate::AppError;
 
 pub struct VisualizationDataService {
     data_repo: web::Data<PgDataRepository>,
     user_repo: web::Data<PgUserRepository>,
+    registry: web::Data<GraphRegistry>,
+    population_repo: web::Data<PgUnitPopulationRepository>,
+    rules_repo: web::Data<PgUnitChartRulesRepository>,
+    health_unit_repo: web::Data<PgHealthUnitRepository>,
+    min_cell_size: i64,
 }
 
 impl VisualizationDataService {
-    pub fn new(data_repo: web::Data<PgDataRepository>, user_repo: web::Data<PgUserRepository>) -> Self {
-        Self { data_repo, user_repo }
+    pub fn new(
+        data_repo: web::Data<PgDataRepository>,
+        user_repo: web::Data<PgUserRepository>,
+        registry: web::Data<GraphRegistry>,
+        population_repo: web::Data<PgUnitPopulationRepository>,
+        rules_repo: web::Data<PgUnitChartRulesRepository>,
+        health_unit_repo: web::Data<PgHealthUnitRepository>,
+        min_cell_size: i64,
+    ) -> Self {
+        Self { data_repo, user_repo, registry, population_repo, rules_repo, health_unit_repo, min_cell_size }
     }
 
+    // Busca os dados processados de qualquer gráfico registrado, opcionalmente limitados a um período,
+    // formatados com os parâmetros próprios do gráfico na consulta
+    pub async fn chart(&self, user_id: String, unidade_id: i32, slug: &str, request: ChartRequest) -> Result<HttpResponse, AppError> {
+        let period: ChartPeriodQuery = parse_chart_query(&request.query_string)?;
+        info!("Fetching chart {} for unit {} and user {} ({:?})", slug, unidade_id, user_id, request);
 
-    pub async fn number_of_appointments_per_month(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching number of appointments per month for unit {} and user {}", unidade_id, user_id);
-
-        // Valida acesso do usuário
-        self.validate_user_access(user_id, unidade_id).await?;
+        let (chart, data) = self.chart_data(user_id, unidade_id, slug, period).await?;
 
-        // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists("number_of_appointments_per_month", "number_of_appointments_per_month", Some(unidade_id)).await?;
+        let query = ChartQuery {
+            query_string: &request.query_string,
+            accept: request.accept.as_deref(),
+            min_cell_size: self.min_cell_size,
+            unidade_id,
+            population_repo: self.population_repo.get_ref(),
+        };
+        let view = chart.present_query(data, &query).await?;
 
-        match self.data_repo.fetch_nested_json("number_of_appointments_per_month", "number_of_appointments_per_month", Some(unidade_id)).await {
-            Ok(data) => {
-                if data.is_empty() {
-                    error!("Error fetching number of appointments per month for unit {}. Organized data is empty", unidade_id);
-                    return Err(AppError::BadRequest("No data found".to_string()));
-                }
+        info!("Chart {} fetched successfully for unit {}", slug, unidade_id);
+        Ok(self.view_response(view))
+    }
 
-                let corrected_data = self.correct_keys(data);
+    // Suprime as células abaixo do tamanho mínimo, conforme o nível das contagens do gráfico
+    fn suppress(&self, level: CellLevel, data: &mut serde_json::Value) -> usize {
+        level.suppress(data, self.min_cell_size)
+    }
 
-                info!("Number of appointments per month fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(corrected_data).into_response())
-            },
-            Err(e) => {
-                error!("Error fetching number of appointments per month for unit {}: {:?}", unidade_id, e);
-                Err(AppError::InternalServerError)
-            }
+    // Resposta com o resumo da supressão em `meta` nos gráficos por bairro, profissional ou etapa
+    fn response_with_suppression(&self, level: CellLevel, data: serde_json::Value, suppressed: usize) -> HttpResponse {
+        if level == CellLevel::Aggregate {
+            return ApiResponse::success(data).into_response();
         }
-    }
 
-    pub async fn number_of_appointments_per_year(&self, user_id: String, unidade_id: i32, year: String) -> Result<HttpResponse, AppError> {
-        info!("Fetching number of appointments per year: {} for unit {} and user {}", year, unidade_id, user_id);
+        ApiResponse::success(data)
+            .with_meta(json!(CellSuppression::new(self.min_cell_size, suppressed)))
+            .into_response()
+    }
 
-        // Valida acesso do usuário
-        self.validate_user_access(user_id, unidade_id).await?;
+    // Resposta de um gráfico formatado. Em GeoJSON, o resumo da supressão segue como membro adicional
+    // da FeatureCollection
+    fn view_response(&self, view: ChartView) -> HttpResponse {
+        if let Some(properties) = view.geojson {
+            let mut collection = feature_collection(&view.data, properties);
+            collection["supressao"] = json!(CellSuppression::new(self.min_cell_size, view.suppressed));
+            return HttpResponse::Ok()
+                .content_type(GEOJSON_CONTENT_TYPE)
+                .json(collection);
+        }
 
-        // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists("number_of_appointments_per_month", "number_of_appointments_per_month", Some(unidade_id)).await?;
+        self.response_with_suppression(view.level, view.data, view.suppressed)
+    }
 
-        match self.data_repo.fetch_nested_json("number_of_appointments_per_month", "number_of_appointments_per_month", Some(unidade_id)).await {
-            Ok(data) => {
-                if data.is_empty() {
-                    error!("Error fetching number of appointments per year for unit {}. Organized data is empty", unidade_id);
-                    return Err(AppError::BadRequest("No data found".to_string()));
-                }
+    // Gráfico por competência com a comparação opcional de cada valor com o período anterior ou o ano anterior
+    async fn compared_chart(&self, user_id: String, unidade_id: i32, slug: &str, query: ChartComparisonQuery) -> Result<serde_json::Value, AppError> {
+        let period = ChartPeriodQuery { from: query.from, to: query.to };
+        let mode = ComparisonMode::from_query(query.compare.as_deref())?;
 
-                let corrected_data = self.correct_keys(data);
+        info!("Fetching chart {} for unit {} and user {} ({:?}, compare: {:?})", slug, unidade_id, user_id, period, mode.map(|m| m.as_str()));
 
-                // Filtra apenas o ano especificado
-                let filtered_data: serde_json::Map<String, serde_json::Value> = corrected_data
-                    .into_iter()
-                    .filter(|(key, _)| key.starts_with(&year))
-                    .collect();
+        let window = validate_period(period.from.as_deref(), period.to.as_deref())?;
+        let (chart, data) = self.chart_data(user_id, unidade_id, slug, period).await?;
 
-                if filtered_data.is_empty() {
-                    error!("No data found for year {} in unit {}", year, unidade_id);
-                    return Err(AppError::NotFound(format!("No data found for year {} in unit {}", year, unidade_id)));
-                }
-
-                // Ordena por mês (formato: ano-mês)
-                let mut sorted_data_vec: Vec<(String, serde_json::Value)> = filtered_data.into_iter().collect();
-                sorted_data_vec.sort_by(|(a, _), (b, _)| {
-                    let a_month = a.split('-').nth(1).unwrap_or("0").parse::<i32>().unwrap_or(0);
-                    let b_month = b.split('-').nth(1).unwrap_or("0").parse::<i32>().unwrap_or(0);
-                    a_month.cmp(&b_month)
-                });
+        let Some(mode) = mode else {
+            info!("Chart {} fetched successfully for unit {}", slug, unidade_id);
+            return Ok(chart.present(data));
+        };
 
-                let sorted_data: serde_json::Map<String, serde_json::Value> = sorted_data_vec.into_iter().collect();
+        // Competências de comparação podem estar fora do período consultado
+        let all = match window {
+            Some(_) => self.unit_chart_data(chart, unidade_id, None).await?,
+            None => data.clone(),
+        };
 
-                info!("Number of appointments per year fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(sorted_data).into_response())
+        // O total ("todos") só é comparado em períodos fechados com dados no período de comparação
+        let previous = match window {
+            Some((start, end)) if start.0 != i32::MIN && end.0 != i32::MAX => {
+                let (start, end) = mode.reference_window((start, end));
+                Some(self.correct_keys(self.chart_for_period(chart, unidade_id, start, end).await?))
+                    .filter(|previous| !previous.is_empty())
             },
-            Err(e) => {
-                error!("Error fetching number of appointments per year for unit {}: {:?}", unidade_id, e);
-                Err(AppError::InternalServerError)
-            }
-        }
+            _ => None,
+        };
+
+        info!("Chart {} compared successfully for unit {}", slug, unidade_id);
+        Ok(with_comparison(&data, &all, previous.as_ref(), mode))
     }
 
-    pub async fn years_available_for_number_of_appointments_per_month(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching years available for number of appointments per month for unit {} and user {}", unidade_id, user_id);
+    // Valida o acesso e retorna os dados do gráfico (armazenados ou do período) com as chaves corrigidas
+    async fn chart_data(
+        &self,
+        user_id: String,
+        unidade_id: i32,
+        slug: &str,
+        period: ChartPeriodQuery,
+    ) -> Result<(&dyn GraphAggregation, serde_json::Map<String, serde_json::Value>), AppError> {
+        let chart = self.registry.find_by_slug(slug)
+            .ok_or_else(|| AppError::NotFound(format!("Chart {} not found", slug)))?;
+
+        let window = validate_period(period.from.as_deref(), period.to.as_deref())?;
 
         // Valida acesso do usuário
         self.validate_user_access(user_id, unidade_id).await?;
 
+        if !chart.applies_to_unit(&self.unit_rules(unidade_id).await?) {
+            return Err(AppError::Forbidden(
+                format!("Chart {} is not available for unit {}", slug, unidade_id)
+            ));
+        }
+
+        let data = self.unit_chart_data(chart, unidade_id, window).await?;
+
+        Ok((chart, data))
+    }
+
+    // Dados armazenados do gráfico na unidade (ou do período), com as chaves corrigidas
+    async fn unit_chart_data(
+        &self,
+        chart: &dyn GraphAggregation,
+        unidade_id: i32,
+        window: Option<((i32, u32), (i32, u32))>,
+    ) -> Result<serde_json::Map<String, serde_json::Value>, AppError> {
+        let slug = chart.slug();
+
         // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists("number_of_appointments_per_month", "number_of_appointments_per_month", Some(unidade_id)).await?;
+        self.verify_unit_data_exists(chart.name(), chart.name(), unidade_id).await?;
 
-        match self.data_repo.fetch_nested_json("number_of_appointments_per_month", "number_of_appointments_per_month", Some(unidade_id)).await {
-            Ok(data) => {
+        let data = match window {
+            Some((start, end)) => {
+                let data = self.chart_for_period(chart, unidade_id, start, end).await?;
                 if data.is_empty() {
-                    error!("Error fetching years available for unit {}. Organized data is empty", unidade_id);
-                    return Err(AppError::BadRequest("No data found".to_string()));
+                    return Err(AppError::NotFound(format!(
+                        "No data found for chart {} in unit {} for the selected period", slug, unidade_id
+                    )));
                 }
-
-                let corrected_data = self.correct_keys(data);
-
-                // Extrai os anos únicos
-                let mut years = HashSet::new();
-                for key in corrected_data.keys() {
-                    if let Some(year) = key.split('-').next() {
-                        years.insert(year.to_string());
+                data
+            },
+            None => match self.data_repo.fetch_nested_json(chart.name(), chart.name(), unidade_id).await {
+                Ok(data) => {
+                    if data.is_empty() {
+                        error!("Error fetching chart {} for unit {}. Organized data is empty", slug, unidade_id);
+                        return Err(AppError::BadRequest("No data found".to_string()));
                     }
+                    data
+                },
+                Err(e) => {
+                    error!("Error fetching chart {} for unit {}: {:?}", slug, unidade_id, e);
+                    return Err(AppError::InternalServerError);
                 }
+            },
+        };
 
-                // Verifica se encontramos anos
-                if years.is_empty() {
-                    error!("No years found for unit {}", unidade_id);
-                    return Err(AppError::NotFound(format!("No years found for unit {}", unidade_id)));
-                }
+        Ok(self.correct_keys(data))
+    }
 
-                // Ordena os anos
-                let mut years_vec: Vec<String> = years.into_iter().collect();
-                years_vec.sort();
+    // Mesmo gráfico de várias unidades lado a lado, com o total combinado e normalização opcional
+    pub async fn compare_units(&self, user_id: String, slug: &str, query: UnitComparisonQuery) -> Result<HttpResponse, AppError> {
+        info!("Comparing chart {} for units {} and user {} ({:?})", slug, query.units, user_id, query);
 
-                info!("Years available fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(years_vec).into_response())
-            },
-            Err(e) => {
-                error!("Error fetching years available for unit {}: {:?}", unidade_id, e);
-                Err(AppError::InternalServerError)
-            }
-        }
-    }
+        let chart = self.registry.find_by_slug(slug)
+            .ok_or_else(|| AppError::NotFound(format!("Chart {} not found", slug)))?;
 
-    pub async fn number_of_appointments_per_flow(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching number of appointments per flow for unit {} and user {}", unidade_id, user_id);
+        let window = validate_period(query.from.as_deref(), query.to.as_deref())?;
+        let unidade_ids = parse_unit_ids(&query.units)?;
 
-        // Valida acesso do usuário
-        self.validate_user_access(user_id, unidade_id).await?;
+        let normalization = query.normalizacao.as_deref().map(|n| n.trim().to_lowercase())
+            .unwrap_or_else(|| NORMALIZATION_NONE.to_string());
 
-        // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists("number_of_appointments_per_flow", "number_of_appointments_per_flow", Some(unidade_id)).await?;
+        if !ALLOWED_NORMALIZATIONS.contains(&normalization.as_str()) {
+            return Err(AppError::BadRequest(format!(
+                "Error: '{}' is not a valid value for 'normalizacao'. Allowed values are: {}",
+                normalization, ALLOWED_NORMALIZATIONS.join(", ")
+            )));
+        }
 
-        match self.data_repo.fetch_nested_json("number_of_appointments_per_flow", "number_of_appointments_per_flow", Some(unidade_id)).await {
-            Ok(data) => {
-                if data.is_empty() {
-                    error!("Error fetching number of appointments per flow for unit {}. Organized data is empty", unidade_id);
-                    return Err(AppError::BadRequest("No data found".to_string()));
-                }
+        if normalization != NORMALIZATION_NONE && !chart.normalizable() {
+            return Err(AppError::BadRequest(format!(
+                "Chart {} does not support normalization", slug
+            )));
+        }
 
-                let corrected_data = self.correct_keys(data);
+        self.validate_user_access_to_units(user_id, &unidade_ids).await?;
 
-                info!("Number of appointments per flow fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(corrected_data).into_response())
-            },
-            Err(e) => {
-                error!("Error fetching number of appointments per flow for unit {}: {:?}", unidade_id, e);
-                Err(AppError::InternalServerError)
+        for unidade_id in &unidade_ids {
+            if !chart.applies_to_unit(&self.unit_rules(*unidade_id).await?) {
+                return Err(AppError::Forbidden(
+                    format!("Chart {} is not available for unit {}", slug, unidade_id)
+                ));
             }
         }
+
+        let mut units_data = Vec::with_capacity(unidade_ids.len());
+        for unidade_id in &unidade_ids {
+            let data = self.unit_chart_data(chart, *unidade_id, window).await?;
+            units_data.push((*unidade_id, serde_json::Value::Object(data)));
+        }
+
+        // Denominador de cada unidade: atendimentos no período ou população de referência
+        let denominators: Option<Vec<f64>> = match normalization.as_str() {
+            NORMALIZATION_SHARE => {
+                let mut totals = Vec::with_capacity(unidade_ids.len());
+                for unidade_id in &unidade_ids {
+                    totals.push(self.attendances_in_window(*unidade_id, window).await? as f64);
+                }
+                Some(totals)
+            },
+            NORMALIZATION_PER_CAPITA => Some(self.unit_populations(&unidade_ids).await?),
+            _ => None,
+        };
+
+        // O total combinado só faz sentido para gráficos de contagem
+        let combined = chart.mergeable().then(|| merge_graph_data(units_data.iter().map(|(_, data)| data)));
+
+        // Células pequenas são suprimidas nas contagens absolutas, antes da normalização
+        let level = chart.cell_level();
+        let present = |data: &serde_json::Value, denominator: Option<f64>| {
+            let mut data = data.clone();
+            match denominator {
+                Some(denominator) => {
+                    let suppressed = self.suppress(level, &mut data);
+                    let data = scale_graph_data(&data, normalization_factor(&normalization, denominator));
+                    (chart.present(data.as_object().cloned().unwrap_or_default()), suppressed)
+                },
+                None => {
+                    let mut presented = chart.present(data.as_object().cloned().unwrap_or_default());
+                    let suppressed = self.suppress(level, &mut presented);
+                    (presented, suppressed)
+                },
+            }
+        };
+
+        let mut suppressed = 0;
+        let unidades: Vec<serde_json::Value> = units_data.iter().enumerate()
+            .map(|(i, (unidade_id, data))| {
+                let denominator = denominators.as_ref().map(|d| d[i]);
+                let (dados, unit_suppressed) = present(data, denominator);
+                suppressed += unit_suppressed;
+                json!({
+                    "unidade_id": unidade_id,
+                    "denominador": denominator,
+                    "dados": dados,
+                })
+            })
+            .collect();
+
+        let total = combined.map(|data| {
+            let denominator = denominators.as_ref().map(|d| d.iter().sum::<f64>());
+            let (dados, total_suppressed) = present(&data, denominator);
+            suppressed += total_suppressed;
+            json!({
+                "denominador": denominator,
+                "dados": dados,
+            })
+        });
+
+        info!("Chart {} compared successfully for {} units", slug, unidade_ids.len());
+        Ok(self.response_with_suppression(level, json!({
+            "grafico": slug,
+            "normalizacao": normalization,
+            "unidades": unidades,
+            "total": total,
+        }), suppressed))
     }
 
-    pub async fn distribuition_of_patients_ages(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching distribuition of patients ages for unit {} and user {}", unidade_id, user_id);
+    // População de referência usada na comparação por habitante
+    pub async fn set_unit_population(&self, unidade_id: i32, population: UnitPopulationRequest, updated_by: String) -> Result<HttpResponse, AppError> {
+        if population.populacao <= 0 {
+            return Err(AppError::BadRequest("Error: 'populacao' must be greater than 0".to_string()));
+        }
 
-        // Valida acesso do usuário
-        self.validate_user_access(user_id, unidade_id).await?;
+        let saved = self.population_repo.upsert_population(unidade_id, &population, &updated_by).await
+            .map_err(|e| {
+                error!("Error saving population for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
 
-        // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists("distribuition_of_patients_ages", "distribuition_of_patients_ages", Some(unidade_id)).await?;
+        info!("Reference population of unit {} set to {} by {}", unidade_id, saved.populacao, updated_by);
+        Ok(ApiResponse::updated(saved).into_response())
+    }
 
-        match self.data_repo.fetch_nested_json("distribuition_of_patients_ages", "distribuition_of_patients_ages", Some(unidade_id)).await {
-            Ok(data) => {
-                if data.is_empty() {
-                    error!("Error fetching distribuition of patients ages for unit {}. Organized data is empty", unidade_id);
-                    return Err(AppError::BadRequest("No data found".to_string()));
-                }
+    pub async fn unit_population(&self, unidade_id: i32) -> Result<HttpResponse, AppError> {
+        let population = self.population_repo.fetch_populations(&[unidade_id]).await
+            .map_err(|e| {
+                error!("Error fetching population for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?
+            .into_iter()
+            .next()
+            .ok_or_else(|| AppError::NotFound(format!("Reference population not registered for unit {}", unidade_id)))?;
+
+        Ok(ApiResponse::success(population).into_response())
+    }
 
-                let corrected_data = self.correct_keys(data);
+    // Regras de montagem dos gráficos da unidade (endereços e bairros excluídos e gráficos não suportados)
+    pub async fn unit_chart_rules(&self, unidade_id: i32) -> Result<HttpResponse, AppError> {
+        let rules = self.unit_rules(unidade_id).await?;
+        Ok(ApiResponse::success(rules).into_response())
+    }
 
-                info!("Distribuition of patients ages fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(corrected_data).into_response())
-            },
-            Err(e) => {
-                error!("Error fetching distribuition of patients ages for unit {}: {:?}", unidade_id, e);
-                Err(AppError::InternalServerError)
-            }
+    // As regras valem a partir da próxima atualização dos gráficos da unidade
+    pub async fn set_unit_chart_rules(&self, unidade_id: i32, rules: UnitChartRulesRequest, updated_by: String) -> Result<HttpResponse, AppError> {
+        if let Some(slug) = rules.graficos_nao_suportados.iter().find(|s| self.registry.find_by_slug(s).is_none()) {
+            return Err(AppError::BadRequest(format!("Error: chart '{}' not found", slug)));
         }
+
+        // Bairros são comparados sem espaços nas pontas e em maiúsculas
+        let rules = UnitChartRulesRequest {
+            enderecos_excluidos: rules.enderecos_excluidos.iter()
+                .map(|e| e.trim().to_string())
+                .filter(|e| !e.is_empty())
+                .collect(),
+            bairros_genericos: rules.bairros_genericos.iter()
+                .map(|b| b.trim().to_uppercase())
+                .filter(|b| !b.is_empty())
+                .collect(),
+            graficos_nao_suportados: rules.graficos_nao_suportados,
+        };
+
+        let saved = self.rules_repo.upsert_rules(unidade_id, &rules, &updated_by).await
+            .map_err(|e| {
+                error!("Error saving chart rules for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        info!("Chart rules of unit {} updated by {}", unidade_id, updated_by);
+        Ok(ApiResponse::updated(saved).into_response())
     }
 
-  
-    pub async fn number_of_calls_per_day_of_the_week(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching number of calls per day of the week for unit {} and user {}", unidade_id, user_id);
+    async fn unit_rules(&self, unidade_id: i32) -> Result<UnitChartRules, AppError> {
+        self.rules_repo.fetch_rules(unidade_id).await
+            .map_err(|e| {
+                error!("Error fetching chart rules for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })
+    }
 
-        // Valida acesso do usuário
-        self.validate_user_access(user_id, unidade_id).await?;
+    // Importa o CSV de população por bairro e faixa etária (ex.: Censo IBGE), substituindo o cadastro da unidade
+    pub async fn import_neighborhood_population(&self, unidade_id: i32, content: web::Bytes, query: PopulationImportQuery, updated_by: String) -> Result<HttpResponse, AppError> {
+        // Arquivos do IBGE costumam vir em Latin-1
+        let text = String::from_utf8(content.to_vec())
+            .unwrap_or_else(|e| e.into_bytes().iter().map(|&b| b as char).collect());
+
+        let entries = parse_population_csv(&text)
+            .map_err(|e| AppError::BadRequest(format!("Error: invalid population file: {}", e)))?;
+
+        let fonte = query.fonte.as_deref().map(str::trim).filter(|f| !f.is_empty());
+        let saved = self.population_repo.replace_neighborhood_populations(unidade_id, &entries, query.ano, fonte, &updated_by).await
+            .map_err(|e| {
+                error!("Error saving neighborhood populations for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        let bairros: HashSet<&str> = entries.iter().map(|e| e.bairro.as_str()).collect();
+
+        info!("{} neighborhood population rows imported for unit {} by {}", saved, unidade_id, updated_by);
+        Ok(ApiResponse::updated(json!({
+            "ifrounidadeid": unidade_id,
+            "bairros": bairros.len(),
+            "linhas": saved,
+            "ano": query.ano,
+            "fonte": fonte,
+        })).into_response())
+    }
 
-        // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists("number_of_calls_per_day_of_the_week", "number_of_calls_per_day_of_the_week", Some(unidade_id)).await?;
+    pub async fn neighborhood_population(&self, unidade_id: i32) -> Result<HttpResponse, AppError> {
+        let populations = self.neighborhood_populations(unidade_id).await?;
+        Ok(ApiResponse::success(populations).into_response())
+    }
 
-        match self.data_repo.fetch_nested_json("number_of_calls_per_day_of_the_week", "number_of_calls_per_day_of_the_week", Some(unidade_id)).await {
-            Ok(data) => {
-                if data.is_empty() {
-                    error!("Error fetching number of calls per day of the week for unit {}. Organized data is empty", unidade_id);
-                    return Err(AppError::BadRequest("No data found".to_string()));
-                }
+    // População por bairro e faixa etária cadastrada para a unidade
+    async fn neighborhood_populations(&self, unidade_id: i32) -> Result<Vec<NeighborhoodPopulation>, AppError> {
+        let populations = self.population_repo.fetch_neighborhood_populations(unidade_id).await
+            .map_err(|e| {
+                error!("Error fetching neighborhood populations for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
 
-                let corrected_data = self.correct_keys(data);
-                println!("Corrected data: {:?}", corrected_data);
-                let ordered_data = self.order_days_of_week_as_array(serde_json::Value::Object(corrected_data));
-                
-                // Cria resposta customizada que preserva a ordem
-                let response_json = serde_json::json!({
-                    "message": "Operation successful",
-                    "status": 200,
-                    "data": ordered_data
-                });
-                
-                info!("Number of calls per day of the week fetched successfully for unit {}", unidade_id);
-                Ok(HttpResponse::Ok().json(response_json))
-            },
-            Err(e) => {
-                error!("Error fetching number of calls per day of the week for unit {}: {:?}", unidade_id, e);
-                Err(AppError::InternalServerError)
-            }
+        if populations.is_empty() {
+            return Err(AppError::NotFound(format!("Neighborhood population not registered for unit {}", unidade_id)));
         }
+
+        Ok(populations)
     }
 
-    pub async fn distribution_of_services_by_hour_group(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching distribution of services by hour group for unit {} and user {}", unidade_id, user_id);
+    // Total de atendimentos da unidade nas competências do período (ou em todas)
+    async fn attendances_in_window(&self, unidade_id: i32, window: Option<((i32, u32), (i32, u32))>) -> Result<i64, AppError> {
+        let counts = self.data_repo.count_by_competencia(unidade_id, None).await
+            .map_err(|e| {
+                error!("Error counting attendances for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        Ok(counts.iter()
+            .filter(|count| window.map(|(start, end)| competencia_in_range(&count.competencia, start, end)).unwrap_or(true))
+            .map(|count| count.quantidade)
+            .sum())
+    }
 
-        // Valida acesso do usuário
-        self.validate_user_access(user_id, unidade_id).await?;
+    // População de referência de cada unidade, na ordem informada
+    async fn unit_populations(&self, unidade_ids: &[i32]) -> Result<Vec<f64>, AppError> {
+        let populations = self.population_repo.fetch_populations(unidade_ids).await
+            .map_err(|e| {
+                error!("Error fetching populations for units {:?}: {}", unidade_ids, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        unidade_ids.iter()
+            .map(|unidade_id| populations.iter()
+                .find(|p| p.ifrounidadeid == *unidade_id && p.populacao > 0)
+                .map(|p| p.populacao as f64)
+                .ok_or_else(|| AppError::BadRequest(format!(
+                    "Reference population not registered for unit {}", unidade_id
+                ))))
+            .collect()
+    }
 
-        // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists("distribution_of_services_by_hour_group", "distribution_of_services_by_hour_group", Some(unidade_id)).await?;
+    // Monta o gráfico apenas com as competências do período, recalculando os totais ("todos")
+    async fn chart_for_period(
+        &self,
+        chart: &dyn GraphAggregation,
+        unidade_id: i32,
+        start: (i32, u32),
+        end: (i32, u32),
+    ) -> Result<serde_json::Map<String, serde_json::Value>, AppError> {
+        if chart.mergeable() {
+            let slices = self.data_repo.fetch_graph_data_slices(chart.name(), unidade_id).await
+                .map_err(|e| {
+                    error!("Error fetching slices of {} for unit {}: {}", chart.name(), unidade_id, e);
+                    AppError::DatabaseError(e.to_string())
+                })?;
+
+            if !slices.is_empty() {
+                let merged = merge_graph_data(slices.iter()
+                    .filter(|(competencia, _)| competencia_in_range(competencia, start, end))
+                    .map(|(_, data)| data));
+
+                return Ok(merged.as_object().cloned().unwrap_or_default());
+            }
+        }
 
-        match self.data_repo.fetch_nested_json("distribution_of_services_by_hour_group", "distribution_of_services_by_hour_group", Some(unidade_id)).await {
-            Ok(data) => {
-                if data.is_empty() {
-                    error!("Error fetching distribution of services by hour group for unit {}. Organized data is empty", unidade_id);
-                    return Err(AppError::BadRequest("No data found".to_string()));
-                }
+        // Gráficos sem fatias (ou de médias) são calculados apenas para as competências do período
+        let competencias: Vec<String> = self.data_repo.fetch_competencias_by_unit(unidade_id).await
+            .map_err(|e| {
+                error!("Error fetching competencias for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?
+            .into_iter()
+            .filter(|competencia| competencia_in_range(competencia, start, end))
+            .collect();
+
+        if competencias.is_empty() {
+            return Ok(serde_json::Map::new());
+        }
 
-                let corrected_data = self.correct_keys(data);
+        let rules = self.unit_rules(unidade_id).await?;
+        let ctx = ChartContext {
+            repo: self.data_repo.get_ref(),
+            unidade_id,
+            competencias: Some(&competencias),
+            rules: &rules,
+        };
 
-                info!("Distribution of services by hour group fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(corrected_data).into_response())
-            },
+        let data = match chart.compute(&ctx).await {
+            Ok(ChartData::Slices(slices)) => merge_graph_data(slices.values()),
+            Ok(ChartData::Complete(data)) => data,
             Err(e) => {
-                error!("Error fetching distribution of services by hour group for unit {}: {:?}", unidade_id, e);
-                Err(AppError::InternalServerError)
+                error!("Error computing {} for unit {}: {}", chart.name(), unidade_id, e);
+                return Err(AppError::DataProcessingError(e.to_string()));
             }
-        }
+        };
+
+        Ok(data.as_object().cloned().unwrap_or_default())
     }
 
-    pub async fn number_of_visits_per_nurse(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching number of visits per nurse for unit {} and user {}", unidade_id, user_id);
+    // Séries de atendimentos por dia ou por semana epidemiológica, no total e por queixa classificada
+    pub async fn time_series(&self, user_id: String, unidade_id: i32, granularity: &str, query: TimeSeriesQuery) -> Result<HttpResponse, AppError> {
+        info!("Fetching {} time series for unit {} and user {}", granularity, unidade_id, user_id);
+
+        let from = query.from.as_deref().map(|value| validate_date("from", value)).transpose()?;
+        let to = query.to.as_deref().map(|value| validate_date("to", value)).transpose()?;
+
+        if let (Some(from), Some(to)) = (from, to) {
+            if from > to {
+                return Err(AppError::BadRequest("Error: 'from' must be before or equal to 'to'".to_string()));
+            }
+        }
+
+        let doenca = query.doenca.map(|d| d.trim().to_lowercase());
 
         // Valida acesso do usuário
         self.validate_user_access(user_id, unidade_id).await?;
 
-        // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists("number_of_visits_per_nurse", "number_of_visits_per_nurse", Some(unidade_id)).await?;
+        let counts = self.data_repo.fetch_daily_counts(unidade_id, from, to, doenca.as_deref()).await
+            .map_err(|e| {
+                error!("Error fetching daily counts for unit {}: {}", unidade_id, e);
+                AppError::DatabaseError(e.to_string())
+            })?;
+
+        // Sem limites informados, o período vai do primeiro ao último atendimento encontrado
+        let (start, end) = match (
+            from.or_else(|| counts.iter().map(|c| c.data).min()),
+            to.or_else(|| counts.iter().map(|c| c.data).max()),
+        ) {
+            (Some(start), Some(end)) if !counts.is_empty() => (start, end),
+            _ => return Err(AppError::NotFound(format!(
+                "No attendances found for unit {} in the selected period", unidade_id
+            ))),
+        };
 
-        match self.data_repo.fetch_nested_json("number_of_visits_per_nurse", "number_of_visits_per_nurse", Some(unidade_id)).await {
-            Ok(data) => {
-                if data.is_empty() {
-                    error!("Error fetching number of visits per nurse for unit {}. Organized data is empty", unidade_id);
-                    return Err(AppError::BadRequest("No data found".to_string()));
-                }
+        let series = build_time_series(&counts, granularity, start, end);
 
-                let corrected_data = self.correct_keys(data);
+        info!("{} time series fetched successfully for unit {}", if granularity == GRANULARITY_EPI_WEEK { "Epi-week" } else { "Daily" }, unidade_id);
+        Ok(ApiResponse::success(series).into_response())
+    }
 
-                info!("Number of visits per nurse fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(corrected_data).into_response())
-            },
-            Err(e) => {
-                error!("Error fetching number of visits per nurse for unit {}: {:?}", unidade_id, e);
-                Err(AppError::InternalServerError)
-            }
-        }
+    pub async fn number_of_appointments_per_month(&self, user_id: String, unidade_id: i32, query: ChartComparisonQuery) -> Result<HttpResponse, AppError> {
+        let data = self.compared_chart(user_id, unidade_id, "number-of-appointments-per-month", query).await?;
+        Ok(ApiResponse::success(data).into_response())
     }
 
-    pub async fn number_of_visits_per_doctor(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching number of visits per doctor for unit {} and user {}", unidade_id, user_id);
+    pub async fn number_of_appointments_per_year(&self, user_id: String, unidade_id: i32, year: String, query: ComparisonQuery) -> Result<HttpResponse, AppError> {
+        info!("Fetching number of appointments per year: {} for unit {} and user {}", year, unidade_id, user_id);
+
+        let mode = ComparisonMode::from_query(query.compare.as_deref())?;
 
         // Valida acesso do usuário
         self.validate_user_access(user_id, unidade_id).await?;
 
         // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists("number_of_visits_per_doctor", "number_of_visits_per_doctor", Some(unidade_id)).await?;
+        self.verify_unit_data_exists("number_of_appointments_per_month", "number_of_appointments_per_month", unidade_id).await?;
 
-        match self.data_repo.fetch_nested_json("number_of_visits_per_doctor", "number_of_visits_per_doctor", Some(unidade_id)).await {
+        match self.data_repo.fetch_nested_json("number_of_appointments_per_month", "number_of_appointments_per_month", unidade_id).await {
             Ok(data) => {
                 if data.is_empty() {
-                    error!("Error fetching number of visits per doctor for unit {}. Organized data is empty", unidade_id);
+                    error!("Error fetching number of appointments per year for unit {}. Organized data is empty", unidade_id);
                     return Err(AppError::BadRequest("No data found".to_string()));
                 }
 
                 let corrected_data = self.correct_keys(data);
 
-                info!("Number of visits per doctor fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(corrected_data).into_response())
+                // Filtra apenas o ano especificado
+                let filtered_data: serde_json::Map<String, serde_json::Value> = corrected_data
+                    .iter()
+                    .filter(|(key, _)| key.starts_with(&year))
+                    .map(|(key, value)| (key.clone(), value.clone()))
+                    .collect();
+
+                if filtered_data.is_empty() {
+                    error!("No data found for year {} in unit {}", year, unidade_id);
+                    return Err(AppError::NotFound(format!("No data found for year {} in unit {}", year, unidade_id)));
+                }
+
+                // Ordena por mês (formato: ano-mês)
+                let mut sorted_data_vec: Vec<(String, serde_json::Value)> = filtered_data.into_iter().collect();
+                sorted_data_vec.sort_by(|(a, _), (b, _)| {
+                    let a_month = a.split('-').nth(1).unwrap_or("0").parse::<i32>().unwrap_or(0);
+                    let b_month = b.split('-').nth(1).unwrap_or("0").parse::<i32>().unwrap_or(0);
+                    a_month.cmp(&b_month)
+                });
+
+                let sorted_data: serde_json::Map<String, serde_json::Value> = sorted_data_vec.into_iter().collect();
+
+                info!("Number of appointments per year fetched successfully for unit {}", unidade_id);
+                match mode {
+                    // Cada mês é comparado com o anterior ou com o mesmo mês do ano anterior
+                    Some(mode) => Ok(ApiResponse::success(with_comparison(&sorted_data, &corrected_data, None, mode)).into_response()),
+                    None => Ok(ApiResponse::success(sorted_data).into_response()),
+                }
             },
             Err(e) => {
-                error!("Error fetching number of visits per doctor for unit {}: {:?}", unidade_id, e);
+                error!("Error fetching number of appointments per year for unit {}: {:?}", unidade_id, e);
                 Err(AppError::InternalServerError)
             }
         }
     }
 
-    pub async fn average_time_in_minutes_per_doctor(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching average time in minutes per doctor for unit {} and user {}", unidade_id, user_id);
+    pub async fn years_available_for_number_of_appointments_per_month(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
+        info!("Fetching years available for number of appointments per month for unit {} and user {}", unidade_id, user_id);
 
         // Valida acesso do usuário
         self.validate_user_access(user_id, unidade_id).await?;
 
         // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists("average_time_per_doctor", "average_time_per_doctor", Some(unidade_id)).await?;
+        self.verify_unit_data_exists("number_of_appointments_per_month", "number_of_appointments_per_month", unidade_id).await?;
 
-        match self.data_repo.fetch_nested_json("average_time_per_doctor", "average_time_per_doctor", Some(unidade_id)).await {
+        match self.data_repo.fetch_nested_json("number_of_appointments_per_month", "number_of_appointments_per_month", unidade_id).await {
             Ok(data) => {
                 if data.is_empty() {
-                    error!("Error fetching average time in minutes per doctor for unit {}. Organized data is empty", unidade_id);
+                    error!("Error fetching years available for unit {}. Organized data is empty", unidade_id);
                     return Err(AppError::BadRequest("No data found".to_string()));
                 }
 
                 let corrected_data = self.correct_keys(data);
 
-                info!("Average time in minutes per doctor fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(corrected_data).into_response())
+                // Extrai os anos únicos
+                let mut years = HashSet::new();
+                for key in corrected_data.keys() {
+                    if let Some(year) = key.split('-').next() {
+                        years.insert(year.to_string());
+                    }
+                }
+
+                // Verifica se encontramos anos
+                if years.is_empty() {
+                    error!("No years found for unit {}", unidade_id);
+                    return Err(AppError::NotFound(format!("No years found for unit {}", unidade_id)));
+                }
+
+                // Ordena os anos
+                let mut years_vec: Vec<String> = years.into_iter().collect();
+                years_vec.sort();
+
+                info!("Years available fetched successfully for unit {}", unidade_id);
+                Ok(ApiResponse::success(years_vec).into_response())
             },
             Err(e) => {
-                error!("Error fetching average time in minutes per doctor for unit {}: {:?}", unidade_id, e);
+                error!("Error fetching years available for unit {}: {:?}", unidade_id, e);
                 Err(AppError::InternalServerError)
             }
         }
     }
 
-    pub async fn heat_map_with_disease_indication(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching heat map with disease indication for unit {} and user {}", unidade_id, user_id);
+    pub async fn number_of_appointments_per_flow(&self, user_id: String, unidade_id: i32, query: ChartComparisonQuery) -> Result<HttpResponse, AppError> {
+        let data = self.compared_chart(user_id, unidade_id, "number-of-appointments-per-flow", query).await?;
+        Ok(ApiResponse::success(data).into_response())
+    }
 
-        // Valida acesso do usuário
-        self.validate_user_access(user_id, unidade_id).await?;
+    pub async fn distribuition_of_patients_ages(&self, user_id: String, unidade_id: i32, request: ChartRequest) -> Result<HttpResponse, AppError> {
+        self.chart(user_id, unidade_id, "distribuition-of-patients-ages", request).await
+    }
 
-        // Verifica se unidade 3 não tem acesso a mapas de calor
-        if unidade_id == 3 {
-            return Err(AppError::Forbidden(
-                "Heat maps are not available for unit 3".to_string()
-            ));
-        }
+  
+    pub async fn number_of_calls_per_day_of_the_week(&self, user_id: String, unidade_id: i32, request: ChartRequest) -> Result<HttpResponse, AppError> {
+        self.chart(user_id, unidade_id, "number-of-calls-per-day-of-the-week", request).await
+    }
 
-        // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists(
-            "heat_map_with_disease_indication",
-            "heat_map_with_disease_indication",
-            Some(unidade_id)
-        ).await?;
-
-        match self.data_repo.fetch_nested_json(
-            "heat_map_with_disease_indication",
-            "heat_map_with_disease_indication",
-            Some(unidade_id)
-        ).await {
-            Ok(data) => {
-                if data.is_empty() {
-                    error!("Error fetching heat map with disease indication. Organized data is empty");
-                    return Err(AppError::BadRequest("No data found".to_string()));
-                }
+    pub async fn distribution_of_services_by_hour_group(&self, user_id: String, unidade_id: i32, request: ChartRequest) -> Result<HttpResponse, AppError> {
+        self.chart(user_id, unidade_id, "distribution-of-services-by-hour-group", request).await
+    }
 
-                let corrected_data = self.correct_keys(data);
+    pub async fn number_of_visits_per_nurse(&self, user_id: String, unidade_id: i32, request: ChartRequest) -> Result<HttpResponse, AppError> {
+        self.chart(user_id, unidade_id, "number-of-visits-per-nurse", request).await
+    }
 
-                info!("Heat map with disease indication fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(corrected_data).into_response())
-            },
-            Err(e) => {
-                error!("Error fetching heat map with disease indication: {:?}", e);
-                Err(AppError::InternalServerError)
-            }
-        }
+    pub async fn number_of_visits_per_doctor(&self, user_id: String, unidade_id: i32, request: ChartRequest) -> Result<HttpResponse, AppError> {
+        self.chart(user_id, unidade_id, "number-of-visits-per-doctor", request).await
     }
 
-    pub async fn heat_map_with_the_number_of_medical_appointments_by_neighborhood(&self, user_id: String, unidade_id: i32) -> Result<HttpResponse, AppError> {
-        info!("Fetching heat map with appointments by neighborhood for unit {} and user {}", unidade_id, user_id);
+    pub async fn average_time_in_minutes_per_doctor(&self, user_id: String, unidade_id: i32, request: ChartRequest) -> Result<HttpResponse, AppError> {
+        self.chart(user_id, unidade_id, "average-time-in-minutes-per-doctor", request).await
+    }
 
-        // Valida acesso do usuário
-        self.validate_user_access(user_id, unidade_id).await?;
+    // Sem `compare`, o mapa segue a rota genérica (GeoJSON e taxa por habitante pela consulta)
+    pub async fn heat_map_with_disease_indication(&self, user_id: String, unidade_id: i32, request: ChartRequest) -> Result<HttpResponse, AppError> {
+        let slug = "heat-map-with-disease-indication";
+        let query: ChartComparisonQuery = parse_chart_query(&request.query_string)?;
+        if query.compare.is_none() {
+            return self.chart(user_id, unidade_id, slug, request).await;
+        }
 
-        // Verifica se unidade 3 não tem acesso a mapas de calor
-        if unidade_id == 3 {
-            return Err(AppError::Forbidden(
-                "Heat maps are not available for unit 3".to_string()
-            ));
+        let output: HeatMapOutputQuery = parse_chart_query(&request.query_string)?;
+        if rate_requested(output.medida.as_deref())? {
+            return Err(AppError::BadRequest("Error: 'compare' cannot be combined with 'medida=taxa'".to_string()));
         }
+        let geojson = wants_geojson(output.format.as_deref(), request.accept.as_deref())?;
 
-        // Verifica se existem dados para esta unidade
-        self.verify_unit_data_exists(
-            "heat_map_with_the_number_of_medical_appointments_by_neighborhood",
-            "heat_map_with_the_number_of_medical_appointments_by_neighborhood",
-            Some(unidade_id)
-        ).await?;
-
-        match self.data_repo.fetch_nested_json(
-            "heat_map_with_the_number_of_medical_appointments_by_neighborhood",
-            "heat_map_with_the_number_of_medical_appointments_by_neighborhood",
-            Some(unidade_id)
-        ).await {
-            Ok(data) => {
-                if data.is_empty() {
-                    error!("Error fetching heat map with appointments by neighborhood. Organized data is empty");
-                    return Err(AppError::BadRequest("No data found".to_string()));
-                }
+        let mut data = self.compared_chart(user_id, unidade_id, slug, query).await?;
+        let suppressed = self.suppress(CellLevel::Neighborhood, &mut data);
 
-                let corrected_data = self.correct_keys(data);
+        Ok(self.view_response(ChartView {
+            data,
+            level: CellLevel::Neighborhood,
+            suppressed,
+            geojson: None,
+        }.with_geojson(geojson, DiseaseHeatMap::GEOJSON_PROPERTIES)))
+    }
 
-                info!("Heat map with appointments by neighborhood fetched successfully for unit {}", unidade_id);
-                Ok(ApiResponse::success(corrected_data).into_response())
-            },
-            Err(e) => {
-                error!("Error fetching heat map with appointments by neighborhood: {:?}", e);
-                Err(AppError::InternalServerError)
-            }
-        }
+    pub async fn heat_map_with_the_number_of_medical_appointments_by_neighborhood(&self, user_id: String, unidade_id: i32, request: ChartRequest) -> Result<HttpResponse, AppError> {
+        self.chart(user_id, unidade_id, "heat-map-with-the-number-of-medical-appointments-by-neighborhood", request).await
     }
 
     // Função auxilia para corrigir as chaves JSON
@@ -456,12 +759,17 @@ impl VisualizationDataService {
 
     // Função auxilia para validação de acesso do usuário
     async fn validate_user_access(&self, user_id: String, unidade_id: i32) -> Result<(), AppError> {
+        self.validate_user_access_to_units(user_id, &[unidade_id]).await
+    }
+
+    // Todas as unidades precisam estar em allowed_health_units
+    async fn validate_user_access_to_units(&self, user_id: String, unidade_ids: &[i32]) -> Result<(), AppError> {
         let user_uuid = Uuid::from_str(&user_id)
             .map_err(|_| AppError::BadRequest("Invalid user ID format".to_string()))?;
 
         match self.user_repo.find_by_id(user_uuid).await {
             Ok(Some(user)) => {
-                if !user.allowed_health_units.contains(&(unidade_id as i64)) {
+                if let Some(unidade_id) = unidade_ids.iter().find(|id| !user.allowed_health_units.contains(&(**id as i64))) {
                     return Err(AppError::Forbidden(
                         format!("User does not have access to unit {}", unidade_id)
                     ));
@@ -474,21 +782,17 @@ impl VisualizationDataService {
     }
 
     // Método auxilia para verificar se existem dados para a unidade solicitada
-    async fn verify_unit_data_exists(&self, table: &str, identifier: &str, unidade_id: Option<i32>) -> Result<(), AppError> {
-        // Se unidade_id for None, assumimos unidade 2 (padrão)
-        let unit_id = unidade_id.unwrap_or(2);
-
-        // Verificamos se a unidade é válida (existe no sistema)
-        let unidades = match self.data_repo.fetch_distinct_values("bpa", "ifrounidadeid").await {
-            Ok(units) => units,
+    async fn verify_unit_data_exists(&self, table: &str, identifier: &str, unit_id: i32) -> Result<(), AppError> {
+        // A unidade precisa estar ativa no cadastro de unidades de saúde
+        let unit = match self.health_unit_repo.find_unit(unit_id).await {
+            Ok(unit) => unit,
             Err(e) => {
-                error!("Erro ao buscar unidades disponíveis: {}", e);
+                error!("Erro ao buscar unidade {} no cadastro: {}", unit_id, e);
                 return Err(AppError::DatabaseError(format!("Erro ao verificar unidades disponíveis: {}", e)));
             }
         };
 
-        // Se a unidade solicitada não existir no sistema
-        if !unidades.contains(&unit_id) {
+        if !unit.map(|u| u.ativo).unwrap_or(false) {
             error!("Unidade {} não encontrada no sistema", unit_id);
             return Err(AppError::NotFound(format!("Unidade {} não encontrada no sistema", unit_id)));
         }
@@ -509,34 +813,43 @@ impl VisualizationDataService {
         }
     }
 
-    fn order_days_of_week_as_array(&self, data: serde_json::Value) -> Vec<serde_json::Value> {
-        let days_order = [
-            "segunda-feira",
-            "terça-feira",
-            "quarta-feira",
-            "quinta-feira",
-            "sexta-feira",
-            "sábado",
-            "domingo",
-        ];
-        
-        let mut ordered_data = Vec::new();
-        
-        if let serde_json::Value::Object(map) = data {
-            // Itera na ordem correta dos dias da semana
-            for day in days_order.iter() {
-                if let Some(value) = map.get(*day) {
-                    // Cria um novo objeto JSON para cada dia e o adiciona ao vetor
-                    let day_data = json!({
-                        "day_of_the_week": day,
-                        "day_of_the_week_data": value.clone()
-                    });
-                    ordered_data.push(day_data);
-                }
-            }
+}
+
+const ALLOWED_NORMALIZATIONS: [&str; 3] = [NORMALIZATION_NONE, NORMALIZATION_SHARE, NORMALIZATION_PER_CAPITA];
+
+// Limite de unidades por comparação
+const MAX_COMPARED_UNITS: usize = 20;
+
+// Lista de unidades da comparação ("2,3,5"), sem repetições e na ordem informada
+fn parse_unit_ids(units: &str) -> Result<Vec<i32>, AppError> {
+    let mut unidade_ids: Vec<i32> = Vec::new();
+
+    for unit in units.split(',').map(str::trim).filter(|u| !u.is_empty()) {
+        let unidade_id = unit.parse::<i32>()
+            .map_err(|_| AppError::BadRequest(format!("Error: '{}' is not a valid unit ID", unit)))?;
+
+        if !unidade_ids.contains(&unidade_id) {
+            unidade_ids.push(unidade_id);
         }
-    
-        ordered_data
     }
 
+    if unidade_ids.is_empty() || unidade_ids.len() > MAX_COMPARED_UNITS {
+        return Err(AppError::BadRequest(format!(
+            "Error: 'units' must list between 1 and {} unit IDs separated by commas", MAX_COMPARED_UNITS
+        )));
+    }
+
+    Ok(unidade_ids)
+}
+
+// Fator aplicado aos valores: percentual dos atendimentos ou taxa por mil habitantes
+fn normalization_factor(normalization: &str, denominator: f64) -> f64 {
+    if denominator <= 0.0 {
+        return 0.0;
+    }
+
+    match normalization {
+        NORMALIZATION_PER_CAPITA => PER_CAPITA_BASE / denominator,
+        _ => 100.0 / denominator,
+    }
 }
@@ -0,0 +1,62 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Deserialize)]
+pub struct ClassificationReviewQuery {
+    pub unidade_id: Option<i32>,
+    pub doenca: Option<String>,
+    pub confianca_min: Option<f64>,
+    pub confianca_max: Option<f64>,
+    pub competencia: Option<String>,
+}
+
+/// Filtros já validados usados na consulta dos registros classificados
+#[derive(Debug, Default)]
+pub struct ClassificationReviewFilter {
+    pub unidade_id: Option<i32>,
+    pub doenca: Option<String>,
+    pub confianca_min: Option<f64>,
+    pub confianca_max: Option<f64>,
+    pub competencias: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClassifiedRecord {
+    pub ifroid: String,
+    pub ifrounidadeid: i32,
+    pub ifrocompetencia: String,
+    pub conduta: String,
+    pub queixa_principal: String,
+    pub confianca: Option<f64>,
+    pub corrigido: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateClassificationCorrectionDto {
+    pub unidade_id: i32,
+    pub ifroid: String,
+    pub queixa_corrigida: String,
+    pub observacao: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ClassificationCorrection {
+    pub id: Uuid,
+    pub ifroid: String,
+    pub ifrounidadeid: i32,
+    pub ifrocompetencia: String,
+    pub conduta: String,
+    pub queixa_anterior: String,
+    pub queixa_corrigida: String,
+    pub confianca: Option<f64>,
+    pub observacao: Option<String>,
+    pub corrected_by: String,
+    pub created_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CorrectionExportQuery {
+    pub format: Option<String>,
+    pub unidade_id: Option<i32>,
+}
@@ -1,4 +1,4 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 
 #[derive(Debug, Serialize)]
@@ -7,4 +7,18 @@ pub struct HealthUnit {
     pub id: i64,
     #[serde(rename = "ifrounidadenome")]
     pub name: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateGraphDataQuery {
+    pub full: Option<bool>,
+}
+
+/// Contagem agregada por competência retornada pelas consultas GROUP BY dos gráficos
+#[derive(Debug, Clone)]
+pub struct GroupedCount {
+    pub competencia: String,
+    pub keys: Vec<String>,
+    pub coordinates: Option<(f64, f64)>,
+    pub quantidade: i64,
 }
\ No newline at end of file
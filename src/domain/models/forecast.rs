@@ -0,0 +1,34 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastParameters {
+    pub alpha: f64,
+    pub beta: f64,
+    pub gamma: f64,
+    /// Suavização da sazonalidade anual. `None` quando o histórico tem menos de dois anos
+    pub delta: Option<f64>,
+    pub phi: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastPoint {
+    pub data: NaiveDate,
+    pub previsto: f64,
+    pub limite_inferior_80: f64,
+    pub limite_superior_80: f64,
+    pub limite_inferior_95: f64,
+    pub limite_superior_95: f64,
+}
+
+/// Previsão diária de atendimentos com intervalos de predição de 80% e 95%
+#[derive(Debug, Clone, Serialize)]
+pub struct ForecastSeries {
+    pub modelo: String,
+    pub sazonalidades: Vec<usize>,
+    pub parametros: ForecastParameters,
+    pub historico_inicio: NaiveDate,
+    pub historico_fim: NaiveDate,
+    pub erro_medio_absoluto: f64,
+    pub previsao: Vec<ForecastPoint>,
+}
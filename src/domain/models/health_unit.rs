@@ -0,0 +1,43 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+pub const UNIT_TYPE_UPA: &str = "UPA";
+pub const UNIT_TYPE_UBS: &str = "UBS";
+pub const UNIT_TYPE_HOSPITAL: &str = "HOSPITAL";
+
+/// Unidade de saúde cadastrada. O identificador é o mesmo de `ifrounidadeid` nos dados importados
+#[derive(Debug, Clone, Serialize)]
+pub struct HealthUnitRecord {
+    pub ifrounidadeid: i32,
+    pub cnes: Option<String>,
+    pub nome: String,
+    pub municipio: Option<String>,
+    pub tipo: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub ativo: bool,
+    /// Recursos da unidade (ex.: "raio_x", "pediatria")
+    pub capacidades: Vec<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Corpo do cadastro e da atualização. `ifrounidadeid` só é usado no cadastro
+#[derive(Debug, Deserialize)]
+pub struct HealthUnitRequest {
+    pub ifrounidadeid: Option<i32>,
+    pub cnes: Option<String>,
+    pub nome: String,
+    pub municipio: Option<String>,
+    pub tipo: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+    pub ativo: Option<bool>,
+    #[serde(default)]
+    pub capacidades: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct HealthUnitQuery {
+    pub ativo: Option<bool>,
+}
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+/// Período e parâmetros da detecção de aglomerados (DBSCAN) e da estatística Gi* de Getis-Ord
+#[derive(Debug, Default, Deserialize)]
+pub struct HotspotQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub raio_metros: Option<f64>,
+    pub min_casos: Option<i64>,
+    pub distancia_gi: Option<f64>,
+}
+
+/// Aglomerado de casos encontrado pelo DBSCAN, com a envoltória convexa em [longitude, latitude]
+#[derive(Debug, Clone, Serialize)]
+pub struct HotspotCluster {
+    pub id: usize,
+    pub quantidade: i64,
+    pub pontos: usize,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub envoltoria: Vec<[f64; 2]>,
+}
+
+/// Local de casos com o aglomerado (quando houver) e a significância da estatística Gi*
+#[derive(Debug, Clone, Serialize)]
+pub struct HotspotPoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub quantidade: i64,
+    pub aglomerado: Option<usize>,
+    pub gi_z: f64,
+    pub p_valor: f64,
+    pub confianca: Option<u32>,
+    pub classificacao: &'static str,
+}
+
+/// Resultado da detecção para uma doença em um período
+#[derive(Debug, Clone, Serialize)]
+pub struct HotspotResult {
+    pub aglomerados: Vec<HotspotCluster>,
+    pub pontos: Vec<HotspotPoint>,
+    pub casos_fora_de_aglomerados: i64,
+}
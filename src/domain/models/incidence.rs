@@ -0,0 +1,50 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Quantidade absoluta de atendimentos
+pub const MEASURE_COUNTS: &str = "casos";
+/// Atendimentos por mil habitantes do bairro
+pub const MEASURE_RATE: &str = "taxa";
+
+/// Doenças somadas nas taxas de incidência
+pub const ALL_DISEASES: &str = "todas";
+/// Atendimentos sem queixa classificada
+pub const UNCLASSIFIED_DISEASE: &str = "nao_classificada";
+
+/// População de um bairro em uma faixa etária (limites inclusivos; sem `faixa_fim`, a faixa é aberta)
+#[derive(Debug, Clone, Serialize)]
+pub struct NeighborhoodPopulation {
+    pub ifrounidadeid: i32,
+    pub bairro: String,
+    pub faixa_inicio: i32,
+    pub faixa_fim: Option<i32>,
+    pub populacao: i64,
+    pub ano: Option<i32>,
+    pub fonte: Option<String>,
+    pub updated_by: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+/// Linha importada do CSV de população por bairro e faixa etária
+#[derive(Debug, Clone, PartialEq)]
+pub struct NeighborhoodPopulationEntry {
+    pub bairro: String,
+    pub faixa_inicio: i32,
+    pub faixa_fim: Option<i32>,
+    pub populacao: i64,
+}
+
+/// Ano e fonte (ex.: Censo IBGE 2022) da população importada
+#[derive(Debug, Default, Deserialize)]
+pub struct PopulationImportQuery {
+    pub ano: Option<i32>,
+    pub fonte: Option<String>,
+}
+
+/// Período e doença opcional das taxas de incidência por bairro
+#[derive(Debug, Default, Deserialize)]
+pub struct IncidenceQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub doenca: Option<String>,
+}
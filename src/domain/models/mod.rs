@@ -6,3 +6,16 @@ pub mod audit;
 pub mod prediction;
 pub mod visualization_data_graph;
 pub mod data_upa;
+pub mod reclassification;
+pub mod classification_review;
+
+pub mod time_series;
+pub mod surveillance;
+pub mod forecast;
+pub mod staffing;
+pub mod service_time;
+pub mod unit_comparison;
+pub mod hotspots;
+pub mod incidence;
+pub mod unit_chart_rules;
+pub mod health_unit;
\ No newline at end of file
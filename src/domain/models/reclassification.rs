@@ -0,0 +1,39 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use uuid::Uuid;
+
+pub const RECLASSIFICATION_STATUS_PENDING: &str = "pendente";
+pub const RECLASSIFICATION_STATUS_RUNNING: &str = "em_execucao";
+pub const RECLASSIFICATION_STATUS_FINISHED: &str = "concluido";
+pub const RECLASSIFICATION_STATUS_FAILED: &str = "falhou";
+
+#[derive(Debug, Deserialize)]
+pub struct CreateReclassificationJobDto {
+    pub unidade_id: i32,
+    pub competencia_inicio: String,
+    pub competencia_fim: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReclassificationJob {
+    pub id: Uuid,
+    pub ifrounidadeid: i32,
+    pub competencia_inicio: String,
+    pub competencia_fim: String,
+    pub status: String,
+    pub requested_by: String,
+    pub summary: Option<Value>,
+    pub error_message: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub finished_at: Option<NaiveDateTime>,
+}
+
+/// Registro de bpa com o texto original da conduta usado na reclassificação
+#[derive(Debug, Clone)]
+pub struct CondutaRecord {
+    pub ifroid: String,
+    pub ifrocompetencia: String,
+    pub conduta: String,
+    pub current_complaint: String,
+}
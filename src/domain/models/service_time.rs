@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+
+/// Remove durações acima das cercas de Tukey (Q3 + 1,5 × IQR)
+pub const OUTLIERS_IQR: &str = "iqr";
+/// Remove durações acima do percentil informado em `percentil_corte`
+pub const OUTLIERS_PERCENTILE: &str = "percentil";
+/// Mantém todas as durações
+pub const OUTLIERS_NONE: &str = "nenhum";
+
+/// Estatísticas por médico e período
+pub const VIEW_STATS: &str = "estatisticas";
+/// Histogramas por médico e período
+pub const VIEW_HISTOGRAM: &str = "histograma";
+
+/// Período, visão, remoção de valores extremos e largura das faixas do histograma de tempos de atendimento
+#[derive(Debug, Default, Deserialize)]
+pub struct ServiceTimeQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub outliers: Option<String>,
+    pub percentil_corte: Option<f64>,
+    pub max_minutos: Option<u32>,
+    pub largura_faixa: Option<u32>,
+    pub visao: Option<String>,
+}
+
+/// Estatísticas dos tempos de atendimento (em minutos) de um médico em um período
+#[derive(Debug, Clone, Serialize)]
+pub struct DurationSummary {
+    pub quantidade: i64,
+    pub removidos: i64,
+    pub media: f64,
+    pub mediana: f64,
+    pub p75: f64,
+    pub p90: f64,
+}
+
+/// Faixa do histograma de tempos de atendimento, com limite inferior inclusivo e superior exclusivo
+#[derive(Debug, Clone, Serialize)]
+pub struct HistogramBin {
+    pub inicio: u32,
+    pub fim: u32,
+    pub quantidade: i64,
+}
@@ -0,0 +1,21 @@
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Quantidade de consultas médicas iniciadas em uma hora de um dia
+#[derive(Debug, Clone)]
+pub struct HourlyArrivalCount {
+    pub data: NaiveDate,
+    pub hora: u32,
+    pub quantidade: i64,
+}
+
+/// Recomendação de médicos para uma faixa de duas horas de um dia da semana
+#[derive(Debug, Clone, Serialize)]
+pub struct StaffingSlot {
+    pub faixa_horaria: String,
+    pub chegadas_por_hora: f64,
+    pub carga_erlangs: f64,
+    pub medicos_recomendados: u32,
+    pub espera_media_minutos: f64,
+    pub probabilidade_espera: f64,
+}
@@ -0,0 +1,75 @@
+use chrono::{NaiveDate, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+pub const ALERT_STATUS_OPEN: &str = "aberto";
+pub const ALERT_STATUS_ACKNOWLEDGED: &str = "reconhecido";
+pub const ALERT_STATUS_DISMISSED: &str = "descartado";
+
+pub const ALERT_SEVERITY_LOW: &str = "baixa";
+pub const ALERT_SEVERITY_MEDIUM: &str = "media";
+pub const ALERT_SEVERITY_HIGH: &str = "alta";
+
+/// Quantidade de atendimentos de uma queixa principal em um bairro e dia
+#[derive(Debug, Clone)]
+pub struct NeighborhoodDailyCount {
+    pub data: NaiveDate,
+    pub queixa: String,
+    pub bairro: String,
+    pub quantidade: i64,
+}
+
+/// Sinal de aberração detectado em uma semana epidemiológica, antes de ser armazenado
+#[derive(Debug, Clone)]
+pub struct AlertSignal {
+    pub doenca: String,
+    /// Bairro da série. `None` para a série de toda a unidade
+    pub bairro: Option<String>,
+    pub semana: String,
+    pub semana_inicio: NaiveDate,
+    pub semana_fim: NaiveDate,
+    pub algoritmo: String,
+    pub severidade: String,
+    pub observado: i64,
+    pub baseline: AlertBaseline,
+    pub estatistica: f64,
+    pub limiar: f64,
+}
+
+/// Semanas usadas como referência no cálculo do sinal
+#[derive(Debug, Clone, Serialize)]
+pub struct AlertBaseline {
+    pub semana_inicio: String,
+    pub semana_fim: String,
+    pub media: f64,
+    pub desvio_padrao: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SurveillanceAlert {
+    pub id: Uuid,
+    pub ifrounidadeid: i32,
+    pub doenca: String,
+    pub bairro: Option<String>,
+    pub semana: String,
+    pub semana_inicio: NaiveDate,
+    pub semana_fim: NaiveDate,
+    pub algoritmo: String,
+    pub severidade: String,
+    pub observado: i64,
+    pub baseline: AlertBaseline,
+    pub estatistica: f64,
+    pub limiar: f64,
+    pub status: String,
+    pub status_updated_by: Option<String>,
+    pub status_updated_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct AlertQuery {
+    pub status: Option<String>,
+    pub severidade: Option<String>,
+    pub doenca: Option<String>,
+}
@@ -0,0 +1,57 @@
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+pub const GRANULARITY_DAILY: &str = "diario";
+pub const GRANULARITY_EPI_WEEK: &str = "semana_epidemiologica";
+
+/// Mapa de calor por doença, bairro e competência
+pub const VIEW_MAP: &str = "mapa";
+/// Série mensal de uma doença por bairro, a partir do mapa de calor
+pub const VIEW_TREND: &str = "tendencia";
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TimeSeriesQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub doenca: Option<String>,
+}
+
+/// Série mensal de uma doença por bairro (`visao=tendencia` do mapa de calor por doença): `bairros`
+/// separados por vírgula (todos quando ausente), `media_movel` com a janela em meses e `acumulado`
+/// para a soma desde o início do período
+#[derive(Debug, Default, Deserialize)]
+pub struct NeighborhoodTrendQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub visao: Option<String>,
+    pub doenca: Option<String>,
+    pub bairros: Option<String>,
+    pub media_movel: Option<usize>,
+    pub acumulado: Option<bool>,
+}
+
+/// Quantidade de atendimentos de uma queixa principal em um dia
+#[derive(Debug, Clone)]
+pub struct DailyCount {
+    pub data: NaiveDate,
+    pub queixa: String,
+    pub quantidade: i64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TimeSeriesPoint {
+    pub periodo: String,
+    pub inicio: NaiveDate,
+    pub fim: NaiveDate,
+    pub quantidade: i64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TimeSeries {
+    pub granularidade: String,
+    pub inicio: NaiveDate,
+    pub fim: NaiveDate,
+    pub total: Vec<TimeSeriesPoint>,
+    pub doencas: BTreeMap<String, Vec<TimeSeriesPoint>>,
+}
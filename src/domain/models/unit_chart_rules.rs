@@ -0,0 +1,47 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Regras de montagem dos gráficos de uma unidade, editáveis pela API
+#[derive(Debug, Clone, Serialize)]
+pub struct UnitChartRules {
+    pub ifrounidadeid: i32,
+    /// Padrões de endereço (sintaxe do ILIKE, com `%` como curinga) ignorados nos mapas
+    pub enderecos_excluidos: Vec<String>,
+    /// Bairros genéricos (ex.: "NAO INFORMADO") ignorados nos mapas
+    pub bairros_genericos: Vec<String>,
+    /// Gráficos (pelo nome da rota) que a unidade não suporta
+    pub graficos_nao_suportados: Vec<String>,
+    pub updated_by: Option<String>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// Endereços fora dos mapas de todas as unidades sem regras cadastradas
+pub const DEFAULT_EXCLUDED_ADDRESSES: [&str; 1] = ["DO IPE"];
+
+impl UnitChartRules {
+    /// Regras de uma unidade sem cadastro: os endereços padrão excluídos e todos os gráficos
+    pub fn defaults(unidade_id: i32) -> Self {
+        Self {
+            ifrounidadeid: unidade_id,
+            enderecos_excluidos: DEFAULT_EXCLUDED_ADDRESSES.iter().map(|address| address.to_string()).collect(),
+            bairros_genericos: Vec::new(),
+            graficos_nao_suportados: Vec::new(),
+            updated_by: None,
+            updated_at: None,
+        }
+    }
+
+    pub fn supports(&self, slug: &str) -> bool {
+        !self.graficos_nao_suportados.iter().any(|s| s == slug)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnitChartRulesRequest {
+    #[serde(default)]
+    pub enderecos_excluidos: Vec<String>,
+    #[serde(default)]
+    pub bairros_genericos: Vec<String>,
+    #[serde(default)]
+    pub graficos_nao_suportados: Vec<String>,
+}
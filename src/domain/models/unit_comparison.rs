@@ -0,0 +1,39 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+
+/// Valores absolutos
+pub const NORMALIZATION_NONE: &str = "nenhuma";
+/// Percentual sobre o total de atendimentos da unidade no período
+pub const NORMALIZATION_SHARE: &str = "participacao";
+/// Taxa por mil habitantes da população de referência da unidade
+pub const NORMALIZATION_PER_CAPITA: &str = "per_capita";
+
+// Os valores por habitante são expressos por mil habitantes
+pub const PER_CAPITA_BASE: f64 = 1000.0;
+
+/// Unidades (separadas por vírgula), período e normalização da comparação de um gráfico
+#[derive(Debug, Default, Deserialize)]
+pub struct UnitComparisonQuery {
+    pub units: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub normalizacao: Option<String>,
+}
+
+/// População de referência da área atendida por uma unidade
+#[derive(Debug, Clone, Serialize)]
+pub struct UnitPopulation {
+    pub ifrounidadeid: i32,
+    pub populacao: i64,
+    pub ano: Option<i32>,
+    pub fonte: Option<String>,
+    pub updated_by: Option<String>,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UnitPopulationRequest {
+    pub populacao: i64,
+    pub ano: Option<i32>,
+    pub fonte: Option<String>,
+}
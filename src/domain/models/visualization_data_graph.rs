@@ -2,4 +2,65 @@
 pub struct DataAccessParams {
     pub user_id: String, 
     pub unidade_id: i32,
+}
+
+/// Parâmetros da URL da rota genérica de gráficos, interpretados conforme o gráfico consultado,
+/// e o cabeçalho `Accept` da requisição
+#[derive(Debug, Default)]
+pub struct ChartRequest {
+    pub query_string: String,
+    pub accept: Option<String>,
+}
+
+/// Período opcional (AAAA-MM) aplicado às rotas de gráficos
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ChartPeriodQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// Período e faixas etárias (esquema nomeado ou limites personalizados) dos gráficos por idade
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct AgeBandQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub faixas: Option<String>,
+    pub limites: Option<String>,
+}
+
+/// Período e quantidade de procedimentos do ranking de procedimentos
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ProcedureRankingQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub top: Option<usize>,
+}
+
+/// Período e etapas (`ifrotabelanome`, separadas por vírgula e em ordem) do funil de fluxos
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct FlowFunnelQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub etapas: Option<String>,
+}
+
+/// Período e comparação opcional (previous_period ou previous_year) dos gráficos por competência
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ChartComparisonQuery {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub compare: Option<String>,
+}
+
+/// Comparação opcional (previous_period ou previous_year) do gráfico de atendimentos por ano
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ComparisonQuery {
+    pub compare: Option<String>,
+}
+
+/// Formato de saída (json ou geojson) e medida (casos ou taxa por mil habitantes) dos mapas de calor
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct HeatMapOutputQuery {
+    pub format: Option<String>,
+    pub medida: Option<String>,
 }
\ No newline at end of file
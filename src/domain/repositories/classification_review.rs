@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+use crate::domain::models::classification_review::{
+    ClassificationCorrection,
+    ClassificationReviewFilter,
+    ClassifiedRecord,
+    CreateClassificationCorrectionDto,
+};
+
+#[async_trait]
+pub trait ClassificationReviewRepository: Send + Sync + 'static {
+    async fn fetch_classified_records(&self, page: i32, filter: &ClassificationReviewFilter) -> Result<(Vec<ClassifiedRecord>, i64), sqlx::Error>;
+    async fn find_classified_record(&self, unidade_id: i32, ifroid: &str) -> Result<Option<ClassifiedRecord>, sqlx::Error>;
+    async fn create_correction(&self, record: &ClassifiedRecord, correction: &CreateClassificationCorrectionDto, corrected_by: &str) -> Result<ClassificationCorrection, sqlx::Error>;
+    async fn fetch_corrections(&self, unidade_id: Option<i32>) -> Result<Vec<ClassificationCorrection>, sqlx::Error>;
+}
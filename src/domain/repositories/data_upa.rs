@@ -1,4 +1,8 @@
 use async_trait::async_trait;
+use crate::domain::models::data_upa::GroupedCount;
+use crate::domain::models::staffing::HourlyArrivalCount;
+use crate::domain::models::time_series::DailyCount;
+use chrono::NaiveDate;
 use polars::frame::DataFrame;
 use std::collections::HashMap;
 use std::error::Error;
@@ -10,12 +14,37 @@ pub trait DataRepository: Send + Sync + 'static {
     async fn check_ifrocompetencia_exists(&self, table: &str, competencia_values: &[String]) -> Result<bool, Box<dyn Error + Send + Sync>>;
     async fn create_table_if_not_exists(&self, df: &DataFrame, table: &str) -> Result<bool, Box<dyn Error + Send + Sync>>;
     async fn insert_data(&self, df: &DataFrame, table: &str) -> Result<bool, Box<dyn Error + Send + Sync>>;
+    async fn insert_conduta_records(&self, df: &DataFrame) -> Result<(), Box<dyn Error + Send + Sync>>;
     async fn fetch_columns_by_name(&self, table: &str, columns: &[String]) -> Result<HashMap<String, Vec<Value>>, Box<dyn Error + Send + Sync>>;
     async fn insert_nested_json(&self, data: Value, table: &str, identifier: &str) -> Result<HashMap<String, Value>, Box<dyn Error + Send + Sync>>;
-    async fn fetch_nested_json(&self, table: &str, identifier: &str, unidade_id: Option<i32>) -> Result<serde_json::Map<String, serde_json::Value>, Box<dyn Error + Send + Sync>>;
-    async fn fetch_distinct_values(&self, table: &str, column: &str) -> Result<Vec<i32>, Box<dyn Error + Send + Sync>>;
+    async fn fetch_nested_json(&self, table: &str, identifier: &str, unidade_id: i32) -> Result<serde_json::Map<String, serde_json::Value>, Box<dyn Error + Send + Sync>>;
     async fn fetch_columns_by_name_with_filter(&self, table: &str, columns: &[String], filter_column: &str, filter_value: i32) -> Result<HashMap<String, Vec<Value>>, Box<dyn Error + Send + Sync>>;
     async fn insert_nested_json_with_unit(&self, data: Value, table: &str, identifier: &str, unidade_id: i32) -> Result<HashMap<String, Value>, Box<dyn Error + Send + Sync>>;
     async fn check_unit_data_exists(&self, table: &str, identifier: &str, unidade_id: i32) -> Result<bool, Box<dyn Error + Send + Sync>>;
-    async fn fetch_distinct_health_units(&self, table: &str, columns: &[String]) -> Result<HashMap<String, Vec<Value>>, Box<dyn Error + Send + Sync>>;
+    async fn fetch_columns_by_name_with_filter_and_competencias(&self, table: &str, columns: &[String], filter_column: &str, filter_value: i32, competencias: &[String]) -> Result<HashMap<String, Vec<Value>>, Box<dyn Error + Send + Sync>>;
+    async fn insert_graph_data_slice(&self, data: Value, identifier: &str, unidade_id: i32, competencia: &str) -> Result<(), Box<dyn Error + Send + Sync>>;
+    async fn fetch_graph_data_slices(&self, identifier: &str, unidade_id: i32) -> Result<Vec<(String, Value)>, Box<dyn Error + Send + Sync>>;
+    async fn check_graph_data_slices_exist(&self, identifier: &str, unidade_id: i32) -> Result<bool, Box<dyn Error + Send + Sync>>;
+    async fn delete_graph_data_slices(&self, identifier: &str, unidade_id: i32, competencias: Option<&[String]>) -> Result<(), Box<dyn Error + Send + Sync>>;
+    async fn mark_competencias_pending(&self, unidade_id: i32, competencias: &[String]) -> Result<(), Box<dyn Error + Send + Sync>>;
+    async fn fetch_pending_competencias(&self) -> Result<HashMap<i32, Vec<String>>, Box<dyn Error + Send + Sync>>;
+    async fn clear_pending_competencias(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<(), Box<dyn Error + Send + Sync>>;
+    async fn fetch_competencias_by_unit(&self, unidade_id: i32) -> Result<Vec<String>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_and_column(&self, column: &str, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_and_hour(&self, cbo: &str, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_and_professional(&self, cbo: &str, tabela: &str, excluded_names: &[String], unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_and_location(&self, group_column: Option<&str>, excluded_addresses: &[String], placeholder_neighborhoods: &[String], unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_weekday_and_hour(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_sex_and_age(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_complaint_and_age(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_complaint_and_point(&self, excluded_addresses: &[String], placeholder_neighborhoods: &[String], unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_neighborhood_complaint_and_age(&self, excluded_addresses: &[String], placeholder_neighborhoods: &[String], unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_and_procedure(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_and_municipality(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn count_visits_by_competencia_stages_and_arrival(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
+    async fn fetch_daily_counts(&self, unidade_id: i32, from: Option<NaiveDate>, to: Option<NaiveDate>, doenca: Option<&str>) -> Result<Vec<DailyCount>, Box<dyn Error + Send + Sync>>;
+    async fn fetch_hourly_arrivals(&self, cbos: &[&str], tabela: &str, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<HourlyArrivalCount>, Box<dyn Error + Send + Sync>>;
+    async fn average_service_minutes(&self, cbos: &[&str], tabela: &str, excluded_names: &[String], unidade_id: i32, competencias: Option<&[String]>) -> Result<Option<f64>, Box<dyn Error + Send + Sync>>;
+    async fn count_by_competencia_professional_and_duration(&self, cbos: &[&str], tabela: &str, excluded_names: &[String], unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>>;
 }
\ No newline at end of file
@@ -0,0 +1,14 @@
+use async_trait::async_trait;
+
+use crate::domain::models::health_unit::{HealthUnitRecord, HealthUnitRequest};
+
+#[async_trait]
+pub trait HealthUnitRepository: Send + Sync + 'static {
+    async fn fetch_units(&self, ativo: Option<bool>) -> Result<Vec<HealthUnitRecord>, sqlx::Error>;
+    async fn find_unit(&self, unidade_id: i32) -> Result<Option<HealthUnitRecord>, sqlx::Error>;
+    async fn create_unit(&self, unidade_id: i32, unit: &HealthUnitRequest) -> Result<HealthUnitRecord, sqlx::Error>;
+    async fn update_unit(&self, unidade_id: i32, unit: &HealthUnitRequest) -> Result<Option<HealthUnitRecord>, sqlx::Error>;
+    async fn delete_unit(&self, unidade_id: i32) -> Result<bool, sqlx::Error>;
+    /// Cadastra as unidades dos dados importados que ainda não estão no cadastro, com o nome da tabela bpa
+    async fn register_imported_units(&self, unidade_ids: &[i32]) -> Result<u64, sqlx::Error>;
+}
@@ -3,3 +3,10 @@ pub mod machine_information;
 pub mod auth_pronto;
 pub mod data_upa;
 pub mod audit;
+pub mod reclassification;
+pub mod classification_review;
+pub mod surveillance;
+
+pub mod unit_population;
+pub mod unit_chart_rules;
+pub mod health_unit;
\ No newline at end of file
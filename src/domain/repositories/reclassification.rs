@@ -0,0 +1,15 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use uuid::Uuid;
+use crate::domain::models::reclassification::{CondutaRecord, CreateReclassificationJobDto, ReclassificationJob};
+
+#[async_trait]
+pub trait ReclassificationRepository: Send + Sync + 'static {
+    async fn create_job(&self, job: &CreateReclassificationJobDto, requested_by: &str) -> Result<Uuid, sqlx::Error>;
+    async fn update_job_status(&self, id: Uuid, status: &str) -> Result<(), sqlx::Error>;
+    async fn finish_job(&self, id: Uuid, status: &str, summary: Option<Value>, error_message: Option<String>) -> Result<(), sqlx::Error>;
+    async fn find_job(&self, id: Uuid) -> Result<Option<ReclassificationJob>, sqlx::Error>;
+    async fn fetch_competencias(&self, unidade_id: i32) -> Result<Vec<String>, sqlx::Error>;
+    async fn fetch_conduta_records(&self, unidade_id: i32, competencias: &[String]) -> Result<Vec<CondutaRecord>, sqlx::Error>;
+    async fn update_complaints(&self, unidade_id: i32, classifications: &[(String, String, f64)]) -> Result<u64, sqlx::Error>;
+}
@@ -0,0 +1,17 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use uuid::Uuid;
+
+use crate::domain::models::surveillance::{AlertQuery, AlertSignal, NeighborhoodDailyCount, SurveillanceAlert};
+
+#[async_trait]
+pub trait SurveillanceRepository: Send + Sync + 'static {
+    /// Contagens diárias da unidade, limitadas ao período informado (datas inclusivas)
+    async fn fetch_daily_counts_by_neighborhood(&self, unidade_id: i32, period: Option<(NaiveDate, NaiveDate)>) -> Result<Vec<NeighborhoodDailyCount>, sqlx::Error>;
+    /// Dia do primeiro atendimento da unidade considerado pela vigilância, início do cálculo completo
+    async fn fetch_first_attendance_day(&self, unidade_id: i32) -> Result<Option<NaiveDate>, sqlx::Error>;
+    /// Salva os sinais e remove os alertas abertos que deixaram de ser sinalizados nas semanas do período
+    async fn sync_alerts(&self, unidade_id: i32, signals: &[AlertSignal], period: Option<(NaiveDate, NaiveDate)>) -> Result<u64, sqlx::Error>;
+    async fn fetch_alerts(&self, unidade_id: i32, query: &AlertQuery) -> Result<Vec<SurveillanceAlert>, sqlx::Error>;
+    async fn update_alert_status(&self, unidade_id: i32, alert_id: Uuid, status: &str, updated_by: &str) -> Result<Option<SurveillanceAlert>, sqlx::Error>;
+}
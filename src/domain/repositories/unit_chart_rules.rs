@@ -0,0 +1,10 @@
+use async_trait::async_trait;
+
+use crate::domain::models::unit_chart_rules::{UnitChartRules, UnitChartRulesRequest};
+
+#[async_trait]
+pub trait UnitChartRulesRepository: Send + Sync + 'static {
+    /// Regras da unidade, ou as regras padrão quando a unidade não tem cadastro
+    async fn fetch_rules(&self, unidade_id: i32) -> Result<UnitChartRules, sqlx::Error>;
+    async fn upsert_rules(&self, unidade_id: i32, rules: &UnitChartRulesRequest, updated_by: &str) -> Result<UnitChartRules, sqlx::Error>;
+}
@@ -0,0 +1,19 @@
+use async_trait::async_trait;
+
+use crate::domain::models::incidence::{NeighborhoodPopulation, NeighborhoodPopulationEntry};
+use crate::domain::models::unit_comparison::{UnitPopulation, UnitPopulationRequest};
+
+#[async_trait]
+pub trait UnitPopulationRepository: Send + Sync + 'static {
+    async fn fetch_populations(&self, unidade_ids: &[i32]) -> Result<Vec<UnitPopulation>, sqlx::Error>;
+    async fn upsert_population(&self, unidade_id: i32, population: &UnitPopulationRequest, updated_by: &str) -> Result<UnitPopulation, sqlx::Error>;
+    async fn fetch_neighborhood_populations(&self, unidade_id: i32) -> Result<Vec<NeighborhoodPopulation>, sqlx::Error>;
+    async fn replace_neighborhood_populations(
+        &self,
+        unidade_id: i32,
+        entries: &[NeighborhoodPopulationEntry],
+        ano: Option<i32>,
+        fonte: Option<&str>,
+        updated_by: &str,
+    ) -> Result<u64, sqlx::Error>;
+}
@@ -0,0 +1,37 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use crate::{
+    application::classification_review_service::ClassificationReviewService,
+    domain::models::{
+        auth::Claims,
+        classification_review::{ClassificationReviewQuery, CorrectionExportQuery, CreateClassificationCorrectionDto},
+    },
+    AppError,
+};
+
+pub async fn classified_records(
+    service: web::Data<ClassificationReviewService>,
+    path: web::Path<i32>,
+    query: web::Query<ClassificationReviewQuery>,
+) -> Result<HttpResponse, AppError> {
+    service.classified_records(path.into_inner(), query.into_inner()).await
+}
+
+pub async fn create_correction(
+    service: web::Data<ClassificationReviewService>,
+    req: HttpRequest,
+    correction: web::Json<CreateClassificationCorrectionDto>,
+) -> Result<HttpResponse, AppError> {
+    let corrected_by = req.extensions()
+        .get::<Claims>()
+        .map(|claims| claims.email.clone())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    service.create_correction(correction.into_inner(), corrected_by).await
+}
+
+pub async fn export_corrections(
+    service: web::Data<ClassificationReviewService>,
+    query: web::Query<CorrectionExportQuery>,
+) -> Result<HttpResponse, AppError> {
+    service.export_corrections(query.into_inner()).await
+}
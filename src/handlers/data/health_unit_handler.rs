@@ -0,0 +1,42 @@
+use actix_web::{web, HttpResponse};
+use crate::{
+    application::health_unit_service::HealthUnitService,
+    domain::models::health_unit::{HealthUnitQuery, HealthUnitRequest},
+    AppError,
+};
+
+pub async fn health_units(
+    service: web::Data<HealthUnitService>,
+    query: web::Query<HealthUnitQuery>,
+) -> Result<HttpResponse, AppError> {
+    service.units(query.into_inner()).await
+}
+
+pub async fn health_unit(
+    service: web::Data<HealthUnitService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    service.unit(path.into_inner()).await
+}
+
+pub async fn create_health_unit(
+    service: web::Data<HealthUnitService>,
+    body: web::Json<HealthUnitRequest>,
+) -> Result<HttpResponse, AppError> {
+    service.create_unit(body.into_inner()).await
+}
+
+pub async fn update_health_unit(
+    service: web::Data<HealthUnitService>,
+    path: web::Path<i32>,
+    body: web::Json<HealthUnitRequest>,
+) -> Result<HttpResponse, AppError> {
+    service.update_unit(path.into_inner(), body.into_inner()).await
+}
+
+pub async fn delete_health_unit(
+    service: web::Data<HealthUnitService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    service.delete_unit(path.into_inner()).await
+}
@@ -1,3 +1,10 @@
 pub mod data_upa_handler;
 pub mod update_graph_data_handler;
-pub mod visualization_data_handler;
\ No newline at end of file
+pub mod visualization_data_handler;
+pub mod reclassification_handler;
+pub mod classification_review_handler;
+pub mod surveillance_handler;
+pub mod unit_comparison_handler;
+pub mod unit_chart_rules_handler;
+pub mod population_handler;
+pub mod health_unit_handler;
\ No newline at end of file
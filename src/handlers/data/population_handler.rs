@@ -0,0 +1,78 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use actix_multipart::Multipart;
+use futures::StreamExt;
+use log::error;
+use crate::{
+    application::visualization_data_service::VisualizationDataService,
+    domain::models::{
+        auth::Claims,
+        incidence::PopulationImportQuery,
+        unit_comparison::UnitPopulationRequest,
+    },
+    AppError,
+};
+
+fn requester_email(req: &HttpRequest) -> String {
+    req.extensions()
+        .get::<Claims>()
+        .map(|claims| claims.email.clone())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+pub async fn unit_population(
+    service: web::Data<VisualizationDataService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    service.unit_population(path.into_inner()).await
+}
+
+pub async fn set_unit_population(
+    service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+    body: web::Json<UnitPopulationRequest>,
+) -> Result<HttpResponse, AppError> {
+    service.set_unit_population(path.into_inner(), body.into_inner(), requester_email(&req)).await
+}
+
+pub async fn neighborhood_population(
+    service: web::Data<VisualizationDataService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    service.neighborhood_population(path.into_inner()).await
+}
+
+pub async fn import_neighborhood_population(
+    service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+    query: web::Query<PopulationImportQuery>,
+    mut payload: Multipart,
+) -> Result<HttpResponse, AppError> {
+    // CSV enviado no campo "file", como no upload de dados
+    let mut file_data = web::BytesMut::new();
+
+    while let Some(item) = payload.next().await {
+        let mut field = item.map_err(|e| {
+            error!("Erro ao processar campo do multipart: {:?}", e);
+            AppError::BadRequest("Erro ao processar arquivo enviado".to_string())
+        })?;
+
+        if field.name() == Some("file") {
+            while let Some(chunk) = field.next().await {
+                let data = chunk.map_err(|e| {
+                    error!("Erro ao ler chunk do arquivo: {:?}", e);
+                    AppError::BadRequest("Erro ao ler arquivo enviado".to_string())
+                })?;
+                file_data.extend_from_slice(&data);
+            }
+            break;
+        }
+    }
+
+    if file_data.is_empty() {
+        return Err(AppError::BadRequest("Nenhum arquivo foi enviado".to_string()));
+    }
+
+    service.import_neighborhood_population(path.into_inner(), file_data.freeze(), query.into_inner(), requester_email(&req)).await
+}
@@ -0,0 +1,36 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use uuid::Uuid;
+use crate::{
+    application::reclassification_service::ReclassificationService,
+    domain::models::{auth::Claims, reclassification::CreateReclassificationJobDto},
+    AppError,
+};
+
+// Unidades liberadas no token de quem fez a requisição
+fn requester_units(req: &HttpRequest) -> Vec<i64> {
+    req.extensions()
+        .get::<Claims>()
+        .map(|claims| claims.allowed_health_units.clone())
+        .unwrap_or_default()
+}
+
+pub async fn start_reclassification(
+    service: web::Data<ReclassificationService>,
+    req: HttpRequest,
+    job: web::Json<CreateReclassificationJobDto>,
+) -> Result<HttpResponse, AppError> {
+    let requested_by = req.extensions()
+        .get::<Claims>()
+        .map(|claims| claims.email.clone())
+        .unwrap_or_else(|| "unknown".to_string());
+
+    service.start_job(job.into_inner(), requested_by, &requester_units(&req)).await
+}
+
+pub async fn reclassification_job(
+    service: web::Data<ReclassificationService>,
+    req: HttpRequest,
+    path: web::Path<Uuid>,
+) -> Result<HttpResponse, AppError> {
+    service.get_job(path.into_inner(), &requester_units(&req)).await
+}
@@ -0,0 +1,57 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use uuid::Uuid;
+use crate::{
+    application::surveillance_service::SurveillanceService,
+    domain::models::{auth::Claims, surveillance::AlertQuery},
+    AppError,
+};
+
+fn requester_email(req: &HttpRequest) -> String {
+    req.extensions()
+        .get::<Claims>()
+        .map(|claims| claims.email.clone())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+// A unidade precisa estar em allowed_health_units do token de quem fez a requisição
+fn validate_unit_access(req: &HttpRequest, unidade_id: i32) -> Result<(), AppError> {
+    let allowed = req.extensions()
+        .get::<Claims>()
+        .map(|claims| claims.allowed_health_units.contains(&(unidade_id as i64)))
+        .unwrap_or(false);
+
+    if !allowed {
+        return Err(AppError::Forbidden(format!("User does not have access to unit {}", unidade_id)));
+    }
+    Ok(())
+}
+
+pub async fn alerts(
+    service: web::Data<SurveillanceService>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+    query: web::Query<AlertQuery>,
+) -> Result<HttpResponse, AppError> {
+    let unidade_id = path.into_inner();
+    validate_unit_access(&req, unidade_id)?;
+
+    service.alerts(unidade_id, query.into_inner()).await
+}
+
+pub async fn acknowledge_alert(
+    service: web::Data<SurveillanceService>,
+    req: HttpRequest,
+    path: web::Path<(i32, Uuid)>, // (unidade_id, alert_id)
+) -> Result<HttpResponse, AppError> {
+    let (unidade_id, alert_id) = path.into_inner();
+    service.acknowledge_alert(unidade_id, alert_id, requester_email(&req)).await
+}
+
+pub async fn dismiss_alert(
+    service: web::Data<SurveillanceService>,
+    req: HttpRequest,
+    path: web::Path<(i32, Uuid)>, // (unidade_id, alert_id)
+) -> Result<HttpResponse, AppError> {
+    let (unidade_id, alert_id) = path.into_inner();
+    service.dismiss_alert(unidade_id, alert_id, requester_email(&req)).await
+}
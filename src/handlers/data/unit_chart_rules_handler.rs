@@ -0,0 +1,29 @@
+use actix_web::{web, HttpMessage, HttpRequest, HttpResponse};
+use crate::{
+    application::visualization_data_service::VisualizationDataService,
+    domain::models::{auth::Claims, unit_chart_rules::UnitChartRulesRequest},
+    AppError,
+};
+
+fn requester_email(req: &HttpRequest) -> String {
+    req.extensions()
+        .get::<Claims>()
+        .map(|claims| claims.email.clone())
+        .unwrap_or_else(|| "unknown".to_string())
+}
+
+pub async fn unit_chart_rules(
+    service: web::Data<VisualizationDataService>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse, AppError> {
+    service.unit_chart_rules(path.into_inner()).await
+}
+
+pub async fn set_unit_chart_rules(
+    service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
+    path: web::Path<i32>,
+    body: web::Json<UnitChartRulesRequest>,
+) -> Result<HttpResponse, AppError> {
+    service.set_unit_chart_rules(path.into_inner(), body.into_inner(), requester_email(&req)).await
+}
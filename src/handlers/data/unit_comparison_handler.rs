@@ -0,0 +1,15 @@
+use actix_web::{web, HttpResponse};
+use crate::{
+    application::visualization_data_service::VisualizationDataService,
+    domain::models::unit_comparison::UnitComparisonQuery,
+    AppError,
+};
+
+pub async fn compare_units(
+    service: web::Data<VisualizationDataService>,
+    path: web::Path<(String, String)>, // (user_id, name)
+    query: web::Query<UnitComparisonQuery>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, name) = path.into_inner();
+    service.compare_units(user_id, &name, query.into_inner()).await
+}
@@ -1,9 +1,11 @@
 use actix_web::{web, HttpResponse};
 use crate::application::update_graph_data_service::UpdateGraphDataService;
+use crate::domain::models::data_upa::UpdateGraphDataQuery;
 use crate::AppError;
 
 pub async fn update_graph_data(
     service: web::Data<UpdateGraphDataService>,
+    query: web::Query<UpdateGraphDataQuery>,
 ) -> Result<HttpResponse, AppError> {
-    service.update_data().await
+    service.update_data(query.full.unwrap_or(false)).await
 }
\ No newline at end of file
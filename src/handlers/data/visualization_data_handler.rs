@@ -1,31 +1,43 @@
-use actix_web::{web, HttpResponse};
+use actix_web::{http::header, web, HttpRequest, HttpResponse};
 use crate::application::visualization_data_service::VisualizationDataService;
+use crate::domain::models::time_series::{TimeSeriesQuery, GRANULARITY_DAILY, GRANULARITY_EPI_WEEK};
+use crate::domain::models::visualization_data_graph::{ChartComparisonQuery, ChartRequest, ComparisonQuery};
 use crate::AppError;
 
 
 
+// Parâmetros da URL e cabeçalho `Accept`, interpretados no serviço conforme o gráfico consultado
+fn chart_request(req: &HttpRequest) -> ChartRequest {
+    ChartRequest {
+        query_string: req.query_string().to_string(),
+        accept: req.headers().get(header::ACCEPT).and_then(|value| value.to_str().ok()).map(String::from),
+    }
+}
+
 pub async fn number_of_appointments_per_month(
     service: web::Data<VisualizationDataService>,
     path: web::Path<(String, String)>, // (user_id, unidade_id)
+    query: web::Query<ChartComparisonQuery>,
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id) = path.into_inner();
     
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.number_of_appointments_per_month(user_id, unidade_id).await
+    service.number_of_appointments_per_month(user_id, unidade_id, query.into_inner()).await
 }
 
 pub async fn number_of_appointments_per_year(
     service: web::Data<VisualizationDataService>,
     path: web::Path<(String, String, String)>, // (user_id, unidade_id, year)
+    query: web::Query<ComparisonQuery>,
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id, year) = path.into_inner();
     
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.number_of_appointments_per_year(user_id, unidade_id, year).await
+    service.number_of_appointments_per_year(user_id, unidade_id, year, query.into_inner()).await
 }
 
 pub async fn years_available_for_number_of_appointments_per_month(
@@ -43,17 +55,19 @@ pub async fn years_available_for_number_of_appointments_per_month(
 pub async fn number_of_appointments_per_flow(
     service: web::Data<VisualizationDataService>,
     path: web::Path<(String, String)>, // (user_id, unidade_id)
+    query: web::Query<ChartComparisonQuery>,
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id) = path.into_inner();
     
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.number_of_appointments_per_flow(user_id, unidade_id).await
+    service.number_of_appointments_per_flow(user_id, unidade_id, query.into_inner()).await
 }
 
 pub async fn distribuition_of_patients_ages(
     service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
     path: web::Path<(String, String)>, // (user_id, unidade_id)
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id) = path.into_inner();
@@ -61,11 +75,12 @@ pub async fn distribuition_of_patients_ages(
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.distribuition_of_patients_ages(user_id, unidade_id).await
+    service.distribuition_of_patients_ages(user_id, unidade_id, chart_request(&req)).await
 }
 
 pub async fn number_of_calls_per_day_of_the_week(
     service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
     path: web::Path<(String, String)>, // (user_id, unidade_id)
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id) = path.into_inner();
@@ -73,11 +88,12 @@ pub async fn number_of_calls_per_day_of_the_week(
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.number_of_calls_per_day_of_the_week(user_id, unidade_id).await
+    service.number_of_calls_per_day_of_the_week(user_id, unidade_id, chart_request(&req)).await
 }
 
 pub async fn distribution_of_services_by_hour_group(
     service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
     path: web::Path<(String, String)>, // (user_id, unidade_id)
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id) = path.into_inner();
@@ -85,11 +101,12 @@ pub async fn distribution_of_services_by_hour_group(
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.distribution_of_services_by_hour_group(user_id, unidade_id).await
+    service.distribution_of_services_by_hour_group(user_id, unidade_id, chart_request(&req)).await
 }
 
 pub async fn number_of_visits_per_nurse(
     service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
     path: web::Path<(String, String)>, // (user_id, unidade_id)
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id) = path.into_inner();
@@ -97,11 +114,12 @@ pub async fn number_of_visits_per_nurse(
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.number_of_visits_per_nurse(user_id, unidade_id).await
+    service.number_of_visits_per_nurse(user_id, unidade_id, chart_request(&req)).await
 }
 
 pub async fn number_of_visits_per_doctor(
     service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
     path: web::Path<(String, String)>, // (user_id, unidade_id)
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id) = path.into_inner();
@@ -109,11 +127,12 @@ pub async fn number_of_visits_per_doctor(
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.number_of_visits_per_doctor(user_id, unidade_id).await
+    service.number_of_visits_per_doctor(user_id, unidade_id, chart_request(&req)).await
 }
 
 pub async fn average_time_in_minutes_per_doctor(
     service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
     path: web::Path<(String, String)>, // (user_id, unidade_id)
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id) = path.into_inner();
@@ -121,11 +140,12 @@ pub async fn average_time_in_minutes_per_doctor(
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.average_time_in_minutes_per_doctor(user_id, unidade_id).await
+    service.average_time_in_minutes_per_doctor(user_id, unidade_id, chart_request(&req)).await
 }
 
 pub async fn heat_map_with_disease_indication(
     service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
     path: web::Path<(String, String)>, // (user_id, unidade_id)
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id) = path.into_inner();
@@ -133,11 +153,12 @@ pub async fn heat_map_with_disease_indication(
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.heat_map_with_disease_indication(user_id, unidade_id).await
+    service.heat_map_with_disease_indication(user_id, unidade_id, chart_request(&req)).await
 }
 
 pub async fn heat_map_with_the_number_of_medical_appointments_by_neighborhood(
     service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
     path: web::Path<(String, String)>, // (user_id, unidade_id)
 ) -> Result<HttpResponse, AppError> {
     let (user_id, unidade_id) = path.into_inner();
@@ -145,5 +166,44 @@ pub async fn heat_map_with_the_number_of_medical_appointments_by_neighborhood(
     let unidade_id: i32 = unidade_id.parse()
         .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
     
-    service.heat_map_with_the_number_of_medical_appointments_by_neighborhood(user_id, unidade_id).await
-}
\ No newline at end of file
+    service.heat_map_with_the_number_of_medical_appointments_by_neighborhood(user_id, unidade_id, chart_request(&req)).await
+}
+
+pub async fn chart(
+    service: web::Data<VisualizationDataService>,
+    req: HttpRequest,
+    path: web::Path<(String, String, String)>, // (user_id, unidade_id, name)
+) -> Result<HttpResponse, AppError> {
+    let (user_id, unidade_id, name) = path.into_inner();
+    
+    let unidade_id: i32 = unidade_id.parse()
+        .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
+    
+    service.chart(user_id, unidade_id, &name, chart_request(&req)).await
+}
+
+pub async fn daily_time_series(
+    service: web::Data<VisualizationDataService>,
+    path: web::Path<(String, String)>, // (user_id, unidade_id)
+    query: web::Query<TimeSeriesQuery>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, unidade_id) = path.into_inner();
+    
+    let unidade_id: i32 = unidade_id.parse()
+        .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
+    
+    service.time_series(user_id, unidade_id, GRANULARITY_DAILY, query.into_inner()).await
+}
+
+pub async fn epi_week_time_series(
+    service: web::Data<VisualizationDataService>,
+    path: web::Path<(String, String)>, // (user_id, unidade_id)
+    query: web::Query<TimeSeriesQuery>,
+) -> Result<HttpResponse, AppError> {
+    let (user_id, unidade_id) = path.into_inner();
+    
+    let unidade_id: i32 = unidade_id.parse()
+        .map_err(|_| AppError::BadRequest("Invalid unit ID format".to_string()))?;
+    
+    service.time_series(user_id, unidade_id, GRANULARITY_EPI_WEEK, query.into_inner()).await
+}
@@ -0,0 +1,225 @@
+use async_trait::async_trait;
+use log::info;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::domain::models::classification_review::{
+    ClassificationCorrection,
+    ClassificationReviewFilter,
+    ClassifiedRecord,
+    CreateClassificationCorrectionDto,
+};
+use crate::domain::repositories::classification_review::ClassificationReviewRepository;
+
+const CLASSIFIED_RECORD_COLUMNS: &str = "c.ifroid::text AS ifroid,
+    b.ifrounidadeid::integer AS ifrounidadeid,
+    COALESCE(b.ifrocompetencia::text, '') AS ifrocompetencia,
+    COALESCE(c.ifroconsultaconduta::text, '') AS conduta,
+    COALESCE(b.ifropacientequeixaprincipal::text, '') AS queixa_principal,
+    NULLIF(c.ifroclassificacaoconfianca, '')::float8 AS confianca,
+    EXISTS (
+        SELECT 1 FROM classification_corrections cc
+        WHERE cc.ifroid = c.ifroid::text AND cc.ifrounidadeid = b.ifrounidadeid::integer
+    ) AS corrigido";
+
+const CORRECTION_COLUMNS: &str = "id, ifroid, ifrounidadeid, ifrocompetencia, conduta, queixa_anterior,
+    queixa_corrigida, confianca, observacao, corrected_by, created_at";
+
+#[derive(Clone)]
+pub struct PgClassificationReviewRepository {
+    pool: PgPool,
+}
+
+impl PgClassificationReviewRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Cria a tabela das correções manuais. Executado uma vez na inicialização
+    pub async fn ensure_corrections_table(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS classification_corrections (
+                id UUID PRIMARY KEY,
+                ifroid TEXT NOT NULL,
+                ifrounidadeid INTEGER NOT NULL,
+                ifrocompetencia TEXT NOT NULL,
+                conduta TEXT NOT NULL,
+                queixa_anterior TEXT NOT NULL,
+                queixa_corrigida TEXT NOT NULL,
+                confianca DOUBLE PRECISION,
+                observacao TEXT,
+                corrected_by TEXT NOT NULL,
+                created_at TIMESTAMP NOT NULL DEFAULT NOW()
+            );"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn row_to_classified_record(row: &PgRow) -> Result<ClassifiedRecord, sqlx::Error> {
+        Ok(ClassifiedRecord {
+            ifroid: row.try_get("ifroid")?,
+            ifrounidadeid: row.try_get("ifrounidadeid")?,
+            ifrocompetencia: row.try_get("ifrocompetencia")?,
+            conduta: row.try_get("conduta")?,
+            queixa_principal: row.try_get("queixa_principal")?,
+            confianca: row.try_get("confianca")?,
+            corrigido: row.try_get("corrigido")?,
+        })
+    }
+
+    fn row_to_correction(row: &PgRow) -> Result<ClassificationCorrection, sqlx::Error> {
+        Ok(ClassificationCorrection {
+            id: row.try_get("id")?,
+            ifroid: row.try_get("ifroid")?,
+            ifrounidadeid: row.try_get("ifrounidadeid")?,
+            ifrocompetencia: row.try_get("ifrocompetencia")?,
+            conduta: row.try_get("conduta")?,
+            queixa_anterior: row.try_get("queixa_anterior")?,
+            queixa_corrigida: row.try_get("queixa_corrigida")?,
+            confianca: row.try_get("confianca")?,
+            observacao: row.try_get("observacao")?,
+            corrected_by: row.try_get("corrected_by")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl ClassificationReviewRepository for PgClassificationReviewRepository {
+    async fn fetch_classified_records(&self, page: i32, filter: &ClassificationReviewFilter) -> Result<(Vec<ClassifiedRecord>, i64), sqlx::Error> {
+        let limit = 15;
+        let offset = (page - 1) * limit;
+
+        // Os filtros ausentes são ignorados pela própria consulta
+        let filters = "WHERE ($1::integer IS NULL OR b.ifrounidadeid::integer = $1)
+            AND ($2::text IS NULL OR b.ifropacientequeixaprincipal = $2)
+            AND ($3::float8 IS NULL OR NULLIF(c.ifroclassificacaoconfianca, '')::float8 >= $3)
+            AND ($4::float8 IS NULL OR NULLIF(c.ifroclassificacaoconfianca, '')::float8 <= $4)
+            AND ($5::text[] IS NULL OR b.ifrocompetencia = ANY($5))";
+
+        let from = "FROM bpa_conduta c
+            JOIN bpa b ON b.ifroid::text = c.ifroid::text AND b.ifrounidadeid::text = c.ifrounidadeid::text";
+
+        // Registros com menor confiança aparecem primeiro
+        let query = format!(
+            "SELECT {} {} {}
+            ORDER BY NULLIF(c.ifroclassificacaoconfianca, '')::float8 ASC NULLS FIRST, c.ifroid
+            LIMIT $6 OFFSET $7",
+            CLASSIFIED_RECORD_COLUMNS, from, filters
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(filter.unidade_id)
+            .bind(&filter.doenca)
+            .bind(filter.confianca_min)
+            .bind(filter.confianca_max)
+            .bind(&filter.competencias)
+            .bind(limit as i64)
+            .bind(offset as i64)
+            .fetch_all(&self.pool)
+            .await?;
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) {} {}", from, filters))
+            .bind(filter.unidade_id)
+            .bind(&filter.doenca)
+            .bind(filter.confianca_min)
+            .bind(filter.confianca_max)
+            .bind(&filter.competencias)
+            .fetch_one(&self.pool)
+            .await?;
+
+        let records = rows.iter()
+            .map(Self::row_to_classified_record)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((records, total))
+    }
+
+    async fn find_classified_record(&self, unidade_id: i32, ifroid: &str) -> Result<Option<ClassifiedRecord>, sqlx::Error> {
+        let query = format!(
+            "SELECT {}
+            FROM bpa_conduta c
+            JOIN bpa b ON b.ifroid::text = c.ifroid::text AND b.ifrounidadeid::text = c.ifrounidadeid::text
+            WHERE b.ifrounidadeid = $1 AND c.ifroid::text = $2
+            LIMIT 1",
+            CLASSIFIED_RECORD_COLUMNS
+        );
+
+        let row = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(ifroid)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(Self::row_to_classified_record).transpose()
+    }
+
+    async fn create_correction(&self, record: &ClassifiedRecord, correction: &CreateClassificationCorrectionDto, corrected_by: &str) -> Result<ClassificationCorrection, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        // A correção substitui a classificação automática usada nos gráficos
+        sqlx::query(
+            "UPDATE bpa SET ifropacientequeixaprincipal = $1
+            WHERE ifroid::text = $2 AND ifrounidadeid = $3"
+        )
+        .bind(&correction.queixa_corrigida)
+        .bind(&record.ifroid)
+        .bind(record.ifrounidadeid)
+        .execute(&mut *tx)
+        .await?;
+
+        let query = format!(
+            "INSERT INTO classification_corrections
+                (id, ifroid, ifrounidadeid, ifrocompetencia, conduta, queixa_anterior, queixa_corrigida, confianca, observacao, corrected_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING {}",
+            CORRECTION_COLUMNS
+        );
+
+        let row = sqlx::query(&query)
+            .bind(Uuid::new_v4())
+            .bind(&record.ifroid)
+            .bind(record.ifrounidadeid)
+            .bind(&record.ifrocompetencia)
+            .bind(&record.conduta)
+            .bind(&record.queixa_principal)
+            .bind(&correction.queixa_corrigida)
+            .bind(record.confianca)
+            .bind(&correction.observacao)
+            .bind(corrected_by)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        let created = Self::row_to_correction(&row)?;
+        info!("Classification correction created with id: {}", created.id);
+
+        Ok(created)
+    }
+
+    async fn fetch_corrections(&self, unidade_id: Option<i32>) -> Result<Vec<ClassificationCorrection>, sqlx::Error> {
+        // Apenas a correção mais recente de cada registro é considerada como rótulo
+        let query = format!(
+            "SELECT {columns} FROM (
+                SELECT DISTINCT ON (ifroid, ifrounidadeid) {columns}
+                FROM classification_corrections
+                WHERE ($1::integer IS NULL OR ifrounidadeid = $1)
+                ORDER BY ifroid, ifrounidadeid, created_at DESC
+            ) latest
+            ORDER BY created_at",
+            columns = CORRECTION_COLUMNS
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(Self::row_to_correction).collect()
+    }
+}
@@ -1,13 +1,46 @@
+use crate::domain::models::data_upa::GroupedCount;
+use crate::domain::models::incidence::UNCLASSIFIED_DISEASE;
+use crate::domain::models::staffing::HourlyArrivalCount;
+use crate::domain::models::time_series::DailyCount;
 use crate::domain::repositories::data_upa::DataRepository;
 use crate::utils::process_data::convert_keys_to_str;
+use crate::utils::time_series::parse_attendance_date;
 use async_trait::async_trait;
+use chrono::NaiveDate;
+use log::debug;
 use polars::frame::DataFrame;
 use serde_json::{Value, json};
-use sqlx::{Column, PgPool, Row, ValueRef};
+use sqlx::{postgres::PgRow, Column, PgPool, Row, ValueRef};
 use uuid::Uuid;
 use std::collections::HashMap;
 use std::error::Error;
 
+// Filtro comum das contagens: unidade e, opcionalmente, competências
+const GROUPED_COUNT_FILTER: &str = "ifrounidadeid = $1
+    AND ($2::text[] IS NULL OR ifrocompetencia = ANY($2))
+    AND COALESCE(ifrocompetencia::text, '') <> ''";
+
+// Idades aceitas nos gráficos por faixa etária ("34" ou "34.0"), truncadas em anos completos
+const AGE_PATTERN: &str = r"^[0-9]+(\.[0-9]+)?$";
+const AGE_EXPRESSION: &str = "FLOOR(ifropacienteidade::text::numeric)::integer::text";
+
+// Coordenadas aceitas nos mapas de calor (valores que podem ser convertidos para número)
+const COORDINATE_PATTERN: &str = r"^[-+]?([0-9]+\.?[0-9]*|\.[0-9]+)([eE][-+]?[0-9]+)?$";
+
+// Endereços e bairros genéricos excluídos pelas regras da unidade (parâmetros $n e $n+1)
+fn location_rules_filter(first_param: usize) -> String {
+    format!(
+        "AND NOT (COALESCE(ifropacienteendereco::text, '') ILIKE ANY(${}::text[]))
+        AND UPPER(TRIM(COALESCE(ifropacientebairro::text, ''))) <> ALL(${}::text[])",
+        first_param,
+        first_param + 1
+    )
+}
+
+// Colunas da tabela bpa lidas pelos gráficos que podem faltar em dados importados antes delas
+// ou em arquivos sem a coluna de origem
+const OPTIONAL_BPA_COLUMNS: [&str; 6] = ["ifroid", "ifropacientesexocd", "ifroprocedimentosuscd", "ifroprocedimentonome", "ifromunicipioibge", "ifrovisitachave"];
+
 pub struct PgDataRepository {
     pool: PgPool,
 }
@@ -16,6 +49,303 @@ impl PgDataRepository {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
+
+    /// Acrescenta à tabela bpa, quando ela já existe, as colunas opcionais ausentes. Executado uma vez na
+    /// inicialização e a cada importação, para que as consultas dos gráficos não alterem a tabela
+    pub async fn ensure_optional_bpa_columns(&self) -> Result<(), sqlx::Error> {
+        let existing_columns: Vec<String> = sqlx::query_scalar(
+            "SELECT column_name::text FROM information_schema.columns WHERE table_name = 'bpa'"
+        )
+            .fetch_all(&self.pool)
+            .await?;
+
+        // A tabela é criada na primeira importação
+        if existing_columns.is_empty() {
+            return Ok(());
+        }
+
+        for column in OPTIONAL_BPA_COLUMNS.iter().filter(|column| !existing_columns.iter().any(|c| c == *column)) {
+            sqlx::query(&format!("ALTER TABLE bpa ADD COLUMN IF NOT EXISTS {} VARCHAR", column))
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    /// Cria a tabela com o texto da conduta e a confiança da classificação de cada atendimento, usada nas
+    /// revisões e reclassificações. O texto é clínico, por isso o acesso de outros usuários do banco é revogado
+    pub async fn ensure_conduta_table(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS bpa_conduta (
+                ifroid VARCHAR,
+                ifrocompetencia VARCHAR,
+                ifrounidadeid VARCHAR,
+                ifroconsultaconduta VARCHAR,
+                ifroclassificacaoconfianca VARCHAR
+            );"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Tabelas criadas por importações anteriores à coluna de confiança
+        sqlx::query("ALTER TABLE bpa_conduta ADD COLUMN IF NOT EXISTS ifroclassificacaoconfianca VARCHAR")
+            .execute(&self.pool)
+            .await?;
+
+        sqlx::query("REVOKE ALL ON bpa_conduta FROM PUBLIC")
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    // Insere as linhas do DataFrame em lotes em uma tabela existente
+    async fn insert_rows(&self, df: &DataFrame, table: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let batch_size = 5000;
+        let total_rows = df.height();
+        
+        for batch_start in (0..total_rows).step_by(batch_size) {
+            let batch_end = std::cmp::min(batch_start + batch_size, total_rows);
+            let df_batch = df.slice(batch_start as i64, batch_end - batch_start);
+            
+            // Filtrar para remover nomes de colunas vazios
+            let filtered_columns: Vec<String> = df.get_column_names()
+                .into_iter()
+                .filter(|name| !name.is_empty())
+                .map(|name| name.to_string())
+                .collect();
+            
+            // Construir a consulta de inserção para este lote
+            let column_names = filtered_columns.join(", ");
+            
+            // Se não há colunas ou linhas, pula este lote
+            if filtered_columns.is_empty() || df_batch.height() == 0 {
+                println!("Lote vazio ou sem colunas válidas, pulando.");
+                continue;
+            }
+            
+            let mut value_strings = Vec::new();
+            
+            // Adicionar valores para cada linha
+            for row_idx in 0..df_batch.height() {
+                let mut row_values = Vec::new();
+                
+                for col_name in &filtered_columns { // Iterate over references to owned Strings
+                    let value = df_batch.column(col_name)?.get(row_idx)?;
+                    
+                    // Formatar o valor de acordo com seu tipo
+                    let formatted_value = match value {
+                        polars::prelude::AnyValue::Null => "NULL".to_string(),
+                        polars::prelude::AnyValue::String(s) => format!("'{}'", s.replace("'", "''")),
+                        _ => format!("{}", value),
+                    };
+                    
+                    row_values.push(formatted_value);
+                }
+                
+                // Junta os valores da linha com vírgulas e envolve em parênteses
+                value_strings.push(format!("({})", row_values.join(", ")));
+            }
+            
+            // Construir a consulta completa
+            let sql_command = format!(
+                "INSERT INTO {} ({}) VALUES {}",
+                table,
+                column_names,
+                value_strings.join(", ")
+            );
+            
+            // Salva parte da query para ver o erro, caso tenha
+            let truncated_query = if sql_command.len() > 5000 {
+                format!("{}...", &sql_command[..5000])
+            } else {
+                sql_command.clone()
+            };
+            println!("SQL Query (truncada): {}", truncated_query);
+            
+            // Executa a consulta de inserção para este lote
+            match sqlx::query(&sql_command).execute(&self.pool).await {
+                Ok(_) => {
+                    println!("Inseridos dados de {} na tabela {}", 
+                        total_rows, table);
+                },
+                Err(e) => {
+                    eprintln!("Erro durante a inserção: {}", e);
+                    return Err(e.into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Cria as fatias dos gráficos por unidade e competência, usadas na atualização incremental. Chamado uma vez
+    /// na inicialização
+    pub async fn ensure_graph_data_slices_table(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS graph_data_slices (
+                identifier TEXT NOT NULL,
+                ifrounidadeid INTEGER NOT NULL,
+                ifrocompetencia TEXT NOT NULL,
+                data JSONB NOT NULL,
+                updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (identifier, ifrounidadeid, ifrocompetencia)
+            );"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Cria a fila das competências importadas ou alteradas que ainda não foram refletidas nos gráficos. Chamado
+    /// uma vez na inicialização
+    pub async fn ensure_graph_data_pending_table(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS graph_data_pending (
+                ifrounidadeid INTEGER NOT NULL,
+                ifrocompetencia TEXT NOT NULL,
+                created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (ifrounidadeid, ifrocompetencia)
+            );"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    // Converte as linhas retornadas em um mapa coluna -> valores
+    fn rows_to_column_map(rows: &[PgRow], columns: &[String]) -> Result<HashMap<String, Vec<Value>>, Box<dyn Error + Send + Sync>> {
+        // Inicializar o resultado
+        let mut result: HashMap<String, Vec<Value>> = HashMap::new();
+        for col_name in columns {
+            result.insert(col_name.to_string(), Vec::new());
+        }
+
+        // Para cada linha de resultado
+        for row in rows {
+            // Para cada coluna na linha
+            for (i, column_name) in columns.iter().enumerate() {
+                // Tenta obter o valor baseado no tipo da coluna
+                let column = row.columns().get(i).unwrap();
+                let type_name = column.type_info().to_string();
+            
+                let value: Value = match type_name.as_str() {
+                    "INT4" | "INT8" => {
+                        // Vrifica se o valor é NULL antes de tentar obter
+                        if row.try_get_raw(i)?.is_null() {
+                            // Se for NULL, retorna 0 ou outro valor padrão em vez de NULL
+                            json!(0) 
+                        } else if let Ok(v) = row.try_get::<i64, _>(i) {
+                            // Se não for NULL e conseguir obter como i64
+                            json!(v)
+                        } else if let Ok(v) = row.try_get::<i32, _>(i) {
+                            // Tenta i32 também como fallback
+                            json!(v as i64)
+                        } else {
+                            // Fallback final
+                            debug!("Erro ao obter valor INT para {}", column_name);
+                            Value::Null
+                        }
+                    },
+                    "FLOAT4" | "FLOAT8" => {
+                        if row.try_get_raw(i)?.is_null() {
+                            json!(0.0)  // Ou Value::Null
+                        } else if let Ok(v) = row.try_get::<f64, _>(i) {
+                            json!(v)
+                        } else {
+                            debug!("Erro ao obter valor FLOAT para {}", column_name);
+                            Value::Null
+                        }
+                    },
+                    "VARCHAR" | "TEXT" => {
+                        if row.try_get_raw(i)?.is_null() {
+                            json!("")  // String vazia para NULL
+                        } else if let Ok(v) = row.try_get::<String, _>(i) {
+                            json!(v)
+                        } else {
+                            debug!("Erro ao obter valor VARCHAR/TEXT para {}", column_name);
+                            Value::Null
+                        }
+                    },
+                    "BOOL" => {
+                        if row.try_get_raw(i)?.is_null() {
+                            json!(false)  // Ou Value::Null
+                        } else if let Ok(v) = row.try_get::<bool, _>(i) {
+                            json!(v)
+                        } else {
+                            debug!("Erro ao obter valor BOOL para {}", column_name);
+                            Value::Null
+                        }
+                    },
+                    "TIMESTAMP" | "TIMESTAMPTZ" => {
+                        if row.try_get_raw(i)?.is_null() {
+                            Value::Null
+                        } else if let Ok(v) = row.try_get::<chrono::DateTime<chrono::Utc>, _>(i) {
+                            json!(v.to_string())
+                        } else {
+                            debug!("Erro ao obter valor TIMESTAMP para {}", column_name);
+                            Value::Null
+                        }
+                    },
+                    "DATE" => {
+                        if row.try_get_raw(i)?.is_null() {
+                            Value::Null
+                        } else if let Ok(v) = row.try_get::<chrono::NaiveDate, _>(i) {
+                            json!(v.to_string())
+                        } else {
+                            debug!("Erro ao obter valor DATE para {}", column_name);
+                            Value::Null
+                        }
+                    },
+                    _ => {
+                        // Para outros tipos, tenta obter como string
+                        if row.try_get_raw(i)?.is_null() {
+                            Value::Null
+                        } else if let Ok(v) = row.try_get::<String, _>(i) {
+                            json!(v)
+                        } else {
+                            debug!("Erro ao obter valor de tipo desconhecido para {}", column_name);
+                            Value::Null
+                        }
+                    }
+                };
+
+                // Adiciona o valor ao vetor da coluna
+                if let Some(column_values) = result.get_mut(column_name) {
+                    column_values.push(value);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    // Converte as linhas das consultas GROUP BY (competencia, key_0..key_n, latitude, longitude, quantidade)
+    fn rows_to_grouped_counts(rows: &[PgRow], key_count: usize, with_coordinates: bool) -> Result<Vec<GroupedCount>, sqlx::Error> {
+        rows.iter()
+            .map(|row| {
+                let keys = (0..key_count)
+                    .map(|i| row.try_get::<String, _>(format!("key_{}", i).as_str()))
+                    .collect::<Result<Vec<String>, _>>()?;
+
+                let coordinates = if with_coordinates {
+                    Some((row.try_get::<f64, _>("latitude")?, row.try_get::<f64, _>("longitude")?))
+                } else {
+                    None
+                };
+
+                Ok(GroupedCount {
+                    competencia: row.try_get("competencia")?,
+                    keys,
+                    coordinates,
+                    quantidade: row.try_get("quantidade")?,
+                })
+            })
+            .collect()
+    }
 }
 
 #[async_trait]
@@ -122,11 +452,9 @@ impl DataRepository for PgDataRepository {
             .join(", ");
 
         // Verifica se a tabela existe
-        let table_exists_query = format!(
-            "SELECT EXISTS (SELECT FROM information_schema.tables WHERE table_name = $1)"
-        );
+        let table_exists_query = "SELECT EXISTS (SELECT FROM information_schema.tables WHERE table_name = $1)";
 
-        let table_exists: bool = sqlx::query_scalar(&table_exists_query)
+        let table_exists: bool = sqlx::query_scalar(table_exists_query)
             .bind(table)
             .fetch_one(&self.pool)
             .await?;
@@ -212,7 +540,11 @@ impl DataRepository for PgDataRepository {
         } else {
             println!("Tabela {} já existe. Anexando dados.", table);
         }
-        
+
+        if table == "bpa" {
+            self.ensure_optional_bpa_columns().await?;
+        }
+
         Ok(true)
     }
     
@@ -221,80 +553,7 @@ impl DataRepository for PgDataRepository {
         match self.create_table_if_not_exists(df, table).await {
             Ok(true) => {
                 // Se a tabela foi criada ou já existe, insere os dados
-                let batch_size = 5000;
-                let total_rows = df.height();
-                
-                for batch_start in (0..total_rows).step_by(batch_size) {
-                    let batch_end = std::cmp::min(batch_start + batch_size, total_rows);
-                    let df_batch = df.slice(batch_start as i64, batch_end - batch_start);
-                    
-                    // Filtrar para remover nomes de colunas vazios
-                    let filtered_columns: Vec<String> = df.get_column_names()
-                        .into_iter()
-                        .filter(|name| !name.is_empty())
-                        .map(|name| name.to_string())
-                        .collect();
-                    
-                    // Construir a consulta de inserção para este lote
-                    let column_names = filtered_columns.join(", ");
-                    
-                    // Se não há colunas ou linhas, pula este lote
-                    if filtered_columns.is_empty() || df_batch.height() == 0 {
-                        println!("Lote vazio ou sem colunas válidas, pulando.");
-                        continue;
-                    }
-                    
-                    let mut value_strings = Vec::new();
-                    
-                    // Adicionar valores para cada linha
-                    for row_idx in 0..df_batch.height() {
-                        let mut row_values = Vec::new();
-                        
-                        for col_name in &filtered_columns { // Iterate over references to owned Strings
-                            let value = df_batch.column(col_name)?.get(row_idx)?;
-                            
-                            // Formatar o valor de acordo com seu tipo
-                            let formatted_value = match value {
-                                polars::prelude::AnyValue::Null => "NULL".to_string(),
-                                polars::prelude::AnyValue::String(s) => format!("'{}'", s.replace("'", "''")),
-                                _ => format!("{}", value),
-                            };
-                            
-                            row_values.push(formatted_value);
-                        }
-                        
-                        // Junta os valores da linha com vírgulas e envolve em parênteses
-                        value_strings.push(format!("({})", row_values.join(", ")));
-                    }
-                    
-                    // Construir a consulta completa
-                    let sql_command = format!(
-                        "INSERT INTO {} ({}) VALUES {}",
-                        table,
-                        column_names,
-                        value_strings.join(", ")
-                    );
-                    
-                    // Salva parte da query para ver o erro, caso tenha
-                    let truncated_query = if sql_command.len() > 5000 {
-                        format!("{}...", &sql_command[..5000])
-                    } else {
-                        sql_command.clone()
-                    };
-                    println!("SQL Query (truncada): {}", truncated_query);
-                    
-                    // Executa a consulta de inserção para este lote
-                    match sqlx::query(&sql_command).execute(&self.pool).await {
-                        Ok(_) => {
-                            println!("Inseridos dados de {} na tabela {}", 
-                                total_rows, table);
-                        },
-                        Err(e) => {
-                            eprintln!("Erro durante a inserção: {}", e);
-                            return Err(e.into());
-                        }
-                    }
-                }
+                self.insert_rows(df, table).await?;
                 
                 println!("Dados inseridos com sucesso na tabela {}.", table);
                 Ok(true)
@@ -310,6 +569,11 @@ impl DataRepository for PgDataRepository {
         }
     }
 
+    async fn insert_conduta_records(&self, df: &DataFrame) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // A tabela é criada na inicialização, com acesso restrito
+        self.insert_rows(df, "bpa_conduta").await
+    }
+
     async fn fetch_columns_by_name(&self, table: &str, columns: &[String]) -> Result<HashMap<String, Vec<Value>>, Box<dyn Error + Send + Sync>> {
         // Constrói a query para buscar colunas específicas
         let query = format!("SELECT {} FROM {}", columns.join(", "), table);
@@ -453,34 +717,19 @@ impl DataRepository for PgDataRepository {
         Ok(result)
     }
 
-    async fn fetch_nested_json(&self, table: &str, identifier: &str, unidade_id: Option<i32>) -> Result<serde_json::Map<String, serde_json::Value>, Box<dyn Error + Send + Sync>> {
+    async fn fetch_nested_json(&self, table: &str, identifier: &str, unidade_id: i32) -> Result<serde_json::Map<String, serde_json::Value>, Box<dyn Error + Send + Sync>> {
         // Consulta SQL para buscar dados JSON
-        let query = if let Some(_unit_id) = unidade_id {
-            format!(
-                "SELECT data FROM {} WHERE identifier = $1 AND ifrounidadeid = $2", 
-                table
-            )
-        } else {
-            // Retrocompatibilidade - busca unidade padrão (UPA Ariquemes)
-            format!(
-                "SELECT data FROM {} WHERE identifier = $1 AND ifrounidadeid = 2", 
-                table
-            )
-        };
+        let query = format!(
+            "SELECT data FROM {} WHERE identifier = $1 AND ifrounidadeid = $2", 
+            table
+        );
         
         // Executa a consulta
-        let row = if let Some(unit_id) = unidade_id {
-            sqlx::query(&query)
-                .bind(identifier)
-                .bind(unit_id)
-                .fetch_optional(&self.pool)
-                .await?
-        } else {
-            sqlx::query(&query)
-                .bind(identifier)
-                .fetch_optional(&self.pool)
-                .await?
-        };
+        let row = sqlx::query(&query)
+            .bind(identifier)
+            .bind(unidade_id)
+            .fetch_optional(&self.pool)
+            .await?;
         
         // Processa resultado
         if let Some(row) = row {
@@ -501,49 +750,6 @@ impl DataRepository for PgDataRepository {
     }
 
 
-    async fn fetch_distinct_values(&self, table: &str, column: &str) 
-        -> Result<Vec<i32>, Box<dyn Error + Send + Sync>> {
-        println!("Buscando valores distintos para {} em {}", column, table);
-        // Consulta SQL para buscar valores distintos
-        let query = format!("SELECT DISTINCT {} FROM {}", column, table);
-        
-        // Executa a consulta
-        let rows = sqlx::query(&query)
-            .fetch_all(&self.pool)
-            .await?;
-
-        if rows.is_empty() {
-            println!("Nenhum valor distinto encontrado para {} em {}", column, table);
-            return Ok(Vec::new());
-        }
-        
-        // Extrair os valores
-        let mut values = Vec::new();
-        for row in rows {
-        // Tenta primeiro como i32, depois como string
-        if let Ok(value) = row.try_get::<i32, _>(0) {
-            values.push(value);
-        } else if let Ok(value_str) = row.try_get::<String, _>(0) {
-            // Converte string para i32
-            match value_str.parse::<i32>() {
-                Ok(value) => values.push(value),
-                Err(_) => {
-                    println!("Erro ao converter '{}' para i32", value_str);
-                    continue;
-                }
-            }
-        } else {
-            println!("Erro ao extrair valor de {}", column);
-            continue;
-        }
-    }
-        
-        println!("Valores distintos para {} em {}: {:?}", column, table, values);
-        Ok(values)
-    }
-
-
-
     async fn fetch_columns_by_name_with_filter(
         &self, 
         table: &str, 
@@ -577,143 +783,527 @@ impl DataRepository for PgDataRepository {
             return Ok(HashMap::new());
         }
     
-        // Inicializar o resultado
-        let mut result: HashMap<String, Vec<Value>> = HashMap::new();
-        for col_name in columns {
-            result.insert(col_name.to_string(), Vec::new());
-        }
+        let result = Self::rows_to_column_map(&rows, columns)?;
     
-        // Para cada linha de resultado
-        for (row_idx, row) in rows.iter().enumerate() {
-            // Para debug, imprime algumas primeiras linhas
-            if row_idx < 5 {
-                println!("Processando linha {}", row_idx);
-            }
-    
-            // Para cada coluna na linha
-            for (i, column_name) in columns.iter().enumerate() {
-                // Tenta obter o valor baseado no tipo da coluna
-                let column = row.columns().get(i).unwrap();
-                let type_name = column.type_info().to_string();
-                
-                // Para debug das primeiras linhas
-                if row_idx < 5 {
-                    println!("Coluna {} ({}): tipo={}", i, column_name, type_name);
-                }
-                
-                let value: Value = match type_name.as_str() {
-                    "INT4" | "INT8" => {
-                        // Vrifica se o valor é NULL antes de tentar obter
-                        if row.try_get_raw(i)?.is_null() {
-                            // Se for NULL, retorna 0 ou outro valor padrão em vez de NULL
-                            json!(0) 
-                        } else if let Ok(v) = row.try_get::<i64, _>(i) {
-                            // Se não for NULL e conseguir obter como i64
-                            json!(v)
-                        } else if let Ok(v) = row.try_get::<i32, _>(i) {
-                            // Tenta i32 também como fallback
-                            json!(v as i64)
-                        } else {
-                            // Fallback final
-                            println!("Erro ao obter valor INT para {}", column_name);
-                            Value::Null
-                        }
-                    },
-                    "FLOAT4" | "FLOAT8" => {
-                        if row.try_get_raw(i)?.is_null() {
-                            json!(0.0)  // Ou Value::Null
-                        } else if let Ok(v) = row.try_get::<f64, _>(i) {
-                            json!(v)
-                        } else {
-                            println!("Erro ao obter valor FLOAT para {}", column_name);
-                            Value::Null
-                        }
-                    },
-                    "VARCHAR" | "TEXT" => {
-                        if row.try_get_raw(i)?.is_null() {
-                            json!("")  // String vazia para NULL
-                        } else if let Ok(v) = row.try_get::<String, _>(i) {
-                            json!(v)
-                        } else {
-                            println!("Erro ao obter valor VARCHAR/TEXT para {}", column_name);
-                            Value::Null
-                        }
-                    },
-                    "BOOL" => {
-                        if row.try_get_raw(i)?.is_null() {
-                            json!(false)  // Ou Value::Null
-                        } else if let Ok(v) = row.try_get::<bool, _>(i) {
-                            json!(v)
-                        } else {
-                            println!("Erro ao obter valor BOOL para {}", column_name);
-                            Value::Null
-                        }
-                    },
-                    "TIMESTAMP" | "TIMESTAMPTZ" => {
-                        if row.try_get_raw(i)?.is_null() {
-                            Value::Null
-                        } else if let Ok(v) = row.try_get::<chrono::DateTime<chrono::Utc>, _>(i) {
-                            json!(v.to_string())
-                        } else {
-                            println!("Erro ao obter valor TIMESTAMP para {}", column_name);
-                            Value::Null
-                        }
-                    },
-                    "DATE" => {
-                        if row.try_get_raw(i)?.is_null() {
-                            Value::Null
-                        } else if let Ok(v) = row.try_get::<chrono::NaiveDate, _>(i) {
-                            json!(v.to_string())
-                        } else {
-                            println!("Erro ao obter valor DATE para {}", column_name);
-                            Value::Null
-                        }
-                    },
-                    _ => {
-                        // Para outros tipos, tenta obter como string
-                        if row.try_get_raw(i)?.is_null() {
-                            Value::Null
-                        } else if let Ok(v) = row.try_get::<String, _>(i) {
-                            json!(v)
-                        } else {
-                            println!("Erro ao obter valor de tipo desconhecido para {}", column_name);
-                            Value::Null
-                        }
-                    }
-                };
-    
-                // Para debug das primeiras linhas
-                if row_idx < 5 {
-                    println!("Valor obtido: {:?}", value);
-                }
-    
-                // Adiciona o valor ao vetor da coluna
-                if let Some(column_values) = result.get_mut(column_name) {
-                    column_values.push(value);
-                }
-            }
-        }
-    
-        println!(
-            "Buscou colunas {:?} da tabela {} com filtro {}={}", 
-            columns, table, filter_column, filter_value
-        );
-        
-        // Imprimir amostras dos dados obtidos para debug
-        for col_name in columns {
-            if let Some(values) = result.get(col_name) {
-                let sample = if values.len() > 5 {
-                    &values[0..5]
-                } else {
-                    values
-                };
-                println!("Amostra de valores para {}: {:?}", col_name, sample);
+        println!(
+            "Buscou colunas {:?} da tabela {} com filtro {}={}", 
+            columns, table, filter_column, filter_value
+        );
+        
+        // Imprimir amostras dos dados obtidos para debug
+        for col_name in columns {
+            if let Some(values) = result.get(col_name) {
+                let sample = if values.len() > 5 {
+                    &values[0..5]
+                } else {
+                    values
+                };
+                println!("Amostra de valores para {}: {:?}", col_name, sample);
             }
         }
         
         Ok(result)
     }
 
+    async fn fetch_columns_by_name_with_filter_and_competencias(
+        &self, 
+        table: &str, 
+        columns: &[String], 
+        filter_column: &str, 
+        filter_value: i32,
+        competencias: &[String]
+    ) -> Result<HashMap<String, Vec<Value>>, Box<dyn Error + Send + Sync>> {
+        // Constrói a query com filtro de unidade e competências
+        let query = format!(
+            "SELECT {} FROM {} WHERE {} = $1 AND ifrocompetencia = ANY($2)", 
+            columns.join(", "), 
+            table, 
+            filter_column
+        );
+
+        debug!("Executando query: {} (competências: {:?})", query, competencias);
+
+        let rows = sqlx::query(&query)
+            .bind(filter_value)
+            .bind(competencias)
+            .fetch_all(&self.pool)
+            .await?;
+
+        if rows.is_empty() {
+            debug!(
+                "Nenhum dado encontrado em {} com filtro {}={} para as competências {:?}", 
+                table, filter_column, filter_value, competencias
+            );
+            return Ok(HashMap::new());
+        }
+
+        Self::rows_to_column_map(&rows, columns)
+    }
+
+    async fn insert_graph_data_slice(&self, data: Value, identifier: &str, unidade_id: i32, competencia: &str) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let processed_data = convert_keys_to_str(data);
+
+        sqlx::query(
+            "INSERT INTO graph_data_slices (identifier, ifrounidadeid, ifrocompetencia, data, updated_at)
+            VALUES ($1, $2, $3, $4::jsonb, NOW())
+            ON CONFLICT (identifier, ifrounidadeid, ifrocompetencia)
+            DO UPDATE SET data = EXCLUDED.data, updated_at = NOW()"
+        )
+        .bind(identifier)
+        .bind(unidade_id)
+        .bind(competencia)
+        .bind(processed_data.to_string())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_graph_data_slices(&self, identifier: &str, unidade_id: i32) -> Result<Vec<(String, Value)>, Box<dyn Error + Send + Sync>> {
+        let rows = sqlx::query(
+            "SELECT ifrocompetencia, data FROM graph_data_slices
+            WHERE identifier = $1 AND ifrounidadeid = $2
+            ORDER BY ifrocompetencia"
+        )
+        .bind(identifier)
+        .bind(unidade_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut slices = Vec::with_capacity(rows.len());
+        for row in rows {
+            let competencia: String = row.try_get("ifrocompetencia")?;
+            let data: Value = row.try_get("data")?;
+            slices.push((competencia, data));
+        }
+
+        Ok(slices)
+    }
+
+    async fn check_graph_data_slices_exist(&self, identifier: &str, unidade_id: i32) -> Result<bool, Box<dyn Error + Send + Sync>> {
+        let exists: bool = sqlx::query_scalar(
+            "SELECT EXISTS (
+                SELECT 1 FROM graph_data_slices
+                WHERE identifier = $1 AND ifrounidadeid = $2
+            )"
+        )
+        .bind(identifier)
+        .bind(unidade_id)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(exists)
+    }
+
+    async fn delete_graph_data_slices(&self, identifier: &str, unidade_id: i32, competencias: Option<&[String]>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        // Sem competências informadas, remove todas as fatias do gráfico na unidade
+        sqlx::query(
+            "DELETE FROM graph_data_slices
+            WHERE identifier = $1 AND ifrounidadeid = $2
+            AND ($3::text[] IS NULL OR ifrocompetencia = ANY($3))"
+        )
+        .bind(identifier)
+        .bind(unidade_id)
+        .bind(competencias)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn mark_competencias_pending(&self, unidade_id: i32, competencias: &[String]) -> Result<(), Box<dyn Error + Send + Sync>> {
+        sqlx::query(
+            "INSERT INTO graph_data_pending (ifrounidadeid, ifrocompetencia)
+            SELECT $1, UNNEST($2::text[])
+            ON CONFLICT (ifrounidadeid, ifrocompetencia) DO NOTHING"
+        )
+        .bind(unidade_id)
+        .bind(competencias)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_pending_competencias(&self) -> Result<HashMap<i32, Vec<String>>, Box<dyn Error + Send + Sync>> {
+        let rows = sqlx::query(
+            "SELECT ifrounidadeid, ifrocompetencia FROM graph_data_pending
+            ORDER BY ifrounidadeid, ifrocompetencia"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut pending: HashMap<i32, Vec<String>> = HashMap::new();
+        for row in rows {
+            let unidade_id: i32 = row.try_get("ifrounidadeid")?;
+            let competencia: String = row.try_get("ifrocompetencia")?;
+            pending.entry(unidade_id).or_default().push(competencia);
+        }
+
+        Ok(pending)
+    }
+
+    async fn clear_pending_competencias(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<(), Box<dyn Error + Send + Sync>> {
+        sqlx::query(
+            "DELETE FROM graph_data_pending
+            WHERE ifrounidadeid = $1
+            AND ($2::text[] IS NULL OR ifrocompetencia = ANY($2))"
+        )
+        .bind(unidade_id)
+        .bind(competencias)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn fetch_competencias_by_unit(&self, unidade_id: i32) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        let competencias: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT ifrocompetencia::text FROM bpa
+            WHERE ifrounidadeid = $1 AND COALESCE(ifrocompetencia::text, '') <> ''"
+        )
+        .bind(unidade_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(competencias)
+    }
+
+    async fn count_by_competencia(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        let query = format!(
+            "SELECT ifrocompetencia::text AS competencia, COUNT(*) AS quantidade
+            FROM bpa WHERE {}
+            GROUP BY 1",
+            GROUPED_COUNT_FILTER
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 0, false)?)
+    }
+
+    async fn count_by_competencia_and_column(&self, column: &str, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        // Valores nulos são agrupados como texto vazio, como na leitura das colunas
+        let query = format!(
+            "SELECT ifrocompetencia::text AS competencia, COALESCE({}::text, '') AS key_0, COUNT(*) AS quantidade
+            FROM bpa WHERE {}
+            GROUP BY 1, 2",
+            column, GROUPED_COUNT_FILTER
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 1, false)?)
+    }
+
+    async fn count_by_competencia_and_hour(&self, cbo: &str, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        // A hora corresponde aos dois primeiros dígitos do horário de atendimento
+        let query = format!(
+            "SELECT ifrocompetencia::text AS competencia, SUBSTRING(ifrohoraatendimento::text FROM 1 FOR 2) AS key_0, COUNT(*) AS quantidade
+            FROM bpa WHERE {}
+            AND ifroprofissionalcbods = $3
+            AND ifrohoraatendimento::text ~ '^[0-9]{{2}}'
+            GROUP BY 1, 2",
+            GROUPED_COUNT_FILTER
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .bind(cbo)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 1, false)?)
+    }
+
+    async fn count_by_competencia_and_professional(
+        &self,
+        cbo: &str,
+        tabela: &str,
+        excluded_names: &[String],
+        unidade_id: i32,
+        competencias: Option<&[String]>
+    ) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        let query = format!(
+            "SELECT ifrocompetencia::text AS competencia, COALESCE(ifroprofissionalnome::text, '') AS key_0, COUNT(*) AS quantidade
+            FROM bpa WHERE {}
+            AND ifroprofissionalcbods = $3
+            AND ifrotabelanome = $4
+            AND NOT (COALESCE(ifroprofissionalnome::text, '') = ANY($5))
+            GROUP BY 1, 2",
+            GROUPED_COUNT_FILTER
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .bind(cbo)
+            .bind(tabela)
+            .bind(excluded_names)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 1, false)?)
+    }
+
+    async fn count_by_competencia_and_location(&self, group_column: Option<&str>, excluded_addresses: &[String], placeholder_neighborhoods: &[String], unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        // Agrupa por bairro e, quando informada, por uma coluna adicional (ex.: queixa principal)
+        let (keys, group_by, key_count) = match group_column {
+            Some(column) => (
+                format!("COALESCE({}::text, '') AS key_0, COALESCE(ifropacientebairro::text, '') AS key_1", column),
+                "competencia, key_0, key_1",
+                2,
+            ),
+            None => (
+                "COALESCE(ifropacientebairro::text, '') AS key_0".to_string(),
+                "competencia, key_0",
+                1,
+            ),
+        };
+
+        // As coordenadas do bairro são as da primeira linha válida pelo identificador do atendimento, mantendo o
+        // par latitude/longitude. Linhas sem identificador vêm por último, desempatadas pelas próprias coordenadas,
+        // para que o resultado não dependa da posição física das linhas (ctid muda após VACUUM FULL ou UPDATE).
+        // A conversão só ocorre depois da validação, para não falhar com valores inválidos
+        let query = format!(
+            "SELECT {group_by},
+                (ARRAY_AGG(latitude ORDER BY row_order NULLS LAST, latitude, longitude))[1] AS latitude,
+                (ARRAY_AGG(longitude ORDER BY row_order NULLS LAST, latitude, longitude))[1] AS longitude,
+                COUNT(*) AS quantidade
+            FROM (
+                SELECT ifroid::text AS row_order, ifrocompetencia::text AS competencia, {keys},
+                    CASE WHEN ifropacientelatitude::text ~ $3 THEN ifropacientelatitude::text::float8 END AS latitude,
+                    CASE WHEN ifropacientelongitude::text ~ $3 THEN ifropacientelongitude::text::float8 END AS longitude
+                FROM bpa WHERE {filter}
+                {rules}
+            ) locations
+            WHERE latitude IS NOT NULL AND longitude IS NOT NULL
+            GROUP BY {group_by}",
+            group_by = group_by,
+            keys = keys,
+            filter = GROUPED_COUNT_FILTER,
+            rules = location_rules_filter(4)
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .bind(COORDINATE_PATTERN)
+            .bind(excluded_addresses)
+            .bind(placeholder_neighborhoods)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, key_count, true)?)
+    }
+
+    async fn count_by_competencia_weekday_and_hour(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        // Agrupa por dia da semana, hora do atendimento e categoria profissional
+        let query = format!(
+            "SELECT ifrocompetencia::text AS competencia,
+                COALESCE(ifrodiasemana::text, '') AS key_0,
+                SUBSTRING(ifrohoraatendimento::text FROM 1 FOR 2) AS key_1,
+                COALESCE(ifroprofissionalcbods::text, '') AS key_2,
+                COUNT(*) AS quantidade
+            FROM bpa WHERE {}
+            AND ifrohoraatendimento::text ~ '^[0-9]{{2}}'
+            GROUP BY 1, 2, 3, 4",
+            GROUPED_COUNT_FILTER
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 3, false)?)
+    }
+
+    async fn count_by_competencia_sex_and_age(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        let query = format!(
+            "SELECT ifrocompetencia::text AS competencia,
+                COALESCE(ifropacientesexocd::text, '') AS key_0,
+                {} AS key_1,
+                COUNT(*) AS quantidade
+            FROM bpa WHERE {}
+            AND ifropacienteidade::text ~ '{}'
+            GROUP BY 1, 2, 3",
+            AGE_EXPRESSION, GROUPED_COUNT_FILTER, AGE_PATTERN
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 2, false)?)
+    }
+
+    async fn count_by_competencia_complaint_and_age(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        let query = format!(
+            "SELECT ifrocompetencia::text AS competencia,
+                ifropacientequeixaprincipal::text AS key_0,
+                {} AS key_1,
+                COUNT(*) AS quantidade
+            FROM bpa WHERE {}
+            AND ifropacienteidade::text ~ '{}'
+            AND COALESCE(ifropacientequeixaprincipal::text, '') <> ''
+            GROUP BY 1, 2, 3",
+            AGE_EXPRESSION, GROUPED_COUNT_FILTER, AGE_PATTERN
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 2, false)?)
+    }
+
+    async fn count_by_competencia_complaint_and_point(&self, excluded_addresses: &[String], placeholder_neighborhoods: &[String], unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        // Casos por queixa e local do paciente, com as coordenadas arredondadas em 4 casas (cerca de 11 m).
+        // A conversão só ocorre depois da validação, como no mapa de calor
+        let query = format!(
+            "SELECT competencia, key_0, latitude, longitude, COUNT(*) AS quantidade
+            FROM (
+                SELECT ifrocompetencia::text AS competencia,
+                    ifropacientequeixaprincipal::text AS key_0,
+                    CASE WHEN ifropacientelatitude::text ~ $3 THEN ROUND(ifropacientelatitude::text::numeric, 4)::float8 END AS latitude,
+                    CASE WHEN ifropacientelongitude::text ~ $3 THEN ROUND(ifropacientelongitude::text::numeric, 4)::float8 END AS longitude
+                FROM bpa WHERE {}
+                AND COALESCE(ifropacientequeixaprincipal::text, '') <> ''
+                {}
+            ) pontos
+            WHERE latitude IS NOT NULL AND longitude IS NOT NULL
+            GROUP BY 1, 2, 3, 4",
+            GROUPED_COUNT_FILTER, location_rules_filter(4)
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .bind(COORDINATE_PATTERN)
+            .bind(excluded_addresses)
+            .bind(placeholder_neighborhoods)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 1, true)?)
+    }
+
+    async fn count_by_competencia_neighborhood_complaint_and_age(&self, excluded_addresses: &[String], placeholder_neighborhoods: &[String], unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        // Atendimentos por bairro, queixa e idade simples. Atendimentos sem queixa entram como não classificados
+        let query = format!(
+            "SELECT ifrocompetencia::text AS competencia,
+                ifropacientebairro::text AS key_0,
+                COALESCE(NULLIF(ifropacientequeixaprincipal::text, ''), '{}') AS key_1,
+                {} AS key_2,
+                COUNT(*) AS quantidade
+            FROM bpa WHERE {}
+            AND ifropacienteidade::text ~ '{}'
+            AND COALESCE(ifropacientebairro::text, '') <> ''
+            {}
+            GROUP BY 1, 2, 3, 4",
+            UNCLASSIFIED_DISEASE, AGE_EXPRESSION, GROUPED_COUNT_FILTER, AGE_PATTERN, location_rules_filter(3)
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .bind(excluded_addresses)
+            .bind(placeholder_neighborhoods)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 3, false)?)
+    }
+
+    async fn count_by_competencia_and_procedure(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        // Atendimentos por código SUS do procedimento (10 dígitos, com os zeros à esquerda perdidos quando a
+        // coluna é lida como número), com o nome mais frequente informado para o código
+        let query = format!(
+            "SELECT ifrocompetencia::text AS competencia,
+                LPAD(REGEXP_REPLACE(ifroprocedimentosuscd::text, '[^0-9]', '', 'g'), 10, '0') AS key_0,
+                COALESCE(MODE() WITHIN GROUP (ORDER BY NULLIF(TRIM(ifroprocedimentonome::text), '')), '') AS key_1,
+                COUNT(*) AS quantidade
+            FROM bpa WHERE {}
+            AND REGEXP_REPLACE(COALESCE(ifroprocedimentosuscd::text, ''), '[^0-9]', '', 'g') <> ''
+            GROUP BY 1, 2",
+            GROUPED_COUNT_FILTER
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 2, false)?)
+    }
+
+    async fn count_by_competencia_and_municipality(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        // Atendimentos pelo código IBGE do município de residência, sem o dígito verificador,
+        // pois o campo pode vir com 6 ou 7 dígitos
+        let query = format!(
+            "SELECT ifrocompetencia::text AS competencia,
+                LEFT(REGEXP_REPLACE(ifromunicipioibge::text, '[^0-9]', '', 'g'), 6) AS key_0,
+                COUNT(*) AS quantidade
+            FROM bpa WHERE {}
+            AND LENGTH(REGEXP_REPLACE(COALESCE(ifromunicipioibge::text, ''), '[^0-9]', '', 'g')) >= 6
+            GROUP BY 1, 2",
+            GROUPED_COUNT_FILTER
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 1, false)?)
+    }
+
+    async fn count_visits_by_competencia_stages_and_arrival(&self, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        // Cada visita com os fluxos registrados (em ordem alfabética, separados por "|") e o dia da semana
+        // e a hora do primeiro registro; as visitas são contadas por combinação de fluxos e chegada
+        let query = format!(
+            "WITH visitas AS (
+                SELECT ifrocompetencia::text AS competencia,
+                    STRING_AGG(DISTINCT ifrotabelanome::text, '|' ORDER BY ifrotabelanome::text) AS etapas,
+                    COALESCE(MIN(ifrodiasemana::text), '') AS dia,
+                    COALESCE(MIN(SUBSTRING(ifrohoraatendimento::text FROM 1 FOR 2)) FILTER (WHERE ifrohoraatendimento::text ~ '^[0-9]{{2}}'), '') AS hora
+                FROM bpa WHERE {}
+                AND COALESCE(ifrovisitachave::text, '') <> ''
+                AND COALESCE(ifrotabelanome::text, '') <> ''
+                GROUP BY 1, ifrovisitachave
+            )
+            SELECT competencia, etapas AS key_0, dia AS key_1, hora AS key_2, COUNT(*) AS quantidade
+            FROM visitas
+            GROUP BY 1, 2, 3, 4",
+            GROUPED_COUNT_FILTER
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 3, false)?)
+    }
+
     
     async fn insert_nested_json_with_unit(
         &self, 
@@ -843,93 +1433,158 @@ impl DataRepository for PgDataRepository {
         Ok(exists)
     }
 
-    async fn fetch_distinct_health_units(
-        &self, 
-        table: &str, 
-        columns: &[String]
-    ) -> Result<HashMap<String, Vec<Value>>, Box<dyn Error + Send + Sync>> {
-        // Query para buscar valores únicos de unidades de saúde
+    async fn fetch_daily_counts(&self, unidade_id: i32, from: Option<NaiveDate>, to: Option<NaiveDate>, doenca: Option<&str>) -> Result<Vec<DailyCount>, Box<dyn Error + Send + Sync>> {
+        // A data do atendimento é armazenada como texto (AAAA-MM-DD). Valores fora do formato são ignorados e,
+        // como o texto não é convertido no banco, o período é comparado como texto, que segue a ordem das datas
+        let rows = sqlx::query(
+            "SELECT dia, queixa, COUNT(*) AS quantidade
+            FROM (
+                SELECT ifrodataatendimento::text AS dia,
+                    COALESCE(ifropacientequeixaprincipal::text, '') AS queixa
+                FROM bpa WHERE ifrounidadeid = $1
+            ) atendimentos
+            WHERE dia ~ '^[0-9]{4}-[0-9]{2}-[0-9]{2}$'
+            AND ($2::text IS NULL OR dia >= $2)
+            AND ($3::text IS NULL OR dia <= $3)
+            AND ($4::text IS NULL OR queixa = $4)
+            GROUP BY dia, queixa
+            ORDER BY dia"
+        )
+        .bind(unidade_id)
+        .bind(from.map(|date| date.format("%Y-%m-%d").to_string()))
+        .bind(to.map(|date| date.format("%Y-%m-%d").to_string()))
+        .bind(doenca)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut counts = Vec::with_capacity(rows.len());
+        for row in rows {
+            let dia: String = row.try_get("dia")?;
+            if let Some(data) = parse_attendance_date(&dia) {
+                counts.push(DailyCount {
+                    data,
+                    queixa: row.try_get("queixa")?,
+                    quantidade: row.try_get("quantidade")?,
+                });
+            }
+        }
+
+        Ok(counts)
+    }
+    async fn fetch_hourly_arrivals(&self, cbos: &[&str], tabela: &str, unidade_id: i32, competencias: Option<&[String]>) -> Result<Vec<HourlyArrivalCount>, Box<dyn Error + Send + Sync>> {
+        // A hora de chegada corresponde aos dois primeiros dígitos do horário de atendimento
         let query = format!(
-            "SELECT DISTINCT {} FROM {} WHERE {} IS NOT NULL AND {} IS NOT NULL ORDER BY {}",
-            columns.join(", "),
-            table,
-            columns[0], // ifrounidadeid
-            columns[1], // ifrounidadenome
-            columns[0]  // ordem por id
+            "SELECT dia, hora, COUNT(*) AS quantidade
+            FROM (
+                SELECT ifrodataatendimento::text AS dia,
+                    SUBSTRING(ifrohoraatendimento::text FROM 1 FOR 2)::integer AS hora
+                FROM bpa WHERE {}
+                AND ifroprofissionalcbods = ANY($3)
+                AND ifrotabelanome = $4
+                AND ifrohoraatendimento::text ~ '^[0-9]{{2}}'
+            ) consultas
+            WHERE dia ~ '^[0-9]{{4}}-[0-9]{{2}}-[0-9]{{2}}$'
+            GROUP BY dia, hora",
+            GROUPED_COUNT_FILTER
         );
-    
-        println!("Executing query: {}", query);
-    
-        // Executa a query
+
+        let cbos: Vec<String> = cbos.iter().map(|c| c.to_string()).collect();
+
         let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .bind(&cbos)
+            .bind(tabela)
             .fetch_all(&self.pool)
             .await?;
-    
-        println!("Number of rows returned: {}", rows.len());
-    
-        if rows.is_empty() {
-            println!("No health units found in table {}", table);
-            return Ok(HashMap::new());
-        }
-    
-        // Inicializa o resultado
-        let mut result: HashMap<String, Vec<Value>> = HashMap::new();
-        for col_name in columns {
-            result.insert(col_name.to_string(), Vec::new());
-        }
-    
-        // Para cada linha de resultado
-        for (row_idx, row) in rows.iter().enumerate() {
-            println!("Processing row {}", row_idx);
-            
-            // Para cada coluna
-            for (i, column_name) in columns.iter().enumerate() {
-                let column = row.columns().get(i).unwrap();
-                let type_name = column.type_info().to_string();
-                println!("Column {} ({}): type={}", i, column_name, type_name);
-                
-                let value: Value = match type_name.as_str() {
-                    "INT4" | "INT8" => {
-                        if let Ok(v) = row.try_get::<i64, _>(i) {
-                            println!("Integer value: {}", v);
-                            json!(v)
-                        } else if let Ok(v) = row.try_get::<i32, _>(i) {
-                            println!("Integer 32 value: {}", v);
-                            json!(v as i64)
-                        } else {
-                            println!("Failed to get integer value");
-                            Value::Null
-                        }
-                    },
-                    "VARCHAR" | "TEXT" => {
-                        if let Ok(v) = row.try_get::<String, _>(i) {
-                            println!("String value: {}", v);
-                            json!(v)
-                        } else {
-                            println!("Failed to get string value");
-                            Value::Null
-                        }
-                    },
-                    _ => {
-                        println!("Unknown type: {}", type_name);
-                        // Para outros tipos, tenta obter como string
-                        if let Ok(v) = row.try_get::<String, _>(i) {
-                            json!(v)
-                        } else {
-                            Value::Null
-                        }
-                    }
-                };
-    
-                // Adiciona o valor ao vetor da coluna
-                if let Some(column_values) = result.get_mut(column_name) {
-                    column_values.push(value);
-                }
+
+        let mut arrivals = Vec::with_capacity(rows.len());
+        for row in rows {
+            let dia: String = row.try_get("dia")?;
+            let hora: i32 = row.try_get("hora")?;
+            if let Some(data) = parse_attendance_date(&dia) {
+                arrivals.push(HourlyArrivalCount {
+                    data,
+                    hora: hora as u32,
+                    quantidade: row.try_get("quantidade")?,
+                });
             }
         }
-    
-        println!("Result: {:?}", result);
-        println!("Fetched distinct health units from table {}", table);
-        Ok(result)
+
+        Ok(arrivals)
+    }
+
+    async fn average_service_minutes(&self, cbos: &[&str], tabela: &str, excluded_names: &[String], unidade_id: i32, competencias: Option<&[String]>) -> Result<Option<f64>, Box<dyn Error + Send + Sync>> {
+        // Mesmo cálculo do tempo médio por médico: o horário de atendimento (HH:MM:SS) convertido em minutos
+        let query = format!(
+            "SELECT AVG(minutos)::float8 AS media
+            FROM (
+                SELECT SPLIT_PART(ifrohoraatendimento::text, ':', 1)::float8 * 60
+                    + SPLIT_PART(ifrohoraatendimento::text, ':', 2)::float8
+                    + SPLIT_PART(ifrohoraatendimento::text, ':', 3)::float8 / 60 AS minutos
+                FROM bpa WHERE {}
+                AND ifroprofissionalcbods = ANY($3)
+                AND ifrotabelanome = $4
+                AND NOT (COALESCE(ifroprofissionalnome::text, '') = ANY($5))
+                AND ifrohoraatendimento::text ~ '^[0-9]+:[0-9]+:[0-9]+(\\.[0-9]+)?$'
+            ) tempos
+            WHERE minutos > 0",
+            GROUPED_COUNT_FILTER
+        );
+
+        let cbos: Vec<String> = cbos.iter().map(|c| c.to_string()).collect();
+
+        let media: Option<f64> = sqlx::query_scalar(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .bind(&cbos)
+            .bind(tabela)
+            .bind(excluded_names)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Ok(media)
+    }
+
+    async fn count_by_competencia_professional_and_duration(
+        &self,
+        cbos: &[&str],
+        tabela: &str,
+        excluded_names: &[String],
+        unidade_id: i32,
+        competencias: Option<&[String]>
+    ) -> Result<Vec<GroupedCount>, Box<dyn Error + Send + Sync>> {
+        // Durações (HH:MM:SS) arredondadas para minutos inteiros, como no tempo médio por médico
+        let query = format!(
+            "SELECT competencia, key_0, ROUND(minutos)::integer::text AS key_1, COUNT(*) AS quantidade
+            FROM (
+                SELECT ifrocompetencia::text AS competencia,
+                    COALESCE(ifroprofissionalnome::text, '') AS key_0,
+                    SPLIT_PART(ifrohoraatendimento::text, ':', 1)::float8 * 60
+                        + SPLIT_PART(ifrohoraatendimento::text, ':', 2)::float8
+                        + SPLIT_PART(ifrohoraatendimento::text, ':', 3)::float8 / 60 AS minutos
+                FROM bpa WHERE {}
+                AND ifroprofissionalcbods = ANY($3)
+                AND ifrotabelanome = $4
+                AND NOT (COALESCE(ifroprofissionalnome::text, '') = ANY($5))
+                AND ifrohoraatendimento::text ~ '^[0-9]+:[0-9]+:[0-9]+(\\.[0-9]+)?$'
+            ) tempos
+            WHERE minutos > 0
+            GROUP BY 1, 2, 3",
+            GROUPED_COUNT_FILTER
+        );
+
+        let cbos: Vec<String> = cbos.iter().map(|c| c.to_string()).collect();
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .bind(&cbos)
+            .bind(tabela)
+            .bind(excluded_names)
+            .fetch_all(&self.pool)
+            .await?;
+
+        Ok(Self::rows_to_grouped_counts(&rows, 2, false)?)
     }
 }
\ No newline at end of file
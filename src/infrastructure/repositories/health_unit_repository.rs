@@ -0,0 +1,191 @@
+use async_trait::async_trait;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::domain::models::health_unit::{HealthUnitRecord, HealthUnitRequest};
+use crate::domain::repositories::health_unit::HealthUnitRepository;
+
+const HEALTH_UNIT_COLUMNS: &str = "ifrounidadeid, cnes, nome, municipio, tipo, latitude, longitude, ativo, capacidades, created_at, updated_at";
+
+// Unidades da tabela bpa, com o nome mais recente de cada uma
+const IMPORTED_UNITS_INSERT: &str = "INSERT INTO health_units (ifrounidadeid, nome)
+    SELECT DISTINCT ON (ifrounidadeid) ifrounidadeid, COALESCE(NULLIF(TRIM(ifrounidadenome::text), ''), 'Unidade ' || ifrounidadeid)
+    FROM bpa
+    WHERE ifrounidadeid IS NOT NULL AND ($1::int4[] IS NULL OR ifrounidadeid = ANY($1))
+    ORDER BY ifrounidadeid, ifrocompetencia DESC
+    ON CONFLICT (ifrounidadeid) DO NOTHING";
+
+#[derive(Clone)]
+pub struct PgHealthUnitRepository {
+    pool: PgPool,
+}
+
+impl PgHealthUnitRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn table_exists(&self, table: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+            .bind(table)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    /// Cria o cadastro de unidades, chamado uma vez na inicialização
+    pub async fn ensure_health_units_table(&self) -> Result<(), sqlx::Error> {
+        if self.table_exists("health_units").await? {
+            return Ok(());
+        }
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS health_units (
+                ifrounidadeid INTEGER PRIMARY KEY,
+                cnes VARCHAR(7) UNIQUE,
+                nome TEXT NOT NULL,
+                municipio TEXT,
+                tipo TEXT,
+                latitude DOUBLE PRECISION,
+                longitude DOUBLE PRECISION,
+                ativo BOOLEAN NOT NULL DEFAULT TRUE,
+                capacidades TEXT[] NOT NULL DEFAULT '{}',
+                created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+                updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+            );"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Na criação, o cadastro parte das unidades já importadas
+        if self.table_exists("bpa").await? {
+            sqlx::query(IMPORTED_UNITS_INSERT)
+                .bind(None::<Vec<i32>>)
+                .execute(&self.pool)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    fn row_to_unit(row: &PgRow) -> Result<HealthUnitRecord, sqlx::Error> {
+        Ok(HealthUnitRecord {
+            ifrounidadeid: row.try_get("ifrounidadeid")?,
+            cnes: row.try_get("cnes")?,
+            nome: row.try_get("nome")?,
+            municipio: row.try_get("municipio")?,
+            tipo: row.try_get("tipo")?,
+            latitude: row.try_get("latitude")?,
+            longitude: row.try_get("longitude")?,
+            ativo: row.try_get("ativo")?,
+            capacidades: row.try_get("capacidades")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl HealthUnitRepository for PgHealthUnitRepository {
+    async fn fetch_units(&self, ativo: Option<bool>) -> Result<Vec<HealthUnitRecord>, sqlx::Error> {
+        let query = format!(
+            "SELECT {} FROM health_units
+            WHERE ($1::boolean IS NULL OR ativo = $1)
+            ORDER BY ifrounidadeid",
+            HEALTH_UNIT_COLUMNS
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(ativo)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(Self::row_to_unit).collect()
+    }
+
+    async fn find_unit(&self, unidade_id: i32) -> Result<Option<HealthUnitRecord>, sqlx::Error> {
+        let query = format!("SELECT {} FROM health_units WHERE ifrounidadeid = $1", HEALTH_UNIT_COLUMNS);
+
+        let row = sqlx::query(&query)
+            .bind(unidade_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(Self::row_to_unit).transpose()
+    }
+
+    async fn create_unit(&self, unidade_id: i32, unit: &HealthUnitRequest) -> Result<HealthUnitRecord, sqlx::Error> {
+        let query = format!(
+            "INSERT INTO health_units (ifrounidadeid, cnes, nome, municipio, tipo, latitude, longitude, ativo, capacidades)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING {}",
+            HEALTH_UNIT_COLUMNS
+        );
+
+        let row = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(&unit.cnes)
+            .bind(&unit.nome)
+            .bind(&unit.municipio)
+            .bind(&unit.tipo)
+            .bind(unit.latitude)
+            .bind(unit.longitude)
+            .bind(unit.ativo.unwrap_or(true))
+            .bind(&unit.capacidades)
+            .fetch_one(&self.pool)
+            .await?;
+
+        Self::row_to_unit(&row)
+    }
+
+    async fn update_unit(&self, unidade_id: i32, unit: &HealthUnitRequest) -> Result<Option<HealthUnitRecord>, sqlx::Error> {
+        // Sem `ativo` no corpo, a situação atual é mantida
+        let query = format!(
+            "UPDATE health_units SET
+                cnes = $2,
+                nome = $3,
+                municipio = $4,
+                tipo = $5,
+                latitude = $6,
+                longitude = $7,
+                ativo = COALESCE($8, ativo),
+                capacidades = $9,
+                updated_at = NOW()
+            WHERE ifrounidadeid = $1
+            RETURNING {}",
+            HEALTH_UNIT_COLUMNS
+        );
+
+        let row = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(&unit.cnes)
+            .bind(&unit.nome)
+            .bind(&unit.municipio)
+            .bind(&unit.tipo)
+            .bind(unit.latitude)
+            .bind(unit.longitude)
+            .bind(unit.ativo)
+            .bind(&unit.capacidades)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(Self::row_to_unit).transpose()
+    }
+
+    async fn delete_unit(&self, unidade_id: i32) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM health_units WHERE ifrounidadeid = $1")
+            .bind(unidade_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn register_imported_units(&self, unidade_ids: &[i32]) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(IMPORTED_UNITS_INSERT)
+            .bind(unidade_ids)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -2,4 +2,10 @@ pub mod user_repository;
 pub mod machine_information_repository;
 pub mod auth_pronto_repository;
 pub mod data_upa_repository;
-pub mod audit_repository;
\ No newline at end of file
+pub mod audit_repository;
+pub mod reclassification_repository;
+pub mod classification_review_repository;
+pub mod surveillance_repository;
+pub mod unit_population_repository;
+pub mod unit_chart_rules_repository;
+pub mod health_unit_repository;
\ No newline at end of file
@@ -0,0 +1,225 @@
+use async_trait::async_trait;
+use log::info;
+use serde_json::Value;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::domain::models::reclassification::{
+    CondutaRecord,
+    CreateReclassificationJobDto,
+    ReclassificationJob,
+    RECLASSIFICATION_STATUS_PENDING,
+};
+use crate::domain::repositories::reclassification::ReclassificationRepository;
+
+#[derive(Clone)]
+pub struct PgReclassificationRepository {
+    pool: PgPool,
+}
+
+impl PgReclassificationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Cria a tabela dos trabalhos de reclassificação. Executado uma vez na inicialização
+    pub async fn ensure_jobs_table(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS reclassification_jobs (
+                id UUID PRIMARY KEY,
+                ifrounidadeid INTEGER NOT NULL,
+                competencia_inicio TEXT NOT NULL,
+                competencia_fim TEXT NOT NULL,
+                status TEXT NOT NULL,
+                requested_by TEXT NOT NULL,
+                summary JSONB,
+                error_message TEXT,
+                created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+                finished_at TIMESTAMP
+            );"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn table_exists(&self, table: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT EXISTS (SELECT FROM information_schema.tables WHERE table_name = $1)")
+            .bind(table)
+            .fetch_one(&self.pool)
+            .await
+    }
+}
+
+#[async_trait]
+impl ReclassificationRepository for PgReclassificationRepository {
+    async fn create_job(&self, job: &CreateReclassificationJobDto, requested_by: &str) -> Result<Uuid, sqlx::Error> {
+        let id: Uuid = sqlx::query_scalar(
+            "INSERT INTO reclassification_jobs (id, ifrounidadeid, competencia_inicio, competencia_fim, status, requested_by)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id"
+        )
+        .bind(Uuid::new_v4())
+        .bind(job.unidade_id)
+        .bind(&job.competencia_inicio)
+        .bind(&job.competencia_fim)
+        .bind(RECLASSIFICATION_STATUS_PENDING)
+        .bind(requested_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        info!("Reclassification job created with id: {}", id);
+        Ok(id)
+    }
+
+    async fn update_job_status(&self, id: Uuid, status: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE reclassification_jobs SET status = $1 WHERE id = $2")
+            .bind(status)
+            .bind(id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn finish_job(&self, id: Uuid, status: &str, summary: Option<Value>, error_message: Option<String>) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "UPDATE reclassification_jobs
+            SET status = $1, summary = $2, error_message = $3, finished_at = NOW()
+            WHERE id = $4"
+        )
+        .bind(status)
+        .bind(summary)
+        .bind(error_message)
+        .bind(id)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn find_job(&self, id: Uuid) -> Result<Option<ReclassificationJob>, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT id, ifrounidadeid, competencia_inicio, competencia_fim, status, requested_by,
+                summary, error_message, created_at, finished_at
+            FROM reclassification_jobs
+            WHERE id = $1"
+        )
+        .bind(id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(ReclassificationJob {
+                id: row.try_get("id")?,
+                ifrounidadeid: row.try_get("ifrounidadeid")?,
+                competencia_inicio: row.try_get("competencia_inicio")?,
+                competencia_fim: row.try_get("competencia_fim")?,
+                status: row.try_get("status")?,
+                requested_by: row.try_get("requested_by")?,
+                summary: row.try_get("summary")?,
+                error_message: row.try_get("error_message")?,
+                created_at: row.try_get("created_at")?,
+                finished_at: row.try_get("finished_at")?,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    async fn fetch_competencias(&self, unidade_id: i32) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar("SELECT DISTINCT ifrocompetencia::text FROM bpa WHERE ifrounidadeid = $1")
+            .bind(unidade_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    async fn fetch_conduta_records(&self, unidade_id: i32, competencias: &[String]) -> Result<Vec<CondutaRecord>, sqlx::Error> {
+        // Registros importados antes da tabela auxiliar não possuem o texto da conduta
+        if !self.table_exists("bpa_conduta").await? {
+            info!("Table bpa_conduta does not exist. No records to reclassify.");
+            return Ok(Vec::new());
+        }
+
+        // Registros corrigidos manualmente não são reclassificados
+        let corrections_filter = if self.table_exists("classification_corrections").await? {
+            "AND NOT EXISTS (
+                SELECT 1 FROM classification_corrections cc
+                WHERE cc.ifroid = c.ifroid::text AND cc.ifrounidadeid = $1
+            )"
+        } else {
+            ""
+        };
+
+        let query = format!(
+            "SELECT c.ifroid::text AS ifroid,
+                b.ifrocompetencia::text AS ifrocompetencia,
+                COALESCE(c.ifroconsultaconduta::text, '') AS conduta,
+                COALESCE(b.ifropacientequeixaprincipal::text, '') AS queixa
+            FROM bpa_conduta c
+            JOIN bpa b ON b.ifroid::text = c.ifroid::text AND b.ifrounidadeid::text = c.ifrounidadeid::text
+            WHERE b.ifrounidadeid = $1 AND b.ifrocompetencia = ANY($2)
+            {}",
+            corrections_filter
+        );
+
+        let rows = sqlx::query(&query)
+            .bind(unidade_id)
+            .bind(competencias)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter()
+            .map(|row| Ok(CondutaRecord {
+                ifroid: row.try_get("ifroid")?,
+                ifrocompetencia: row.try_get("ifrocompetencia")?,
+                conduta: row.try_get("conduta")?,
+                current_complaint: row.try_get("queixa")?,
+            }))
+            .collect()
+    }
+
+    async fn update_complaints(&self, unidade_id: i32, classifications: &[(String, String, f64)]) -> Result<u64, sqlx::Error> {
+        let batch_size = 5000;
+        let mut updated = 0;
+
+        for batch in classifications.chunks(batch_size) {
+            let ids: Vec<String> = batch.iter().map(|(id, _, _)| id.clone()).collect();
+            let complaints: Vec<String> = batch.iter().map(|(_, complaint, _)| complaint.clone()).collect();
+            let confidences: Vec<f64> = batch.iter().map(|(_, _, confidence)| *confidence).collect();
+
+            let mut tx = self.pool.begin().await?;
+
+            let result = sqlx::query(
+                "UPDATE bpa AS b
+                SET ifropacientequeixaprincipal = v.queixa
+                FROM UNNEST($1::text[], $2::text[]) AS v(ifroid, queixa)
+                WHERE b.ifroid::text = v.ifroid AND b.ifrounidadeid = $3
+                AND b.ifropacientequeixaprincipal IS DISTINCT FROM v.queixa"
+            )
+            .bind(&ids)
+            .bind(&complaints)
+            .bind(unidade_id)
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query(
+                "UPDATE bpa_conduta AS c
+                SET ifroclassificacaoconfianca = v.confianca::text
+                FROM UNNEST($1::text[], $2::float8[]) AS v(ifroid, confianca)
+                WHERE c.ifroid::text = v.ifroid AND c.ifrounidadeid::text = $3::text"
+            )
+            .bind(&ids)
+            .bind(&confidences)
+            .bind(unidade_id)
+            .execute(&mut *tx)
+            .await?;
+
+            tx.commit().await?;
+
+            updated += result.rows_affected();
+        }
+
+        Ok(updated)
+    }
+}
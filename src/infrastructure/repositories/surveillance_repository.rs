@@ -0,0 +1,269 @@
+use async_trait::async_trait;
+use chrono::NaiveDate;
+use log::info;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+use uuid::Uuid;
+
+use crate::domain::models::surveillance::{
+    AlertBaseline,
+    AlertQuery,
+    AlertSignal,
+    NeighborhoodDailyCount,
+    SurveillanceAlert,
+    ALERT_STATUS_OPEN,
+};
+use crate::domain::repositories::surveillance::SurveillanceRepository;
+use crate::utils::time_series::parse_attendance_date;
+
+const ALERT_COLUMNS: &str = "id, ifrounidadeid, doenca, NULLIF(bairro, '') AS bairro, semana, semana_inicio, semana_fim,
+    algoritmo, severidade, observado, baseline_inicio, baseline_fim, baseline_media, baseline_desvio,
+    estatistica, limiar, status, status_updated_by, status_updated_at, created_at, updated_at";
+
+#[derive(Clone)]
+pub struct PgSurveillanceRepository {
+    pool: PgPool,
+}
+
+impl PgSurveillanceRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Cria a tabela de alertas, chamada uma vez na inicialização
+    pub async fn ensure_alerts_table(&self) -> Result<(), sqlx::Error> {
+        // Alertas da unidade inteira são armazenados com bairro vazio, mantendo a chave única
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS surveillance_alerts (
+                id UUID PRIMARY KEY,
+                ifrounidadeid INTEGER NOT NULL,
+                doenca TEXT NOT NULL,
+                bairro TEXT NOT NULL DEFAULT '',
+                semana TEXT NOT NULL,
+                semana_inicio DATE NOT NULL,
+                semana_fim DATE NOT NULL,
+                algoritmo TEXT NOT NULL,
+                severidade TEXT NOT NULL,
+                observado BIGINT NOT NULL,
+                baseline_inicio TEXT NOT NULL,
+                baseline_fim TEXT NOT NULL,
+                baseline_media DOUBLE PRECISION NOT NULL,
+                baseline_desvio DOUBLE PRECISION NOT NULL,
+                estatistica DOUBLE PRECISION NOT NULL,
+                limiar DOUBLE PRECISION NOT NULL,
+                status TEXT NOT NULL DEFAULT 'aberto',
+                status_updated_by TEXT,
+                status_updated_at TIMESTAMP,
+                created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+                updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+                UNIQUE (ifrounidadeid, doenca, bairro, semana)
+            );"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn row_to_alert(row: &PgRow) -> Result<SurveillanceAlert, sqlx::Error> {
+        Ok(SurveillanceAlert {
+            id: row.try_get("id")?,
+            ifrounidadeid: row.try_get("ifrounidadeid")?,
+            doenca: row.try_get("doenca")?,
+            bairro: row.try_get("bairro")?,
+            semana: row.try_get("semana")?,
+            semana_inicio: row.try_get("semana_inicio")?,
+            semana_fim: row.try_get("semana_fim")?,
+            algoritmo: row.try_get("algoritmo")?,
+            severidade: row.try_get("severidade")?,
+            observado: row.try_get("observado")?,
+            baseline: AlertBaseline {
+                semana_inicio: row.try_get("baseline_inicio")?,
+                semana_fim: row.try_get("baseline_fim")?,
+                media: row.try_get("baseline_media")?,
+                desvio_padrao: row.try_get("baseline_desvio")?,
+            },
+            estatistica: row.try_get("estatistica")?,
+            limiar: row.try_get("limiar")?,
+            status: row.try_get("status")?,
+            status_updated_by: row.try_get("status_updated_by")?,
+            status_updated_at: row.try_get("status_updated_at")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl SurveillanceRepository for PgSurveillanceRepository {
+    async fn fetch_daily_counts_by_neighborhood(&self, unidade_id: i32, period: Option<(NaiveDate, NaiveDate)>) -> Result<Vec<NeighborhoodDailyCount>, sqlx::Error> {
+        // A data do atendimento é armazenada como texto (AAAA-MM-DD). Valores fora do formato são ignorados e as
+        // datas inexistentes são descartadas na conversão; o período é comparado como texto, na ordem das datas
+        let rows = sqlx::query(
+            "SELECT dia, queixa, bairro, COUNT(*) AS quantidade
+            FROM (
+                SELECT ifrodataatendimento::text AS dia,
+                    COALESCE(ifropacientequeixaprincipal::text, '') AS queixa,
+                    COALESCE(ifropacientebairro::text, '') AS bairro
+                FROM bpa WHERE ifrounidadeid = $1
+            ) atendimentos
+            WHERE dia ~ '^[0-9]{4}-[0-9]{2}-[0-9]{2}$' AND queixa <> ''
+            AND ($2::text IS NULL OR dia BETWEEN $2 AND $3)
+            GROUP BY dia, queixa, bairro
+            ORDER BY dia"
+        )
+        .bind(unidade_id)
+        .bind(period.map(|(start, _)| start.format("%Y-%m-%d").to_string()))
+        .bind(period.map(|(_, end)| end.format("%Y-%m-%d").to_string()))
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut counts = Vec::with_capacity(rows.len());
+        for row in rows {
+            let dia: String = row.try_get("dia")?;
+            if let Some(data) = parse_attendance_date(&dia) {
+                counts.push(NeighborhoodDailyCount {
+                    data,
+                    queixa: row.try_get("queixa")?,
+                    bairro: row.try_get("bairro")?,
+                    quantidade: row.try_get("quantidade")?,
+                });
+            }
+        }
+
+        Ok(counts)
+    }
+
+    async fn fetch_first_attendance_day(&self, unidade_id: i32) -> Result<Option<NaiveDate>, sqlx::Error> {
+        // Mesmos critérios das contagens diárias: o primeiro dia é o menor texto que for uma data existente
+        let days: Vec<String> = sqlx::query_scalar(
+            "SELECT DISTINCT dia
+            FROM (
+                SELECT ifrodataatendimento::text AS dia,
+                    COALESCE(ifropacientequeixaprincipal::text, '') AS queixa
+                FROM bpa WHERE ifrounidadeid = $1
+            ) atendimentos
+            WHERE dia ~ '^[0-9]{4}-[0-9]{2}-[0-9]{2}$' AND queixa <> ''
+            ORDER BY dia"
+        )
+        .bind(unidade_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(days.iter().find_map(|dia| parse_attendance_date(dia)))
+    }
+
+    async fn sync_alerts(&self, unidade_id: i32, signals: &[AlertSignal], period: Option<(NaiveDate, NaiveDate)>) -> Result<u64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let mut current_ids: Vec<Uuid> = Vec::with_capacity(signals.len());
+
+        // Sinais recalculados atualizam o alerta existente sem alterar o status definido pelos administradores
+        for signal in signals {
+            let id: Uuid = sqlx::query_scalar(
+                "INSERT INTO surveillance_alerts
+                    (id, ifrounidadeid, doenca, bairro, semana, semana_inicio, semana_fim, algoritmo, severidade,
+                    observado, baseline_inicio, baseline_fim, baseline_media, baseline_desvio, estatistica, limiar)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                ON CONFLICT (ifrounidadeid, doenca, bairro, semana) DO UPDATE SET
+                    algoritmo = EXCLUDED.algoritmo,
+                    severidade = EXCLUDED.severidade,
+                    observado = EXCLUDED.observado,
+                    baseline_inicio = EXCLUDED.baseline_inicio,
+                    baseline_fim = EXCLUDED.baseline_fim,
+                    baseline_media = EXCLUDED.baseline_media,
+                    baseline_desvio = EXCLUDED.baseline_desvio,
+                    estatistica = EXCLUDED.estatistica,
+                    limiar = EXCLUDED.limiar,
+                    updated_at = NOW()
+                RETURNING id"
+            )
+            .bind(Uuid::new_v4())
+            .bind(unidade_id)
+            .bind(&signal.doenca)
+            .bind(signal.bairro.as_deref().unwrap_or(""))
+            .bind(&signal.semana)
+            .bind(signal.semana_inicio)
+            .bind(signal.semana_fim)
+            .bind(&signal.algoritmo)
+            .bind(&signal.severidade)
+            .bind(signal.observado)
+            .bind(&signal.baseline.semana_inicio)
+            .bind(&signal.baseline.semana_fim)
+            .bind(signal.baseline.media)
+            .bind(signal.baseline.desvio_padrao)
+            .bind(signal.estatistica)
+            .bind(signal.limiar)
+            .fetch_one(&mut *tx)
+            .await?;
+
+            current_ids.push(id);
+        }
+
+        // Alertas abertos que deixaram de ser sinalizados (ex.: após correções de classificação) são removidos,
+        // apenas nas semanas recalculadas
+        let removed = sqlx::query(
+            "DELETE FROM surveillance_alerts
+            WHERE ifrounidadeid = $1 AND status = $2 AND id <> ALL($3)
+            AND ($4::date IS NULL OR semana_inicio BETWEEN $4 AND $5)"
+        )
+        .bind(unidade_id)
+        .bind(ALERT_STATUS_OPEN)
+        .bind(&current_ids)
+        .bind(period.map(|(start, _)| start))
+        .bind(period.map(|(_, end)| end))
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+
+        info!("Unit {}: {} surveillance alerts synced, {} stale alerts removed", unidade_id, current_ids.len(), removed);
+
+        Ok(current_ids.len() as u64)
+    }
+
+    async fn fetch_alerts(&self, unidade_id: i32, query: &AlertQuery) -> Result<Vec<SurveillanceAlert>, sqlx::Error> {
+        // Alertas mais recentes e mais graves aparecem primeiro
+        let sql = format!(
+            "SELECT {} FROM surveillance_alerts
+            WHERE ifrounidadeid = $1
+            AND ($2::text IS NULL OR status = $2)
+            AND ($3::text IS NULL OR severidade = $3)
+            AND ($4::text IS NULL OR doenca = $4)
+            ORDER BY semana_inicio DESC,
+                CASE severidade WHEN 'alta' THEN 0 WHEN 'media' THEN 1 ELSE 2 END,
+                doenca, bairro",
+            ALERT_COLUMNS
+        );
+
+        let rows = sqlx::query(&sql)
+            .bind(unidade_id)
+            .bind(&query.status)
+            .bind(&query.severidade)
+            .bind(&query.doenca)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(Self::row_to_alert).collect()
+    }
+
+    async fn update_alert_status(&self, unidade_id: i32, alert_id: Uuid, status: &str, updated_by: &str) -> Result<Option<SurveillanceAlert>, sqlx::Error> {
+        let sql = format!(
+            "UPDATE surveillance_alerts
+            SET status = $1, status_updated_by = $2, status_updated_at = NOW()
+            WHERE id = $3 AND ifrounidadeid = $4
+            RETURNING {}",
+            ALERT_COLUMNS
+        );
+
+        let row = sqlx::query(&sql)
+            .bind(status)
+            .bind(updated_by)
+            .bind(alert_id)
+            .bind(unidade_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(Self::row_to_alert).transpose()
+    }
+}
@@ -0,0 +1,114 @@
+use async_trait::async_trait;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::domain::models::unit_chart_rules::{UnitChartRules, UnitChartRulesRequest};
+use crate::domain::repositories::unit_chart_rules::UnitChartRulesRepository;
+
+#[derive(Clone)]
+pub struct PgUnitChartRulesRepository {
+    pool: PgPool,
+}
+
+impl PgUnitChartRulesRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    async fn table_exists(&self, table: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT to_regclass($1) IS NOT NULL")
+            .bind(table)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    /// Cria a tabela de regras, chamada uma vez na inicialização
+    pub async fn ensure_rules_table(&self) -> Result<(), sqlx::Error> {
+        if self.table_exists("unit_chart_rules").await? {
+            return Ok(());
+        }
+
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS unit_chart_rules (
+                ifrounidadeid INTEGER PRIMARY KEY,
+                enderecos_excluidos TEXT[] NOT NULL DEFAULT '{}',
+                bairros_genericos TEXT[] NOT NULL DEFAULT '{}',
+                graficos_nao_suportados TEXT[] NOT NULL DEFAULT '{}',
+                updated_by TEXT,
+                updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+            );"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Regra que era fixa no código: a unidade 3 sem endereços georreferenciados. O endereço
+        // "DO IPE" fica fora dos mapas pelas regras padrão, inclusive em unidades importadas depois
+        sqlx::query(
+            "INSERT INTO unit_chart_rules (ifrounidadeid, enderecos_excluidos, graficos_nao_suportados, updated_by)
+            VALUES (3, $1, ARRAY[
+                'heat-map-with-disease-indication',
+                'heat-map-with-the-number-of-medical-appointments-by-neighborhood',
+                'disease-hotspots'
+            ], 'sistema')
+            ON CONFLICT (ifrounidadeid) DO NOTHING"
+        )
+        .bind(&UnitChartRules::defaults(3).enderecos_excluidos)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn row_to_rules(row: &PgRow) -> Result<UnitChartRules, sqlx::Error> {
+        Ok(UnitChartRules {
+            ifrounidadeid: row.try_get("ifrounidadeid")?,
+            enderecos_excluidos: row.try_get("enderecos_excluidos")?,
+            bairros_genericos: row.try_get("bairros_genericos")?,
+            graficos_nao_suportados: row.try_get("graficos_nao_suportados")?,
+            updated_by: row.try_get("updated_by")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl UnitChartRulesRepository for PgUnitChartRulesRepository {
+    async fn fetch_rules(&self, unidade_id: i32) -> Result<UnitChartRules, sqlx::Error> {
+        let row = sqlx::query(
+            "SELECT ifrounidadeid, enderecos_excluidos, bairros_genericos, graficos_nao_suportados, updated_by, updated_at
+            FROM unit_chart_rules
+            WHERE ifrounidadeid = $1"
+        )
+        .bind(unidade_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Self::row_to_rules(&row),
+            None => Ok(UnitChartRules::defaults(unidade_id)),
+        }
+    }
+
+    async fn upsert_rules(&self, unidade_id: i32, rules: &UnitChartRulesRequest, updated_by: &str) -> Result<UnitChartRules, sqlx::Error> {
+        let row = sqlx::query(
+            "INSERT INTO unit_chart_rules (ifrounidadeid, enderecos_excluidos, bairros_genericos, graficos_nao_suportados, updated_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (ifrounidadeid) DO UPDATE SET
+                enderecos_excluidos = EXCLUDED.enderecos_excluidos,
+                bairros_genericos = EXCLUDED.bairros_genericos,
+                graficos_nao_suportados = EXCLUDED.graficos_nao_suportados,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING ifrounidadeid, enderecos_excluidos, bairros_genericos, graficos_nao_suportados, updated_by, updated_at"
+        )
+        .bind(unidade_id)
+        .bind(&rules.enderecos_excluidos)
+        .bind(&rules.bairros_genericos)
+        .bind(&rules.graficos_nao_suportados)
+        .bind(updated_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Self::row_to_rules(&row)
+    }
+}
@@ -0,0 +1,175 @@
+use async_trait::async_trait;
+use sqlx::postgres::PgRow;
+use sqlx::{PgPool, Row};
+
+use crate::domain::models::incidence::{NeighborhoodPopulation, NeighborhoodPopulationEntry};
+use crate::domain::models::unit_comparison::{UnitPopulation, UnitPopulationRequest};
+use crate::domain::repositories::unit_population::UnitPopulationRepository;
+
+#[derive(Clone)]
+pub struct PgUnitPopulationRepository {
+    pool: PgPool,
+}
+
+impl PgUnitPopulationRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Cria a tabela de população das unidades, chamada uma vez na inicialização
+    pub async fn ensure_populations_table(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS unit_populations (
+                ifrounidadeid INTEGER PRIMARY KEY,
+                populacao BIGINT NOT NULL,
+                ano INTEGER,
+                fonte TEXT,
+                updated_by TEXT,
+                updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+            );"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Cria a tabela de população dos bairros, chamada uma vez na inicialização
+    pub async fn ensure_neighborhood_populations_table(&self) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS neighborhood_populations (
+                ifrounidadeid INTEGER NOT NULL,
+                bairro TEXT NOT NULL,
+                faixa_inicio INTEGER NOT NULL,
+                faixa_fim INTEGER,
+                populacao BIGINT NOT NULL,
+                ano INTEGER,
+                fonte TEXT,
+                updated_by TEXT,
+                updated_at TIMESTAMP NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (ifrounidadeid, bairro, faixa_inicio)
+            );"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    fn row_to_neighborhood_population(row: &PgRow) -> Result<NeighborhoodPopulation, sqlx::Error> {
+        Ok(NeighborhoodPopulation {
+            ifrounidadeid: row.try_get("ifrounidadeid")?,
+            bairro: row.try_get("bairro")?,
+            faixa_inicio: row.try_get("faixa_inicio")?,
+            faixa_fim: row.try_get("faixa_fim")?,
+            populacao: row.try_get("populacao")?,
+            ano: row.try_get("ano")?,
+            fonte: row.try_get("fonte")?,
+            updated_by: row.try_get("updated_by")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
+    fn row_to_population(row: &PgRow) -> Result<UnitPopulation, sqlx::Error> {
+        Ok(UnitPopulation {
+            ifrounidadeid: row.try_get("ifrounidadeid")?,
+            populacao: row.try_get("populacao")?,
+            ano: row.try_get("ano")?,
+            fonte: row.try_get("fonte")?,
+            updated_by: row.try_get("updated_by")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+#[async_trait]
+impl UnitPopulationRepository for PgUnitPopulationRepository {
+    async fn fetch_populations(&self, unidade_ids: &[i32]) -> Result<Vec<UnitPopulation>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT ifrounidadeid, populacao, ano, fonte, updated_by, updated_at
+            FROM unit_populations
+            WHERE ifrounidadeid = ANY($1)
+            ORDER BY ifrounidadeid"
+        )
+        .bind(unidade_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_population).collect()
+    }
+
+    async fn upsert_population(&self, unidade_id: i32, population: &UnitPopulationRequest, updated_by: &str) -> Result<UnitPopulation, sqlx::Error> {
+        let row = sqlx::query(
+            "INSERT INTO unit_populations (ifrounidadeid, populacao, ano, fonte, updated_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (ifrounidadeid) DO UPDATE SET
+                populacao = EXCLUDED.populacao,
+                ano = EXCLUDED.ano,
+                fonte = EXCLUDED.fonte,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING ifrounidadeid, populacao, ano, fonte, updated_by, updated_at"
+        )
+        .bind(unidade_id)
+        .bind(population.populacao)
+        .bind(population.ano)
+        .bind(&population.fonte)
+        .bind(updated_by)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Self::row_to_population(&row)
+    }
+
+    async fn fetch_neighborhood_populations(&self, unidade_id: i32) -> Result<Vec<NeighborhoodPopulation>, sqlx::Error> {
+        let rows = sqlx::query(
+            "SELECT ifrounidadeid, bairro, faixa_inicio, faixa_fim, populacao, ano, fonte, updated_by, updated_at
+            FROM neighborhood_populations
+            WHERE ifrounidadeid = $1
+            ORDER BY bairro, faixa_inicio"
+        )
+        .bind(unidade_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(Self::row_to_neighborhood_population).collect()
+    }
+
+    async fn replace_neighborhood_populations(
+        &self,
+        unidade_id: i32,
+        entries: &[NeighborhoodPopulationEntry],
+        ano: Option<i32>,
+        fonte: Option<&str>,
+        updated_by: &str,
+    ) -> Result<u64, sqlx::Error> {
+        // A importação substitui toda a população da unidade
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("DELETE FROM neighborhood_populations WHERE ifrounidadeid = $1")
+            .bind(unidade_id)
+            .execute(&mut *tx)
+            .await?;
+
+        for entry in entries {
+            sqlx::query(
+                "INSERT INTO neighborhood_populations (ifrounidadeid, bairro, faixa_inicio, faixa_fim, populacao, ano, fonte, updated_by)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+            )
+            .bind(unidade_id)
+            .bind(&entry.bairro)
+            .bind(entry.faixa_inicio)
+            .bind(entry.faixa_fim)
+            .bind(entry.populacao)
+            .bind(ano)
+            .bind(fonte)
+            .bind(updated_by)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+
+        Ok(entries.len() as u64)
+    }
+}
@@ -17,12 +17,23 @@ use new_data_upa_dashboard_api::{
         user_service::UserService, 
         visualization_data_service::VisualizationDataService,
         information_service::InformationService,
+        reclassification_service::ReclassificationService,
+        classification_review_service::ClassificationReviewService,
+        graph_aggregation::GraphRegistry,
+        surveillance_service::SurveillanceService,
+        health_unit_service::HealthUnitService,
    }, infrastructure::{
         database::init_database,
         repositories::{
             audit_repository::PgAuditRepository, 
             auth_pronto_repository::SqlServerAuthProntoRepository, 
             data_upa_repository::PgDataRepository, 
+            reclassification_repository::PgReclassificationRepository,
+            classification_review_repository::PgClassificationReviewRepository,
+            surveillance_repository::PgSurveillanceRepository,
+            unit_population_repository::PgUnitPopulationRepository,
+            unit_chart_rules_repository::PgUnitChartRulesRepository,
+            health_unit_repository::PgHealthUnitRepository,
             user_repository::PgUserRepository
         },
    }, middleware::{
@@ -58,27 +69,102 @@ async fn main() -> std::io::Result<()> {
    let user_repository = web::Data::new(PgUserRepository::new(pool.clone()));
    let data_repository = web::Data::new(PgDataRepository::new(pool.clone()));
    let audit_repository = web::Data::new(PgAuditRepository::new(pool.clone()));
+   let reclassification_repository = web::Data::new(PgReclassificationRepository::new(pool.clone()));
+   let classification_review_repository = web::Data::new(PgClassificationReviewRepository::new(pool.clone()));
+   let surveillance_repository = web::Data::new(PgSurveillanceRepository::new(pool.clone()));
+   let unit_population_repository = web::Data::new(PgUnitPopulationRepository::new(pool.clone()));
+   let unit_chart_rules_repository = web::Data::new(PgUnitChartRulesRepository::new(pool.clone()));
+   let health_unit_repository = web::Data::new(PgHealthUnitRepository::new(pool.clone()));
    
    info!("Repositórios criados");
 
+   // Colunas opcionais da tabela bpa, criadas uma vez para que as consultas dos gráficos sejam apenas leituras
+   data_repository.ensure_optional_bpa_columns().await
+       .expect("Failed to ensure optional bpa columns");
+
+   // Fatias incrementais dos gráficos e competências pendentes de recálculo
+   data_repository.ensure_graph_data_slices_table().await
+       .expect("Failed to ensure graph data slices table");
+   data_repository.ensure_graph_data_pending_table().await
+       .expect("Failed to ensure graph data pending table");
+
+   // Condutas dos atendimentos, trabalhos de reclassificação e correções manuais, criados uma vez em vez de a
+   // cada importação ou consulta
+   data_repository.ensure_conduta_table().await
+       .expect("Failed to ensure bpa_conduta table");
+   reclassification_repository.ensure_jobs_table().await
+       .expect("Failed to ensure reclassification jobs table");
+   classification_review_repository.ensure_corrections_table().await
+       .expect("Failed to ensure classification corrections table");
+
+   // Tabela de regras dos gráficos, criada uma vez em vez de a cada consulta
+   unit_chart_rules_repository.ensure_rules_table().await
+       .expect("Failed to ensure unit chart rules table");
+
+   // Alertas de vigilância, criados uma vez em vez de a cada detecção ou consulta
+   surveillance_repository.ensure_alerts_table().await
+       .expect("Failed to ensure surveillance alerts table");
+
+   // Populações das unidades e dos bairros usadas nas taxas
+   unit_population_repository.ensure_populations_table().await
+       .expect("Failed to ensure unit populations table");
+   unit_population_repository.ensure_neighborhood_populations_table().await
+       .expect("Failed to ensure neighborhood populations table");
+
+   // Cadastro de unidades, semeado com as unidades já importadas na primeira criação
+   health_unit_repository.ensure_health_units_table().await
+       .expect("Failed to ensure health units table");
+
+   // Gráficos disponíveis para recálculo e consulta
+   let graph_registry = web::Data::new(GraphRegistry::new());
+
    // Cria service de dados UPA
    let data_upa_service = web::Data::new(DataUpaService::new(
        data_repository.clone(),
+       health_unit_repository.clone(),
+       config.visit_key_secret.clone(),
+   ));
+
+   let health_unit_service = web::Data::new(HealthUnitService::new(
+       health_unit_repository.clone(),
+   ));
+
+   let surveillance_service = web::Data::new(SurveillanceService::new(
+       surveillance_repository.clone(),
    ));
 
    let update_graph_data_service = web::Data::new(UpdateGraphDataService::new(
        data_repository.clone(),
+       graph_registry.clone(),
+       surveillance_service.clone(),
+       unit_chart_rules_repository.clone(),
+       health_unit_repository.clone(),
    ));
 
    let visualization_data_service = web::Data::new(VisualizationDataService::new(
         data_repository.clone(),
         user_repository.clone(),
+        graph_registry.clone(),
+        unit_population_repository.clone(),
+        unit_chart_rules_repository.clone(),
+       health_unit_repository.clone(),
+       config.min_cell_size,
    ));
 
    let information_service = web::Data::new(InformationService::new(
         audit_repository.clone()
     ));
 
+   let reclassification_service = web::Data::new(ReclassificationService::new(
+        reclassification_repository.clone(),
+        update_graph_data_service.clone(),
+   ));
+
+   let classification_review_service = web::Data::new(ClassificationReviewService::new(
+        classification_review_repository.clone(),
+        data_repository.clone(),
+   ));
+
 
    info!("Serviço de dados UPA criado");
 
@@ -87,7 +173,7 @@ async fn main() -> std::io::Result<()> {
        user_repository.clone(),
        password_encryptor.clone(),
        web::Data::new(config.clone()),
-       data_repository.clone(),
+       health_unit_repository.clone(),
    ));
 
 
@@ -140,6 +226,10 @@ async fn main() -> std::io::Result<()> {
             .app_data(update_graph_data_service.clone())
             .app_data(visualization_data_service.clone())
             .app_data(information_service.clone())
+            .app_data(reclassification_service.clone())
+            .app_data(classification_review_service.clone())
+            .app_data(surveillance_service.clone())
+            .app_data(health_unit_service.clone())
             .app_data(user_service.clone())
             .app_data(auth_service.clone())
             .app_data(auth_pronto_service)
@@ -1,5 +1,5 @@
 use actix_web::web;
-use crate::handlers::data::{data_upa_handler,update_graph_data_handler, visualization_data_handler};
+use crate::handlers::data::{classification_review_handler, data_upa_handler, health_unit_handler, population_handler, reclassification_handler, surveillance_handler, unit_chart_rules_handler, unit_comparison_handler, update_graph_data_handler, visualization_data_handler};
 
 pub fn configure_routes(cfg: &mut web::ServiceConfig) {
     cfg.service(
@@ -60,10 +60,84 @@ pub fn configure_routes(cfg: &mut web::ServiceConfig) {
                 web::resource("/user/{user_id}/unit/{unit_id}/heat-map-with-the-number-of-medical-appointments-by-neighborhood")
                     .route(web::get().to(visualization_data_handler::heat_map_with_the_number_of_medical_appointments_by_neighborhood))
             )
+            .service(
+                web::resource("/user/{user_id}/unit/{unit_id}/chart/{name}")
+                    .route(web::get().to(visualization_data_handler::chart))
+            )
+            .service(
+                web::resource("/user/{user_id}/compare/{name}")
+                    .route(web::get().to(unit_comparison_handler::compare_units))
+            )
+            .service(
+                web::resource("/user/{user_id}/unit/{unit_id}/time-series/daily")
+                    .route(web::get().to(visualization_data_handler::daily_time_series))
+            )
+            .service(
+                web::resource("/user/{user_id}/unit/{unit_id}/time-series/epi-week")
+                    .route(web::get().to(visualization_data_handler::epi_week_time_series))
+            )
             .service(
                 web::resource("/available-health-units")
                     .route(web::get().to(data_upa_handler::available_health_units))
             )
+            .service(
+                web::resource("/reclassification")
+                    .route(web::post().to(reclassification_handler::start_reclassification))
+            )
+            .service(
+                web::resource("/reclassification/{job_id}")
+                    .route(web::get().to(reclassification_handler::reclassification_job))
+            )
+            .service(
+                web::resource("/classification/review/{page}")
+                    .route(web::get().to(classification_review_handler::classified_records))
+            )
+            .service(
+                web::resource("/classification/corrections")
+                    .route(web::post().to(classification_review_handler::create_correction))
+            )
+            .service(
+                web::resource("/classification/corrections/export")
+                    .route(web::get().to(classification_review_handler::export_corrections))
+            )
+            .service(
+                web::resource("/unit/{unit_id}/alerts")
+                    .route(web::get().to(surveillance_handler::alerts))
+            )
+            .service(
+                web::resource("/unit/{unit_id}/alerts/{alert_id}/acknowledge")
+                    .route(web::patch().to(surveillance_handler::acknowledge_alert))
+            )
+            .service(
+                web::resource("/unit/{unit_id}/alerts/{alert_id}/dismiss")
+                    .route(web::patch().to(surveillance_handler::dismiss_alert))
+            )
+            .service(
+                web::resource("/unit/{unit_id}/population")
+                    .route(web::get().to(population_handler::unit_population))
+                    .route(web::put().to(population_handler::set_unit_population))
+            )
+            .service(
+                web::resource("/unit/{unit_id}/neighborhood-population")
+                    .route(web::get().to(population_handler::neighborhood_population))
+                    .route(web::post().to(population_handler::import_neighborhood_population))
+            )
+            .service(
+                web::resource("/unit/{unit_id}/chart-rules")
+                    .route(web::get().to(unit_chart_rules_handler::unit_chart_rules))
+                    .route(web::put().to(unit_chart_rules_handler::set_unit_chart_rules))
+            )
+            .service(
+                web::resource("/health-units")
+                    .route(web::get().to(health_unit_handler::health_units))
+                    .route(web::post().to(health_unit_handler::create_health_unit))
+            )
+            .service(
+                web::resource("/health-units/{unit_id}")
+                    .route(web::get().to(health_unit_handler::health_unit))
+                    .route(web::put().to(health_unit_handler::update_health_unit))
+                    .route(web::delete().to(health_unit_handler::delete_health_unit))
+            )
 
     );
 }
@@ -0,0 +1,231 @@
+use serde_json::{json, Map, Value};
+
+use crate::AppError;
+
+/// Faixas originais do gráfico de distribuição de idades
+pub const AGE_BANDS_DEFAULT: &str = "padrao";
+/// Faixas quinquenais do IBGE (0 a 4, 5 a 9, ..., 100 ou mais)
+pub const AGE_BANDS_IBGE: &str = "ibge";
+/// Ciclos de vida do Ministério da Saúde (criança, adolescente, adulto e idoso)
+pub const AGE_BANDS_LIFE_CYCLE: &str = "ciclos_de_vida";
+
+pub const AGE_BAND_SCHEMES: [&str; 3] = [AGE_BANDS_DEFAULT, AGE_BANDS_IBGE, AGE_BANDS_LIFE_CYCLE];
+
+// Limite de segurança para faixas informadas pelo usuário
+const MAX_CUSTOM_LIMIT: i64 = 150;
+
+/// Faixa etária com limites inclusivos. Sem `end`, a faixa é aberta
+pub struct AgeBand {
+    pub label: String,
+    pub start: i64,
+    pub end: Option<i64>,
+}
+
+/// Conjunto ordenado de faixas etárias sem sobreposição, iniciado em zero
+pub struct AgeBands {
+    bands: Vec<AgeBand>,
+}
+
+impl AgeBands {
+    /// Faixas de 20 anos usadas desde a primeira versão do gráfico de idades
+    pub fn default_bands() -> Self {
+        let band = |label: &str, start: i64, end: Option<i64>| AgeBand { label: label.to_string(), start, end };
+
+        Self {
+            bands: vec![
+                band("0 a 19", 0, Some(19)),
+                band("20 a 39", 20, Some(39)),
+                band("40 a 59", 40, Some(59)),
+                band("60 a 79", 60, Some(79)),
+                band("80 a 100", 80, Some(100)),
+                band("+ de 100", 101, None),
+            ],
+        }
+    }
+
+    pub fn ibge() -> Self {
+        let limits: Vec<i64> = (0..=100).step_by(5).collect();
+        Self::from_limits(&limits).expect("limites do IBGE são válidos")
+    }
+
+    pub fn life_cycle() -> Self {
+        Self::from_limits(&[0, 10, 20, 60]).expect("limites dos ciclos de vida são válidos")
+    }
+
+    /// Monta as faixas a partir das idades iniciais de cada uma ("0,5,10" gera 0 a 4, 5 a 9 e 10 ou mais)
+    pub fn from_limits(limits: &[i64]) -> Result<Self, String> {
+        if limits.first() != Some(&0) {
+            return Err("the first limit must be 0".to_string());
+        }
+
+        if limits.windows(2).any(|pair| pair[0] >= pair[1]) {
+            return Err("limits must be in strictly increasing order".to_string());
+        }
+
+        if limits.iter().any(|limit| *limit > MAX_CUSTOM_LIMIT) {
+            return Err(format!("limits must not exceed {}", MAX_CUSTOM_LIMIT));
+        }
+
+        let bands = limits.iter().enumerate()
+            .map(|(i, start)| match limits.get(i + 1) {
+                Some(next) => AgeBand { label: format!("{} a {}", start, next - 1), start: *start, end: Some(next - 1) },
+                None => AgeBand { label: format!("{} ou mais", start), start: *start, end: None },
+            })
+            .collect();
+
+        Ok(Self { bands })
+    }
+
+    pub fn from_scheme(scheme: &str) -> Option<Self> {
+        match scheme {
+            AGE_BANDS_DEFAULT => Some(Self::default_bands()),
+            AGE_BANDS_IBGE => Some(Self::ibge()),
+            AGE_BANDS_LIFE_CYCLE => Some(Self::life_cycle()),
+            _ => None,
+        }
+    }
+
+    /// Faixas escolhidas na consulta. Limites personalizados têm precedência sobre o esquema nomeado
+    pub fn from_query(faixas: Option<&str>, limites: Option<&str>, default_scheme: &str) -> Result<Self, AppError> {
+        if let Some(limites) = limites.map(str::trim).filter(|l| !l.is_empty()) {
+            let limits = limites.split(',')
+                .map(|limit| limit.trim().parse::<i64>())
+                .collect::<Result<Vec<i64>, _>>()
+                .map_err(|_| AppError::BadRequest(format!(
+                    "Error: '{}' is not a valid value for 'limites'. Expected comma separated ages, e.g. 0,5,15,60",
+                    limites
+                )))?;
+
+            return Self::from_limits(&limits).map_err(|e| AppError::BadRequest(format!(
+                "Error: '{}' is not a valid value for 'limites': {}", limites, e
+            )));
+        }
+
+        let scheme = faixas.map(|f| f.trim().to_lowercase()).filter(|f| !f.is_empty())
+            .unwrap_or_else(|| default_scheme.to_string());
+
+        Self::from_scheme(&scheme).ok_or_else(|| AppError::BadRequest(format!(
+            "Error: '{}' is not a valid value for 'faixas'. Allowed values are: {}",
+            scheme, AGE_BAND_SCHEMES.join(", ")
+        )))
+    }
+
+    pub fn labels(&self) -> Vec<&str> {
+        self.bands.iter().map(|band| band.label.as_str()).collect()
+    }
+
+    /// Posição da faixa que contém a idade (idades negativas não pertencem a nenhuma)
+    pub fn index_of(&self, age: i64) -> Option<usize> {
+        self.bands.iter().position(|band| age >= band.start && band.end.map(|end| age <= end).unwrap_or(true))
+    }
+
+    pub fn label_of(&self, age: i64) -> Option<&str> {
+        self.index_of(age).map(|i| self.bands[i].label.as_str())
+    }
+}
+
+/// Agrupa contagens por idade simples ({grupo: {idade: {período: n}}}) nas faixas informadas,
+/// gerando um vetor por período na ordem das faixas ({grupo: {período: [n, ...]}})
+pub fn group_ages_into_bands(data: &Map<String, Value>, bands: &AgeBands) -> Value {
+    let band_count = bands.labels().len();
+    let mut grouped = Map::new();
+
+    for (group, ages) in data {
+        let mut periods: Map<String, Value> = Map::new();
+
+        for (age, counts) in ages.as_object().into_iter().flatten() {
+            let Some(index) = age.parse::<i64>().ok().and_then(|age| bands.index_of(age)) else {
+                continue;
+            };
+
+            for (period, count) in counts.as_object().into_iter().flatten() {
+                let values = periods.entry(period.clone()).or_insert_with(|| json!(vec![0i64; band_count]));
+                if let Some(slot) = values.get_mut(index) {
+                    // Contagens normalizadas (ex.: por habitante) chegam como decimais
+                    *slot = match (slot.as_i64(), count.as_i64()) {
+                        (Some(a), Some(b)) => json!(a + b),
+                        _ => json!(slot.as_f64().unwrap_or(0.0) + count.as_f64().unwrap_or(0.0)),
+                    };
+                }
+            }
+        }
+
+        grouped.insert(group.clone(), Value::Object(periods));
+    }
+
+    Value::Object(grouped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_build_contiguous_bands_with_the_last_open() {
+        let bands = AgeBands::from_limits(&[0, 5, 15, 60]).unwrap();
+
+        assert_eq!(bands.labels(), vec!["0 a 4", "5 a 14", "15 a 59", "60 ou mais"]);
+        assert_eq!(bands.label_of(4), Some("0 a 4"));
+        assert_eq!(bands.label_of(5), Some("5 a 14"));
+        assert_eq!(bands.label_of(120), Some("60 ou mais"));
+        assert_eq!(bands.index_of(-1), None);
+    }
+
+    #[test]
+    fn invalid_limits_are_rejected() {
+        assert!(AgeBands::from_limits(&[]).is_err());
+        assert!(AgeBands::from_limits(&[1, 10]).is_err());
+        // Limites repetidos ou fora de ordem criariam faixas sobrepostas
+        assert!(AgeBands::from_limits(&[0, 10, 10]).is_err());
+        assert!(AgeBands::from_limits(&[0, 20, 10]).is_err());
+        assert!(AgeBands::from_limits(&[0, MAX_CUSTOM_LIMIT + 1]).is_err());
+    }
+
+    #[test]
+    fn named_schemes_cover_every_age_once() {
+        for scheme in AGE_BAND_SCHEMES {
+            let bands = AgeBands::from_scheme(scheme).unwrap();
+            for age in 0..=130 {
+                let containing = bands.bands.iter()
+                    .filter(|band| age >= band.start && band.end.map(|end| age <= end).unwrap_or(true))
+                    .count();
+                assert_eq!(containing, 1, "idade {} no esquema {}", age, scheme);
+            }
+        }
+
+        assert_eq!(AgeBands::ibge().labels().len(), 21);
+        assert_eq!(AgeBands::life_cycle().labels(), vec!["0 a 9", "10 a 19", "20 a 59", "60 ou mais"]);
+    }
+
+    #[test]
+    fn query_prefers_custom_limits_over_the_scheme() {
+        let bands = AgeBands::from_query(Some("ibge"), Some(" 0, 18 "), AGE_BANDS_DEFAULT).unwrap();
+        assert_eq!(bands.labels(), vec!["0 a 17", "18 ou mais"]);
+
+        let bands = AgeBands::from_query(Some(" Ciclos_de_Vida "), None, AGE_BANDS_DEFAULT).unwrap();
+        assert_eq!(bands.labels().len(), 4);
+
+        let bands = AgeBands::from_query(None, Some(""), AGE_BANDS_IBGE).unwrap();
+        assert_eq!(bands.labels().len(), 21);
+
+        assert!(AgeBands::from_query(None, Some("0,dez"), AGE_BANDS_DEFAULT).is_err());
+        assert!(AgeBands::from_query(None, Some("0,10,5"), AGE_BANDS_DEFAULT).is_err());
+        assert!(AgeBands::from_query(Some("decadas"), None, AGE_BANDS_DEFAULT).is_err());
+    }
+
+    #[test]
+    fn ages_are_grouped_per_period_in_band_order() {
+        let data = json!({
+            "F": {"3": {"2024-1": 2, "todos": 2}, "30": {"2024-1": 1, "todos": 4}, "idade": {"todos": 9}},
+            "M": {"70": {"todos": 0.5}, "-2": {"todos": 3}},
+        });
+        let bands = AgeBands::life_cycle();
+
+        let grouped = group_ages_into_bands(data.as_object().unwrap(), &bands);
+
+        assert_eq!(grouped, json!({
+            "F": {"2024-1": [2, 0, 1, 0], "todos": [2, 0, 4, 0]},
+            "M": {"todos": [0, 0, 0, 0.5]},
+        }));
+    }
+}
@@ -0,0 +1,94 @@
+use chrono::{Duration, NaiveDate};
+
+use crate::AppError;
+
+/// Intervalo de competências (ano, mês), do início ao fim inclusive
+pub type CompetenciaRange = ((i32, u32), (i32, u32));
+
+/// Converte uma competência no formato "AAAA-M" ou "AAAA-MM" em (ano, mês)
+pub fn parse_competencia(value: &str) -> Option<(i32, u32)> {
+    let mut parts = value.trim().split('-');
+
+    let year = parts.next()?.parse::<i32>().ok()?;
+    let month = parts.next()?.parse::<u32>().ok()?;
+
+    if parts.next().is_some() || !(1..=12).contains(&month) {
+        return None;
+    }
+
+    Some((year, month))
+}
+
+/// Valida uma competência recebida pela API, exigindo o formato "AAAA-MM"
+pub fn validate_competencia(field: &str, value: &str) -> Result<(i32, u32), AppError> {
+    let well_formed = value.len() == 7 && value.as_bytes()[4] == b'-';
+
+    match parse_competencia(value) {
+        Some(parsed) if well_formed => Ok(parsed),
+        _ => Err(AppError::BadRequest(format!(
+            "Error: '{}' is not a valid value for '{}'. Expected format: YYYY-MM",
+            value, field
+        ))),
+    }
+}
+
+/// Verifica se a competência está dentro do intervalo (inclusivo)
+pub fn competencia_in_range(value: &str, start: (i32, u32), end: (i32, u32)) -> bool {
+    match parse_competencia(value) {
+        Some(parsed) => parsed >= start && parsed <= end,
+        None => false,
+    }
+}
+
+/// Ordena competências cronologicamente (valores inválidos ficam no final)
+pub fn sort_competencias(values: &mut [String]) {
+    values.sort_by_key(|value| parse_competencia(value).unwrap_or((i32::MAX, 12)));
+}
+
+/// Formatos possíveis de uma competência armazenada ("AAAA-M" e "AAAA-MM")
+pub fn competencia_variants((year, month): (i32, u32)) -> Vec<String> {
+    let mut variants = vec![format!("{}-{}", year, month), format!("{}-{:02}", year, month)];
+    variants.dedup();
+    variants
+}
+
+/// Valida o período opcional `from`/`to` ("AAAA-MM") das consultas de gráficos.
+/// Retorna `None` quando nenhum limite é informado. Limites ausentes ficam em aberto
+pub fn validate_period(from: Option<&str>, to: Option<&str>) -> Result<Option<CompetenciaRange>, AppError> {
+    if from.is_none() && to.is_none() {
+        return Ok(None);
+    }
+
+    let start = match from {
+        Some(value) => validate_competencia("from", value)?,
+        None => (i32::MIN, 1),
+    };
+    let end = match to {
+        Some(value) => validate_competencia("to", value)?,
+        None => (i32::MAX, 12),
+    };
+
+    if start > end {
+        return Err(AppError::BadRequest(
+            "Error: 'from' must be before or equal to 'to'".to_string()
+        ));
+    }
+
+    Ok(Some((start, end)))
+}
+
+/// Primeiro e último dia cobertos por uma lista de competências
+pub fn competencias_date_range(competencias: &[String]) -> Option<(NaiveDate, NaiveDate)> {
+    let parsed: Vec<(i32, u32)> = competencias.iter().filter_map(|c| parse_competencia(c)).collect();
+    let (first_year, first_month) = *parsed.iter().min()?;
+    let (last_year, last_month) = *parsed.iter().max()?;
+
+    let start = NaiveDate::from_ymd_opt(first_year, first_month, 1)?;
+    let next_month = if last_month == 12 {
+        NaiveDate::from_ymd_opt(last_year + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(last_year, last_month + 1, 1)?
+    };
+
+    Some((start, next_month - Duration::days(1)))
+}
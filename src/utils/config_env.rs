@@ -1,5 +1,9 @@
+use log::warn;
 use std::env;
 
+// Tamanho mínimo de célula quando MIN_CELL_SIZE não é informado ou é inválido
+const DEFAULT_MIN_CELL_SIZE: i64 = 5;
+
 #[derive(Clone)]  // Adiciona esta linha
 pub struct Config {
     pub database_url: String,
@@ -16,6 +20,8 @@ pub struct Config {
     pub database: String,
     pub user_pronto: String,
     pub password: String,
+    pub min_cell_size: i64,
+    pub visit_key_secret: Option<String>,
 }
 
 impl Config {
@@ -35,6 +41,15 @@ impl Config {
             database: env::var("DATABASE").expect("DATABASE NAME PRONTO must be set"),
             user_pronto: env::var("USER_PRONTO").expect("USER NAME PRONTO DB must be set"),
             password: env::var("PASSWORD").expect("PASSWORD DB PRONTO must be set"),
+            // Menor contagem exibida por bairro ou profissional; valores abaixo são suprimidos
+            min_cell_size: env::var("MIN_CELL_SIZE")
+                .ok()
+                .and_then(|v| v.trim().parse().map_err(|_| {
+                    warn!("MIN_CELL_SIZE '{}' is not an integer, using the default of {}", v, DEFAULT_MIN_CELL_SIZE);
+                }).ok())
+                .unwrap_or(DEFAULT_MIN_CELL_SIZE),
+            // Segredo da chave de visita; sem ele a importação de dados é recusada
+            visit_key_secret: env::var("VISIT_KEY_SECRET").ok().filter(|secret| !secret.trim().is_empty()),
         }
     }
 }
@@ -0,0 +1,229 @@
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use crate::domain::models::visualization_data_graph::FlowFunnelQuery;
+use crate::AppError;
+
+/// Etapas padrão do funil: acolhimento (classificação de risco) e consulta médica
+pub const DEFAULT_FUNNEL_STAGES: [&str; 2] = ["Acolhimento", "ConsultaMedica"];
+
+/// Separador das etapas de uma visita e do dia e hora de chegada nos dados armazenados
+pub const FUNNEL_KEY_SEPARATOR: char = '|';
+
+const MAX_FUNNEL_STAGES: usize = 10;
+
+/// Etapas (`ifrotabelanome`) do funil informadas na consulta, na ordem em que são percorridas
+pub fn stages_from_query(query: &FlowFunnelQuery) -> Result<Vec<String>, AppError> {
+    let Some(etapas) = query.etapas.as_deref() else {
+        return Ok(DEFAULT_FUNNEL_STAGES.iter().map(|stage| stage.to_string()).collect());
+    };
+
+    let stages: Vec<String> = etapas.split(',')
+        .map(|stage| stage.trim().to_string())
+        .filter(|stage| !stage.is_empty())
+        .collect();
+
+    if !(2..=MAX_FUNNEL_STAGES).contains(&stages.len()) {
+        return Err(AppError::BadRequest(format!(
+            "Error: 'etapas' must list between 2 and {} stages separated by commas",
+            MAX_FUNNEL_STAGES
+        )));
+    }
+
+    let mut seen = HashSet::new();
+    if let Some(repeated) = stages.iter().find(|stage| !seen.insert(stage.to_lowercase())) {
+        return Err(AppError::BadRequest(format!("Error: stage '{}' is repeated in 'etapas'", repeated)));
+    }
+
+    Ok(stages)
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn percentage(part: i64, total: i64) -> Option<f64> {
+    (total > 0).then(|| round2(part as f64 / total as f64 * 100.0))
+}
+
+// Visitas de um grupo (período, dia ou hora) e quantas chegaram a cada etapa do funil
+#[derive(Default)]
+struct FunnelTally {
+    visitas: i64,
+    reached: Vec<i64>,
+}
+
+impl FunnelTally {
+    fn add(&mut self, depth: usize, stages: usize, quantidade: i64) {
+        self.reached.resize(stages, 0);
+        self.visitas += quantidade;
+        for reached in self.reached.iter_mut().take(depth) {
+            *reached += quantidade;
+        }
+    }
+
+    // Cada etapa é comparada com a anterior; a primeira, com todas as visitas do grupo
+    fn to_value(&self, stages: &[String]) -> Value {
+        let mut previous = self.visitas;
+        let funil: Vec<Value> = stages.iter().enumerate()
+            .map(|(i, stage)| {
+                let visitas = self.reached.get(i).copied().unwrap_or(0);
+                let conversao = percentage(visitas, previous);
+                let step = json!({
+                    "etapa": stage,
+                    "visitas": visitas,
+                    "conversao": conversao,
+                    "perda": previous - visitas,
+                    "perda_percentual": conversao.map(|c| round2(100.0 - c)),
+                });
+                previous = visitas;
+                step
+            })
+            .collect();
+
+        json!({
+            "visitas": self.visitas,
+            "funil": funil,
+        })
+    }
+}
+
+// Tabelas por período: geral, por dia da semana e por hora de chegada
+#[derive(Default)]
+struct PeriodFunnel {
+    geral: FunnelTally,
+    por_dia_semana: BTreeMap<String, FunnelTally>,
+    por_hora: BTreeMap<String, FunnelTally>,
+}
+
+/// Funil das visitas entre as etapas escolhidas, a partir dos dados armazenados
+/// ({"etapa_a|etapa_b": {"dia|hora": {período: n}}}). Uma visita chega a uma etapa quando passou por
+/// ela e por todas as anteriores, de modo que a perda de cada etapa conta, por exemplo, os pacientes
+/// acolhidos que não foram atendidos pelo médico. Dia e hora são os do primeiro registro da visita
+pub fn flow_funnel(data: &Map<String, Value>, stages: &[String]) -> Value {
+    let available: BTreeSet<&str> = data.keys()
+        .flat_map(|combination| combination.split(FUNNEL_KEY_SEPARATOR))
+        .filter(|stage| !stage.is_empty())
+        .collect();
+
+    // Etapas comparadas sem diferenciar maiúsculas, exibidas com o nome armazenado
+    let stages: Vec<String> = stages.iter()
+        .map(|stage| available.iter()
+            .find(|known| known.to_lowercase() == stage.to_lowercase())
+            .map(|known| known.to_string())
+            .unwrap_or_else(|| stage.clone()))
+        .collect();
+
+    let mut periods: BTreeMap<String, PeriodFunnel> = BTreeMap::new();
+
+    for (combination, arrivals) in data {
+        let visited: HashSet<String> = combination.split(FUNNEL_KEY_SEPARATOR).map(str::to_lowercase).collect();
+        let depth = stages.iter().take_while(|stage| visited.contains(&stage.to_lowercase())).count();
+
+        for (arrival, counts) in arrivals.as_object().into_iter().flatten() {
+            let (dia, hora) = arrival.split_once(FUNNEL_KEY_SEPARATOR).unwrap_or((arrival.as_str(), ""));
+
+            for (period, count) in counts.as_object().into_iter().flatten() {
+                let quantidade = count.as_i64().unwrap_or(0);
+                if quantidade <= 0 {
+                    continue;
+                }
+
+                let funnel = periods.entry(period.clone()).or_default();
+                funnel.geral.add(depth, stages.len(), quantidade);
+
+                // Visitas sem dia ou hora de chegada entram apenas no funil geral
+                if !dia.is_empty() {
+                    funnel.por_dia_semana.entry(dia.to_string()).or_default().add(depth, stages.len(), quantidade);
+                }
+                if !hora.is_empty() {
+                    funnel.por_hora.entry(hora.to_string()).or_default().add(depth, stages.len(), quantidade);
+                }
+            }
+        }
+    }
+
+    let periods: Map<String, Value> = periods.into_iter()
+        .map(|(period, funnel)| {
+            let mut value = funnel.geral.to_value(&stages);
+            value["por_dia_semana"] = json!(funnel.por_dia_semana.iter()
+                .map(|(dia, tally)| (dia.clone(), tally.to_value(&stages)))
+                .collect::<Map<String, Value>>());
+            value["por_hora"] = json!(funnel.por_hora.iter()
+                .map(|(hora, tally)| (hora.clone(), tally.to_value(&stages)))
+                .collect::<Map<String, Value>>());
+            (period, value)
+        })
+        .collect();
+
+    json!({
+        "etapas": stages,
+        "etapas_disponiveis": available,
+        "periodos": periods,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stages(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    // Visitas armazenadas por combinação de fluxos, chegada e período
+    fn stored() -> Map<String, Value> {
+        json!({
+            "Acolhimento|ConsultaMedica": { "segunda-feira|07": { "2024-3": 8 } },
+            "Acolhimento": { "segunda-feira|08": { "2024-3": 2 } },
+            "Procedimento": { "|": { "2024-3": 1 } },
+        })
+        .as_object()
+        .cloned()
+        .unwrap()
+    }
+
+    #[test]
+    fn visits_reach_a_stage_only_after_all_previous_ones() {
+        let funnel = flow_funnel(&stored(), &stages(&["Acolhimento", "ConsultaMedica"]));
+        let period = &funnel["periodos"]["2024-3"];
+
+        assert_eq!(period["visitas"], 11);
+        assert_eq!(period["funil"][0], json!({
+            "etapa": "Acolhimento", "visitas": 10, "conversao": 90.91, "perda": 1, "perda_percentual": 9.09,
+        }));
+        assert_eq!(period["funil"][1], json!({
+            "etapa": "ConsultaMedica", "visitas": 8, "conversao": 80.0, "perda": 2, "perda_percentual": 20.0,
+        }));
+    }
+
+    #[test]
+    fn visits_missing_a_stage_stop_there() {
+        // Consultas de visitas que não passaram pelo procedimento não chegam à segunda etapa
+        let funnel = flow_funnel(&stored(), &stages(&["Procedimento", "ConsultaMedica"]));
+        let funil = &funnel["periodos"]["2024-3"]["funil"];
+
+        assert_eq!(funil[0]["visitas"], 1);
+        assert_eq!(funil[1]["visitas"], 0);
+        assert_eq!(funil[1]["perda"], 1);
+    }
+
+    #[test]
+    fn stages_match_stored_names_ignoring_case() {
+        let funnel = flow_funnel(&stored(), &stages(&["acolhimento", "CONSULTAMEDICA"]));
+
+        assert_eq!(funnel["etapas"], json!(["Acolhimento", "ConsultaMedica"]));
+        assert_eq!(funnel["etapas_disponiveis"], json!(["Acolhimento", "ConsultaMedica", "Procedimento"]));
+        assert_eq!(funnel["periodos"]["2024-3"]["funil"][1]["visitas"], 8);
+    }
+
+    #[test]
+    fn arrivals_without_day_or_hour_stay_in_the_overall_funnel() {
+        let funnel = flow_funnel(&stored(), &stages(&["Acolhimento", "ConsultaMedica"]));
+        let period = &funnel["periodos"]["2024-3"];
+
+        assert_eq!(period["por_dia_semana"]["segunda-feira"]["visitas"], 10);
+        assert_eq!(period["por_hora"]["07"]["funil"][1]["visitas"], 8);
+        assert_eq!(period["por_hora"].as_object().unwrap().len(), 2);
+    }
+}
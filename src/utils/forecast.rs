@@ -0,0 +1,269 @@
+use chrono::{Duration, NaiveDate};
+
+use crate::domain::models::forecast::{ForecastParameters, ForecastPoint, ForecastSeries};
+
+pub const FORECAST_HORIZON_DAYS: usize = 28;
+
+const WEEKLY_PERIOD: usize = 7;
+const YEARLY_PERIOD: usize = 365;
+
+// Histórico mínimo para ajustar o modelo
+const MIN_HISTORY_DAYS: usize = 4 * WEEKLY_PERIOD;
+
+// Tendência amortecida, evitando extrapolações lineares em quatro semanas
+const DAMPING: f64 = 0.98;
+
+const Z_80: f64 = 1.2816;
+const Z_95: f64 = 1.96;
+
+// Valores testados na escolha dos parâmetros de suavização
+const ALPHA_GRID: [f64; 5] = [0.05, 0.1, 0.2, 0.3, 0.5];
+const BETA_GRID: [f64; 3] = [0.0, 0.01, 0.05];
+const GAMMA_GRID: [f64; 4] = [0.05, 0.1, 0.2, 0.3];
+const DELTA_GRID: [f64; 3] = [0.05, 0.1, 0.2];
+
+#[derive(Clone, Copy)]
+struct Smoothing {
+    alpha: f64,
+    beta: f64,
+    gamma: f64,
+    delta: Option<f64>,
+}
+
+// Estado do modelo. As sazonalidades são indexadas pelo dia da série módulo o período
+#[derive(Clone)]
+struct State {
+    level: f64,
+    trend: f64,
+    weekly: Vec<f64>,
+    yearly: Option<Vec<f64>>,
+}
+
+struct Fit {
+    smoothing: Smoothing,
+    state: State,
+    sse: f64,
+    sae: f64,
+    errors: usize,
+}
+
+fn mean(values: &[f64]) -> f64 {
+    values.iter().sum::<f64>() / values.len() as f64
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+// Média móvel centrada de 7 dias, com janela reduzida nas extremidades
+fn weekly_moving_average(values: &[f64]) -> Vec<f64> {
+    (0..values.len())
+        .map(|t| {
+            let start = t.saturating_sub(WEEKLY_PERIOD / 2);
+            let end = (t + WEEKLY_PERIOD / 2 + 1).min(values.len());
+            mean(&values[start..end])
+        })
+        .collect()
+}
+
+// Estado inicial a partir do primeiro ano (ou das primeiras quatro semanas, sem sazonalidade anual)
+fn initial_state(values: &[f64], yearly: bool) -> State {
+    let window = if yearly { YEARLY_PERIOD } else { MIN_HISTORY_DAYS };
+    let first = &values[..window];
+
+    let trend = if yearly {
+        (mean(&values[YEARLY_PERIOD..2 * YEARLY_PERIOD]) - mean(first)) / YEARLY_PERIOD as f64
+    } else {
+        (mean(&first[window - WEEKLY_PERIOD..]) - mean(&first[..WEEKLY_PERIOD])) / (window - WEEKLY_PERIOD) as f64
+    };
+
+    // Desvios de cada dia em relação à média móvel semanal formam a sazonalidade semanal
+    let moving_average = weekly_moving_average(first);
+    let mut weekly = vec![0.0; WEEKLY_PERIOD];
+    for (i, season) in weekly.iter_mut().enumerate() {
+        let deviations: Vec<f64> = (i..window).step_by(WEEKLY_PERIOD)
+            .map(|t| first[t] - moving_average[t])
+            .collect();
+        *season = mean(&deviations);
+    }
+
+    let yearly = yearly.then(|| {
+        let level = mean(&moving_average);
+        moving_average.iter().map(|ma| ma - level).collect()
+    });
+
+    State {
+        level: mean(first),
+        trend,
+        weekly,
+        yearly,
+    }
+}
+
+// Holt-Winters aditivo com sazonalidade dupla (Taylor, 2003) e tendência amortecida
+fn fit(values: &[f64], initial: &State, smoothing: Smoothing) -> Fit {
+    let mut state = initial.clone();
+    let (mut sse, mut sae, mut errors) = (0.0, 0.0, 0);
+
+    for (t, y) in values.iter().enumerate() {
+        let weekly = state.weekly[t % WEEKLY_PERIOD];
+        let yearly = state.yearly.as_ref().map(|s| s[t % YEARLY_PERIOD]).unwrap_or(0.0);
+
+        let damped_trend = DAMPING * state.trend;
+        let error = y - (state.level + damped_trend + weekly + yearly);
+
+        // A primeira semana apenas ajusta o estado inicial
+        if t >= WEEKLY_PERIOD {
+            sse += error * error;
+            sae += error.abs();
+            errors += 1;
+        }
+
+        let level = smoothing.alpha * (y - weekly - yearly) + (1.0 - smoothing.alpha) * (state.level + damped_trend);
+        state.trend = smoothing.beta * (level - state.level) + (1.0 - smoothing.beta) * damped_trend;
+        state.weekly[t % WEEKLY_PERIOD] = smoothing.gamma * (y - level - yearly) + (1.0 - smoothing.gamma) * weekly;
+
+        if let (Some(season), Some(delta)) = (state.yearly.as_mut(), smoothing.delta) {
+            season[t % YEARLY_PERIOD] = delta * (y - level - weekly) + (1.0 - delta) * yearly;
+        }
+
+        state.level = level;
+    }
+
+    Fit { smoothing, state, sse, sae, errors }
+}
+
+// Escolhe os parâmetros com menor erro quadrático de previsão um passo à frente
+fn best_fit(values: &[f64], yearly: bool) -> Fit {
+    let initial = initial_state(values, yearly);
+    let deltas: Vec<Option<f64>> = if yearly { DELTA_GRID.iter().map(|d| Some(*d)).collect() } else { vec![None] };
+
+    let mut best: Option<Fit> = None;
+    for alpha in ALPHA_GRID {
+        for beta in BETA_GRID {
+            for gamma in GAMMA_GRID {
+                for delta in &deltas {
+                    let candidate = fit(values, &initial, Smoothing { alpha, beta, gamma, delta: *delta });
+                    if best.as_ref().map(|b| candidate.sse < b.sse).unwrap_or(true) {
+                        best = Some(candidate);
+                    }
+                }
+            }
+        }
+    }
+
+    best.expect("a grade de parâmetros nunca é vazia")
+}
+
+/// Ajusta o modelo às contagens diárias iniciadas em `start` (dias sem atendimento com zero)
+/// e prevê as próximas quatro semanas. Retorna `None` com menos de quatro semanas de histórico
+pub fn forecast_daily_counts(start: NaiveDate, values: &[f64]) -> Option<ForecastSeries> {
+    if values.len() < MIN_HISTORY_DAYS {
+        return None;
+    }
+
+    // A sazonalidade anual só é estimada com ao menos dois anos de histórico
+    let yearly = values.len() >= 2 * YEARLY_PERIOD;
+    let fit = best_fit(values, yearly);
+    let Smoothing { alpha, beta, gamma, delta } = fit.smoothing;
+
+    let n = values.len();
+    let sigma = (fit.sse / fit.errors.max(1) as f64).sqrt();
+    let last_day = start + Duration::days(n as i64 - 1);
+
+    let mut previsao = Vec::with_capacity(FORECAST_HORIZON_DAYS);
+    let (mut damping_sum, mut variance_factor): (f64, f64) = (0.0, 1.0);
+
+    for h in 1..=FORECAST_HORIZON_DAYS {
+        let t = n - 1 + h;
+        damping_sum += DAMPING.powi(h as i32);
+
+        let yearly_season = fit.state.yearly.as_ref().map(|s| s[t % YEARLY_PERIOD]).unwrap_or(0.0);
+        let point = fit.state.level + damping_sum * fit.state.trend + fit.state.weekly[t % WEEKLY_PERIOD] + yearly_season;
+
+        // Variância aproximada do erro h passos à frente
+        let spread = sigma * variance_factor.sqrt();
+        let seasonal_term = if h % WEEKLY_PERIOD == 0 { gamma } else { 0.0 };
+        variance_factor += (alpha * (1.0 + beta * damping_sum) + seasonal_term).powi(2);
+
+        previsao.push(ForecastPoint {
+            data: last_day + Duration::days(h as i64),
+            previsto: round2(point.max(0.0)),
+            limite_inferior_80: round2((point - Z_80 * spread).max(0.0)),
+            limite_superior_80: round2((point + Z_80 * spread).max(0.0)),
+            limite_inferior_95: round2((point - Z_95 * spread).max(0.0)),
+            limite_superior_95: round2((point + Z_95 * spread).max(0.0)),
+        });
+    }
+
+    Some(ForecastSeries {
+        modelo: if yearly { "holt_winters_sazonalidade_dupla" } else { "holt_winters_sazonalidade_semanal" }.to_string(),
+        sazonalidades: if yearly { vec![WEEKLY_PERIOD, YEARLY_PERIOD] } else { vec![WEEKLY_PERIOD] },
+        parametros: ForecastParameters { alpha, beta, gamma, delta, phi: DAMPING },
+        historico_inicio: start,
+        historico_fim: last_day,
+        erro_medio_absoluto: round2(fit.sae / fit.errors.max(1) as f64),
+        previsao,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WEEKLY_PATTERN: [f64; 7] = [40.0, 55.0, 50.0, 48.0, 52.0, 30.0, 25.0];
+
+    fn weekly_series(weeks: usize) -> Vec<f64> {
+        (0..weeks * WEEKLY_PERIOD).map(|t| WEEKLY_PATTERN[t % WEEKLY_PERIOD]).collect()
+    }
+
+    #[test]
+    fn grid_search_keeps_the_smallest_squared_error() {
+        let values: Vec<f64> = weekly_series(12).iter().enumerate()
+            .map(|(t, v)| v + (t as f64 * 0.7).sin() * 4.0)
+            .collect();
+
+        let best = best_fit(&values, false);
+        let initial = initial_state(&values, false);
+
+        for alpha in ALPHA_GRID {
+            for beta in BETA_GRID {
+                for gamma in GAMMA_GRID {
+                    let candidate = fit(&values, &initial, Smoothing { alpha, beta, gamma, delta: None });
+                    assert!(best.sse <= candidate.sse);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn forecast_requires_four_weeks_of_history() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+
+        assert!(forecast_daily_counts(start, &weekly_series(3)).is_none());
+        assert!(forecast_daily_counts(start, &weekly_series(4)).is_some());
+    }
+
+    #[test]
+    fn forecast_repeats_a_stable_weekly_pattern() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 1).unwrap();
+        let values = weekly_series(10);
+        let series = forecast_daily_counts(start, &values).unwrap();
+
+        assert_eq!(series.modelo, "holt_winters_sazonalidade_semanal");
+        assert_eq!(series.historico_fim, start + Duration::days(values.len() as i64 - 1));
+        assert_eq!(series.previsao.len(), FORECAST_HORIZON_DAYS);
+
+        for (h, point) in series.previsao.iter().enumerate() {
+            assert_eq!(point.data, series.historico_fim + Duration::days(h as i64 + 1));
+
+            let expected = WEEKLY_PATTERN[(values.len() + h) % WEEKLY_PERIOD];
+            assert!((point.previsto - expected).abs() < 1.0, "dia {}: {} != {}", h, point.previsto, expected);
+
+            assert!(point.limite_inferior_95 <= point.limite_inferior_80);
+            assert!(point.limite_inferior_80 <= point.previsto);
+            assert!(point.previsto <= point.limite_superior_80);
+            assert!(point.limite_superior_80 <= point.limite_superior_95);
+        }
+    }
+}
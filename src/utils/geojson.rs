@@ -0,0 +1,118 @@
+use serde_json::{json, Map, Value};
+
+use crate::AppError;
+
+pub const FORMAT_JSON: &str = "json";
+pub const FORMAT_GEOJSON: &str = "geojson";
+pub const GEOJSON_CONTENT_TYPE: &str = "application/geo+json";
+
+/// Formato de saída dos mapas de calor. O parâmetro `format` tem precedência sobre o cabeçalho `Accept`
+pub fn wants_geojson(format: Option<&str>, accept: Option<&str>) -> Result<bool, AppError> {
+    match format.map(|f| f.trim().to_lowercase()).as_deref() {
+        Some(FORMAT_GEOJSON) => Ok(true),
+        Some(FORMAT_JSON) => Ok(false),
+        Some(other) => Err(AppError::BadRequest(format!(
+            "Error: '{}' is not a valid value for 'format'. Expected: {} or {}",
+            other, FORMAT_JSON, FORMAT_GEOJSON
+        ))),
+        None => Ok(accept.map(|a| a.to_lowercase().contains(GEOJSON_CONTENT_TYPE)).unwrap_or(false)),
+    }
+}
+
+// Percorre os níveis do mapa até os bairros (objetos com latitude e longitude), guardando as chaves do caminho
+fn collect_features(value: &Value, path: &mut Vec<String>, properties: &[&str], features: &mut Vec<Value>) {
+    let Some(map) = value.as_object() else {
+        return;
+    };
+
+    if let (Some(latitude), Some(longitude)) = (map.get("latitude"), map.get("longitude")) {
+        let mut feature_properties: Map<String, Value> = properties.iter()
+            .zip(path.iter())
+            .map(|(name, key)| (name.to_string(), json!(key)))
+            .collect();
+
+        for (key, field) in map {
+            if key != "latitude" && key != "longitude" {
+                feature_properties.insert(key.clone(), field.clone());
+            }
+        }
+
+        // GeoJSON usa a ordem longitude, latitude (RFC 7946)
+        features.push(json!({
+            "type": "Feature",
+            "geometry": { "type": "Point", "coordinates": [longitude, latitude] },
+            "properties": feature_properties,
+        }));
+        return;
+    }
+
+    for (key, child) in map {
+        path.push(key.clone());
+        collect_features(child, path, properties, features);
+        path.pop();
+    }
+}
+
+/// Converte um mapa de calor ({nível: ... {bairro: {latitude, longitude, quantidade}}}) em uma
+/// FeatureCollection de pontos. `properties` nomeia as chaves de cada nível até o bairro
+pub fn feature_collection(data: &Value, properties: &[&str]) -> Value {
+    let mut features = Vec::new();
+    collect_features(data, &mut Vec::new(), properties, &mut features);
+
+    json!({
+        "type": "FeatureCollection",
+        "features": features,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_parameter_takes_precedence_over_accept() {
+        assert!(wants_geojson(Some("GeoJSON"), None).unwrap());
+        assert!(!wants_geojson(Some("json"), Some(GEOJSON_CONTENT_TYPE)).unwrap());
+        assert!(wants_geojson(None, Some("application/json, Application/Geo+JSON")).unwrap());
+        assert!(!wants_geojson(None, Some("application/json")).unwrap());
+        assert!(!wants_geojson(None, None).unwrap());
+        assert!(wants_geojson(Some("kml"), None).is_err());
+    }
+
+    #[test]
+    fn heat_map_becomes_a_collection_of_points() {
+        let data = json!({
+            "dengue": {
+                "2024-1": {
+                    "CENTRO": {"latitude": -8.76, "longitude": -63.9, "quantidade": 12},
+                    "SUL": {"latitude": -8.8, "longitude": -63.85, "quantidade": 7, "taxa": 1.5},
+                },
+            },
+        });
+
+        let collection = feature_collection(&data, &["queixa", "periodo", "bairro"]);
+
+        assert_eq!(collection, json!({
+            "type": "FeatureCollection",
+            "features": [
+                {
+                    "type": "Feature",
+                    "geometry": {"type": "Point", "coordinates": [-63.9, -8.76]},
+                    "properties": {"queixa": "dengue", "periodo": "2024-1", "bairro": "CENTRO", "quantidade": 12},
+                },
+                {
+                    "type": "Feature",
+                    "geometry": {"type": "Point", "coordinates": [-63.85, -8.8]},
+                    "properties": {"queixa": "dengue", "periodo": "2024-1", "bairro": "SUL", "quantidade": 7, "taxa": 1.5},
+                },
+            ],
+        }));
+    }
+
+    #[test]
+    fn levels_without_coordinates_produce_no_features() {
+        let data = json!({"todos": {"CENTRO": {"quantidade": 3}}, "total": 3});
+
+        assert_eq!(feature_collection(&data, &["periodo", "bairro"]), json!({"type": "FeatureCollection", "features": []}));
+    }
+}
@@ -3,6 +3,33 @@ use serde_json::{Value, json};
 use std::collections::HashMap;
 use std::error::Error;
 
+use crate::domain::models::data_upa::GroupedCount;
+use crate::utils::age_bands::AgeBands;
+use crate::utils::flow_funnel::FUNNEL_KEY_SEPARATOR;
+use crate::utils::hotspots::point_key;
+
+// Bairro -> (latitude, longitude, quantidade)
+type NeighborhoodPoints = HashMap<String, (f64, f64, i64)>;
+
+// Dias da semana como armazenados em ifrodiasemana e como exibidos nos gráficos
+const DAY_MAPPINGS: [(&str, &str); 7] = [
+    ("Monday", "segunda-feira"),
+    ("Tuesday", "terça-feira"),
+    ("Wednesday", "quarta-feira"),
+    ("Thursday", "quinta-feira"),
+    ("Friday", "sexta-feira"),
+    ("Saturday", "sábado"),
+    ("Sunday", "domingo")
+];
+
+// Sexos exibidos na pirâmide etária
+pub const SEX_MALE: &str = "masculino";
+pub const SEX_FEMALE: &str = "feminino";
+pub const SEX_NOT_INFORMED: &str = "nao_informado";
+
+// Chave da matriz de dia da semana e hora que reúne todas as categorias profissionais
+pub const ALL_CATEGORIES_KEY: &str = "todas";
+
 
 pub struct DataProcessingForGraphPlotting;
 
@@ -38,91 +65,22 @@ impl DataProcessingForGraphPlotting {
     }
 
     // Implementação das funções de processamento
-    pub async fn create_dict_to_number_of_appointments_per_month(&self, df: &DataFrame) -> Result<Value, Box<dyn Error + Send + Sync>> {
-        // Imprimir colunas originais
-        println!("Colunas originais: {:?}", df.get_column_names());
-        
-        // Em vez de usar .lazy() e groupby, vamos fazer contagem manual
-        let comp_values = df.column("ifrocompetencia")?
-            .str()?
-            .into_iter()
-            .filter_map(|opt_s| opt_s.map(String::from))
-            .collect::<Vec<String>>();
-        
-        // Fazer contagem
-        let mut counts: HashMap<String, i64> = HashMap::new();
-        for comp in comp_values {
-            *counts.entry(comp).or_insert(0) += 1;
-        }
-        
-        // Converter para o formato final
-        let mut result = HashMap::new();
-        for (category, count) in counts {
-            result.insert(category, json!(count));
+    pub fn create_dict_to_number_of_appointments_per_month(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // As contagens já chegam agrupadas por competência
+        let mut result: HashMap<String, i64> = HashMap::new();
+        for count in counts {
+            *result.entry(count.competencia.clone()).or_insert(0) += count.quantidade;
         }
         
         Ok(json!(result))
     }
 
    
-    pub async fn create_dict_to_number_of_appointments_per_flow(&self, df: &DataFrame) -> Result<Value, Box<dyn Error + Send + Sync>> {
-        // Imprimir colunas originais
-        println!("Colunas originais: {:?}", df.get_column_names());
-        
-        // Extrair dados das colunas necessárias
-        let competencias = df.column("ifrocompetencia")?
-            .str()?
-            .into_iter()
-            .filter_map(|opt_s| opt_s.map(String::from))
-            .collect::<Vec<String>>();
-        
-        let tabelas = df.column("ifrotabelanome")?
-            .str()?
-            .into_iter()
-            .filter_map(|opt_s| opt_s.map(String::from))
-            .collect::<Vec<String>>();
-        
-        // Garantir que os vetores têm o mesmo tamanho
-        if competencias.len() != tabelas.len() {
-            return Err(Box::<dyn Error + Send + Sync>::from("Tamanhos de colunas incompatíveis"));
-        }
-        
-        // Fazer contagem manual com agrupamento duplo
-        let mut counts: HashMap<String, HashMap<String, i64>> = HashMap::new();
+    pub fn create_dict_to_number_of_appointments_per_flow(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        let rows = counts.iter()
+            .filter_map(|count| count.keys.first().map(|tabela| (tabela.clone(), count)));
         
-        for i in 0..competencias.len() {
-            let categoria = &tabelas[i];
-            let competencia = &competencias[i];
-            
-            // Inicializar categoria se ainda não existe
-            if !counts.contains_key(categoria) {
-                counts.insert(categoria.clone(), HashMap::new());
-            }
-            
-            // Incrementar contagem para esta competência
-            let comp_counts = counts.get_mut(categoria).unwrap();
-            *comp_counts.entry(competencia.clone()).or_insert(0) += 1;
-        }
-        
-        // Converter para o formato final
-        let mut organized_data = HashMap::new();
-        
-        for (categoria, comp_counts) in counts {
-            let mut category_data = HashMap::new();
-            
-            // Calcular total
-            let total: i64 = comp_counts.values().sum();
-            category_data.insert("todos".to_string(), json!(total));
-            
-            // Adicionar contagens por competência
-            for (competencia, count) in comp_counts {
-                category_data.insert(competencia, json!(count));
-            }
-            
-            organized_data.insert(categoria, category_data);
-        }
-        
-        Ok(json!(organized_data))
+        Ok(organize_counts_by_key(rows, &[]))
     }
 
 
@@ -162,19 +120,12 @@ impl DataProcessingForGraphPlotting {
         println!("Amostra de competências: {:?}", &competencias[0..5.min(competencias.len())]);
         println!("Amostra de idades: {:?}", &idades[0..5.min(idades.len())]);
         
-        // Definir faixas etárias
-        let age_groups = vec![
-            ("0 a 19", 0..20),
-            ("20 a 39", 20..40),
-            ("40 a 59", 40..60),
-            ("60 a 79", 60..80),
-            ("80 a 100", 80..101),
-            ("+ de 100", 101..i32::MAX)
-        ];
+        // Faixas etárias padrão do gráfico
+        let age_groups = AgeBands::default_bands();
         
         // Contadores para cada faixa etária
         let mut age_data: HashMap<&str, HashMap<String, i64>> = HashMap::new();
-        for (group, _) in &age_groups {
+        for group in age_groups.labels() {
             age_data.insert(group, HashMap::new());
         }
         
@@ -230,17 +181,12 @@ impl DataProcessingForGraphPlotting {
             }
             
             // Se idade válida, classifica na faixa etária correspondente
-            if idade >= 0 {
-                for (group, range) in &age_groups {
-                    if range.contains(&idade) {
-                        *age_data
-                            .get_mut(group)
-                            .unwrap()
-                            .entry(competencia.clone())
-                            .or_insert(0) += 1;
-                        break;
-                    }
-                }
+            if let Some(group) = age_groups.label_of(idade as i64) {
+                *age_data
+                    .get_mut(group)
+                    .unwrap()
+                    .entry(competencia.clone())
+                    .or_insert(0) += 1;
             }
         }
         
@@ -248,7 +194,7 @@ impl DataProcessingForGraphPlotting {
         
         // Construir o resultado final
         let mut result = HashMap::new();
-        for (group, _) in &age_groups {
+        for group in age_groups.labels() {
             let counts = &age_data[group];
             let total: i64 = counts.values().sum();
             
@@ -270,107 +216,23 @@ impl DataProcessingForGraphPlotting {
 
 
 
-    pub async fn create_dict_to_number_of_calls_per_day_of_the_week(&self, df: &DataFrame) -> Result<Value, Box<dyn Error + Send + Sync>> {
-        // Mapeamento de dias da semana
-        let day_mappings = [
-            ("Monday", "segunda-feira"),
-            ("Tuesday", "terça-feira"),
-            ("Wednesday", "quarta-feira"),
-            ("Thursday", "quinta-feira"),
-            ("Friday", "sexta-feira"),
-            ("Saturday", "sábado"),
-            ("Sunday", "domingo")
-        ];
-        
-        // Cria Series com mapeamento de dias
-        let mut day_mapping_expr: Expr = when(col("ifrodiasemana").eq(lit("")))
-            .then(lit(""))
-            .otherwise(lit("")); 
-        
-        for (en_day, pt_day) in day_mappings.iter() {
-            day_mapping_expr = when(col("ifrodiasemana").eq(lit(*en_day)))
-                .then(lit(*pt_day))
-                .otherwise(day_mapping_expr); 
-        }
-        
-        // Aplica mapeamento
-        let df_with_pt_days = df.clone().lazy()
-            .with_column(day_mapping_expr.alias("dia_semana_pt"))
-            .collect()?;
-        
-        // Cria dicionário organizado para cada dia da semana
-        let mut organized_data = HashMap::new();
-        
-        for (_, pt_day) in day_mappings.iter() {
-            // Filtra por este dia da semana
-            let pt_day_owned = (*pt_day).to_string();
-            
-            let df_day = df_with_pt_days.clone().lazy()
-                .filter(col("dia_semana_pt").eq(lit(pt_day_owned.clone())))
-                .collect()?;
-            
-            // Extrai competências
-            let competencias = df_day.column("ifrocompetencia")?
-                .str()?
-                .into_iter()
-                .filter_map(|opt_s| opt_s.map(String::from))
-                .collect::<Vec<String>>();
-            
-            // Faz a contagem manual
-            let mut comp_counts: HashMap<String, i64> = HashMap::new();
-            for comp in competencias {
-                *comp_counts.entry(comp).or_insert(0) += 1;
-            }
-            
-            let mut day_data = HashMap::new();
-            day_data.insert("todos".to_string(), json!(df_day.height() as i64));
-            
-            // Adiciona contagens por competência
-            for (competencia, count) in comp_counts {
-                day_data.insert(competencia, json!(count));
-            }
-            
-            organized_data.insert(pt_day_owned, day_data);
-        }
-        
-        Ok(json!(organized_data))
+    pub fn create_dict_to_number_of_calls_per_day_of_the_week(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Dias fora do mapeamento são ignorados
+        let rows = counts.iter().filter_map(|count| {
+            let en_day = count.keys.first()?;
+            DAY_MAPPINGS.iter()
+                .find(|(en, _)| en == en_day)
+                .map(|(_, pt_day)| (pt_day.to_string(), count))
+        });
+        
+        let pt_days: Vec<&str> = DAY_MAPPINGS.iter().map(|(_, pt_day)| *pt_day).collect();
+        
+        Ok(organize_counts_by_key(rows, &pt_days))
     }
 
 
 
-    pub async fn create_dict_to_distribution_of_services_by_hour_group(&self, df: &DataFrame) -> Result<Value, Box<dyn Error + Send + Sync>> {
-        println!("Processando distribuição de serviços por grupo de hora");
-        
-        // Filtrar para médicos clínicos manualmente
-        let mut medicos_indices = Vec::new();
-        for i in 0..df.height() {
-            let especialidade = df.column("ifroprofissionalcbods")?.str()?.get(i).unwrap_or("");
-            if especialidade == "MEDICO CLINICO" {
-                medicos_indices.push(i);
-            }
-        }
-        
-        // Extrair horas e competências apenas dos médicos clínicos
-        let mut horas = Vec::new();
-        let mut competencias = Vec::new();
-        
-        for &idx in &medicos_indices {
-            let time_str = df.column("ifrohoraatendimento")?.str()?.get(idx).unwrap_or("");
-            let competencia = df.column("ifrocompetencia")?.str()?.get(idx).unwrap_or("").to_string();
-            
-            let hour = if time_str.len() >= 2 {
-                // Tentar extrair as primeiras 2 posições como hora
-                time_str[0..2].parse::<i32>().unwrap_or(-1)
-            } else {
-                -1 // Valor inválido
-            };
-            
-            if hour >= 0 { // Ignorar valores inválidos
-                horas.push(hour);
-                competencias.push(competencia);
-            }
-        }
-        
+    pub fn create_dict_to_distribution_of_services_by_hour_group(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
         // Definir os grupos de horas
         let hour_groups = [
             "00h-02h", "02h-04h", "04h-06h", "06h-08h", "08h-10h", 
@@ -378,222 +240,177 @@ impl DataProcessingForGraphPlotting {
             "20h-22h", "22h-24h"
         ];
         
-        // Preparar contadores para cada grupo de hora
-        let mut hour_group_data: HashMap<String, HashMap<String, i64>> = HashMap::new();
-        for &group in &hour_groups {
-            hour_group_data.insert(group.to_string(), HashMap::new());
-        }
+        // Cada grupo cobre duas horas. Horas a partir de 22 ficam no último grupo
+        let rows = counts.iter().filter_map(|count| {
+            let hour = count.keys.first()?.parse::<usize>().ok()?;
+            let group = hour_groups[(hour / 2).min(hour_groups.len() - 1)];
+            Some((group.to_string(), count))
+        });
         
-        // Classificar cada hora em seu grupo e contar
-        for i in 0..horas.len() {
-            let hour = horas[i];
-            let competencia = &competencias[i];
-            
-            let group = if hour >= 0 && hour < 2 {
-                "00h-02h"
-            } else if hour >= 2 && hour < 4 {
-                "02h-04h"
-            } else if hour >= 4 && hour < 6 {
-                "04h-06h"
-            } else if hour >= 6 && hour < 8 {
-                "06h-08h"
-            } else if hour >= 8 && hour < 10 {
-                "08h-10h"
-            } else if hour >= 10 && hour < 12 {
-                "10h-12h"
-            } else if hour >= 12 && hour < 14 {
-                "12h-14h"
-            } else if hour >= 14 && hour < 16 {
-                "14h-16h"
-            } else if hour >= 16 && hour < 18 {
-                "16h-18h"
-            } else if hour >= 18 && hour < 20 {
-                "18h-20h"
-            } else if hour >= 20 && hour < 22 {
-                "20h-22h"
-            } else {
-                "22h-24h"
+        Ok(organize_counts_by_key(rows, &hour_groups))
+    }
+
+
+
+    pub fn create_dict_to_weekday_hour_matrix(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Matriz 7x24 (segunda a domingo x 0h a 23h) por categoria e competência, além do total ("todos")
+        let mut matrices: HashMap<String, HashMap<String, [[i64; 24]; 7]>> = HashMap::new();
+
+        for count in counts {
+            let (en_day, hour, categoria) = match count.keys.as_slice() {
+                [en_day, hour, categoria] => (en_day, hour, categoria),
+                _ => continue,
             };
-            
-            // Incrementar contagem para esta competência e grupo de hora
-            let comp_counts = hour_group_data.get_mut(group).unwrap();
-            *comp_counts.entry(competencia.clone()).or_insert(0) += 1;
-        }
-        
-        // Criar o dicionário organizado final
-        let mut organized_data = HashMap::new();
-        
-        for group in hour_groups {
-            let comp_counts = hour_group_data.get(group).unwrap();
-            
-            // Calcular total para este grupo de hora
-            let total: i64 = comp_counts.values().sum();
-            
-            let mut hour_data = HashMap::new();
-            hour_data.insert("todos".to_string(), json!(total));
-            
-            // Adicionar contagens por competência
-            for (competencia, count) in comp_counts {
-                hour_data.insert(competencia.clone(), json!(count));
+
+            // Dias fora do mapeamento e horas inválidas são ignorados
+            let day = match DAY_MAPPINGS.iter().position(|(en, _)| en == en_day) {
+                Some(day) => day,
+                None => continue,
+            };
+            let hour = match hour.parse::<usize>() {
+                Ok(hour) if hour < 24 => hour,
+                _ => continue,
+            };
+
+            let mut keys = vec![ALL_CATEGORIES_KEY];
+            if !categoria.is_empty() {
+                keys.push(categoria);
+            }
+
+            for key in keys {
+                let by_competencia = matrices.entry(key.to_string()).or_default();
+                for period in [count.competencia.as_str(), "todos"] {
+                    by_competencia.entry(period.to_string()).or_insert([[0; 24]; 7])[day][hour] += count.quantidade;
+                }
             }
-            
-            organized_data.insert(group.to_string(), hour_data);
         }
-        
-        Ok(json!(organized_data))
+
+        Ok(json!(matrices))
     }
 
 
+    pub fn create_dict_to_age_pyramid(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Contagens por sexo e idade simples; as faixas etárias são aplicadas na consulta
+        let rows = counts.iter().filter_map(|count| match count.keys.as_slice() {
+            [sexo, idade] => Some((sex_label(sexo).to_string(), idade.clone(), count)),
+            _ => None,
+        });
 
-    pub async fn create_dict_to_number_of_visits_per_nurse(&self, df: &DataFrame, df_non_nurse: &DataFrame) -> Result<Value, Box<dyn Error + Send + Sync>> {
-        // Obter lista de enfermeiras a excluir
-        let non_nurse_names: Vec<String> = df_non_nurse.column("ifroprofissionalnome")?
-            .str()?
-            .into_iter()
-            .filter_map(|opt_s| opt_s.map(String::from))
-            .collect();
-        
-        // Filtrar DataFrame para enfermeiros e acolhimento
-        let df_nurse_acolhimento = df.clone().lazy()
-            .filter(
-                col("ifroprofissionalcbods").eq(lit("ENFERMEIRO"))
-                .and(col("ifrotabelanome").eq(lit("Acolhimento")))
-            )
-            .collect()?;
-        
-        // Filtrar nomes não desejados
-        let mut keep_rows = Vec::with_capacity(df_nurse_acolhimento.height());
-        
-        for i in 0..df_nurse_acolhimento.height() {
-            let nome = df_nurse_acolhimento.column("ifroprofissionalnome")?.str()?.get(i).unwrap_or("");
-            let keep = !non_nurse_names.contains(&nome.to_string());
-            keep_rows.push(keep);
-        }
-        
-        // Converter para Series e filtrar
-        let mask = BooleanChunked::new("mask".into(), keep_rows);
-        let df_filtered = df_nurse_acolhimento.filter(&mask)?;
-        
-        // Obter nomes únicos de enfermeiras
-        let unique_nurses = df_filtered.column("ifroprofissionalnome")?
-            .unique()?
-            .str()?
-            .into_iter()
-            .filter_map(|opt_s| opt_s.map(String::from))
-            .collect::<Vec<String>>();
-        
-        // Criar dicionário organizado para cada enfermeira
-        let mut organized_data = HashMap::new();
-        
-        for nurse_name in unique_nurses {
-            // Filtrar para esta enfermeira
-            let nurse_name_owned = nurse_name.clone();
-            
-            let df_nurse = df_filtered.clone().lazy()
-                .filter(col("ifroprofissionalnome").eq(lit(nurse_name_owned)))
-                .collect()?;
-            
-            // Extrair competências
-            let competencias = df_nurse.column("ifrocompetencia")?
-                .str()?
-                .into_iter()
-                .filter_map(|opt_s| opt_s.map(String::from))
-                .collect::<Vec<String>>();
-            
-            // Fazer contagem manual
-            let mut comp_counts: HashMap<String, i64> = HashMap::new();
-            for comp in competencias {
-                *comp_counts.entry(comp).or_insert(0) += 1;
-            }
-            
-            let mut nurse_data = HashMap::new();
-            nurse_data.insert("todos".to_string(), json!(df_nurse.height() as i64));
-            
-            // Adicionar contagens por competência
-            for (competencia, count) in comp_counts {
-                nurse_data.insert(competencia, json!(count));
+        Ok(organize_counts_by_two_keys(rows))
+    }
+
+
+    pub fn create_dict_to_age_by_disease(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Contagens por queixa classificada e idade simples
+        let rows = counts.iter().filter_map(|count| match count.keys.as_slice() {
+            [queixa, idade] => Some((queixa.clone(), idade.clone(), count)),
+            _ => None,
+        });
+
+        Ok(organize_counts_by_two_keys(rows))
+    }
+
+
+    pub fn create_dict_to_incidence_by_neighborhood(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Contagens por bairro, queixa e idade simples ({bairro: {queixa: {idade: {competência, todos}}}})
+        let mut por_bairro: HashMap<String, Vec<(String, String, &GroupedCount)>> = HashMap::new();
+        for count in counts {
+            if let [bairro, queixa, idade] = count.keys.as_slice() {
+                por_bairro.entry(bairro.clone()).or_default().push((queixa.clone(), idade.clone(), count));
             }
-            
-            organized_data.insert(nurse_name, nurse_data);
         }
-        
+
+        let organized_data: HashMap<String, Value> = por_bairro.into_iter()
+            .map(|(bairro, rows)| (bairro, organize_counts_by_two_keys(rows.into_iter())))
+            .collect();
+
         Ok(json!(organized_data))
     }
 
 
-    pub async fn create_dict_to_number_of_visits_per_doctor(&self, df: &DataFrame, df_non_doctors: &DataFrame) -> Result<Value, Box<dyn Error + Send + Sync>> {
-        // Obter lista de médicos a excluir
-        let non_doctor_names: Vec<String> = df_non_doctors.column("ifroprofissionalnome")?
-            .str()?
-            .into_iter()
-            .filter_map(|opt_s| opt_s.map(String::from))
-            .collect();
-        
-        // Filtrar DataFrame para médicos e consulta médica
-        let df_doctor_consulta = df.clone().lazy()
-            .filter(
-                col("ifroprofissionalcbods").eq(lit("MEDICO CLINICO"))
-                .and(col("ifrotabelanome").eq(lit("ConsultaMedica")))
-            )
-            .collect()?;
-        
-        // Filtrar nomes não desejados
-        let mut keep_rows = Vec::with_capacity(df_doctor_consulta.height());
-        
-        for i in 0..df_doctor_consulta.height() {
-            let nome = df_doctor_consulta.column("ifroprofissionalnome")?.str()?.get(i).unwrap_or("");
-            let keep = !non_doctor_names.contains(&nome.to_string());
-            keep_rows.push(keep);
+    pub fn create_dict_to_procedure_ranking(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Atendimentos por código SUS do procedimento, com o nome em "nome" ({código: {nome, competência, todos}})
+        let rows = counts.iter()
+            .filter_map(|count| count.keys.first().map(|codigo| (codigo.clone(), count)));
+
+        let mut organized_data = organize_counts_by_key(rows, &[]);
+
+        // Textos são mantidos na combinação das fatias, então o nome não interfere nas somas
+        for count in counts {
+            if let [codigo, nome] = count.keys.as_slice() {
+                if let Some(procedure) = organized_data.get_mut(codigo).filter(|_| !nome.is_empty()) {
+                    procedure["nome"] = json!(nome);
+                }
+            }
         }
+
+        Ok(organized_data)
+    }
+
+
+    pub fn create_dict_to_attendances_by_municipality(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Atendimentos pelo código IBGE de 6 dígitos do município de residência ({código: {competência, todos}})
+        let rows = counts.iter()
+            .filter_map(|count| count.keys.first().map(|codigo| (codigo.clone(), count)));
+
+        Ok(organize_counts_by_key(rows, &[]))
+    }
+
+
+    pub fn create_dict_to_flow_funnel(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Visitas por combinação de fluxos e chegada ({"etapa_a|etapa_b": {"dia|hora": {competência, todos}}})
+        let rows = counts.iter().filter_map(|count| match count.keys.as_slice() {
+            [etapas, en_day, hora] => {
+                let dia = DAY_MAPPINGS.iter()
+                    .find(|(en, _)| en == en_day)
+                    .map(|(_, pt_day)| *pt_day)
+                    .unwrap_or("");
+                Some((etapas.clone(), format!("{}{}{}", dia, FUNNEL_KEY_SEPARATOR, hora), count))
+            },
+            _ => None,
+        });
+
+        Ok(organize_counts_by_two_keys(rows))
+    }
+
+
+    pub fn create_dict_to_number_of_visits_per_nurse(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Enfermeiros do acolhimento, já sem os nomes excluídos
+        let rows = counts.iter()
+            .filter_map(|count| count.keys.first().map(|nome| (nome.clone(), count)));
         
-        // Converter para Series e filtrar
-        let mask = BooleanChunked::new("mask".into(), keep_rows);
-        let df_filtered = df_doctor_consulta.filter(&mask)?;
-        
-        // Obter nomes únicos de médicos
-        let unique_doctors = df_filtered.column("ifroprofissionalnome")?
-            .unique()?
-            .str()?
-            .into_iter()
-            .filter_map(|opt_s| opt_s.map(String::from))
-            .collect::<Vec<String>>();
-        
-        // Criar dicionário organizado para cada médico
-        let mut organized_data = HashMap::new();
+        Ok(organize_counts_by_key(rows, &[]))
+    }
+
+
+    pub fn create_dict_to_number_of_visits_per_doctor(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Médicos clínicos em consulta médica, já sem os nomes excluídos
+        let rows = counts.iter()
+            .filter_map(|count| count.keys.first().map(|nome| (nome.clone(), count)));
         
-        for doctor_name in unique_doctors {
-            // Filtrar para este médico
-            let doctor_name_owned = doctor_name.clone();
-            
-            let df_doctor = df_filtered.clone().lazy()
-                .filter(col("ifroprofissionalnome").eq(lit(doctor_name_owned)))
-                .collect()?;
-            
-            // Extrair competências
-            let competencias = df_doctor.column("ifrocompetencia")?
-                .str()?
-                .into_iter()
-                .filter_map(|opt_s| opt_s.map(String::from))
-                .collect::<Vec<String>>();
-            
-            // Fazer contagem manual
-            let mut comp_counts: HashMap<String, i64> = HashMap::new();
-            for comp in competencias {
-                *comp_counts.entry(comp).or_insert(0) += 1;
-            }
-            
-            let mut doctor_data = HashMap::new();
-            doctor_data.insert("todos".to_string(), json!(df_doctor.height() as i64));
-            
-            // Adicionar contagens por competência
-            for (competencia, count) in comp_counts {
-                doctor_data.insert(competencia, json!(count));
+        Ok(organize_counts_by_key(rows, &[]))
+    }
+
+
+    pub fn create_dict_to_service_time_distribution_per_doctor(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Quantidade de atendimentos por duração em minutos, para cada médico e competência e no total ("todos")
+        let mut distribution: HashMap<String, HashMap<String, HashMap<String, i64>>> = HashMap::new();
+
+        for count in counts {
+            let (nome, minutos) = match count.keys.as_slice() {
+                [nome, minutos] => (nome, minutos),
+                _ => continue,
+            };
+
+            let by_period = distribution.entry(nome.clone()).or_default();
+            for period in [count.competencia.as_str(), "todos"] {
+                *by_period.entry(period.to_string()).or_default()
+                    .entry(minutos.clone())
+                    .or_insert(0) += count.quantidade;
             }
-            
-            organized_data.insert(doctor_name, doctor_data);
         }
-        
-        Ok(json!(organized_data))
+
+        Ok(json!(distribution))
     }
 
 
@@ -744,97 +561,39 @@ impl DataProcessingForGraphPlotting {
     }
 
 
-    pub async fn create_dictionary_with_location_and_number_per_disease(&self, df: &DataFrame) -> Result<Value, Box<dyn Error + Send + Sync>> {
-        println!("Processando mapa de calor com indicação de doenças");
-        
-        // Extrair todos os dados relevantes
-        let mut dados_validos = Vec::new();
-        
-        for i in 0..df.height() {
-            let competencia = df.column("ifrocompetencia")?.str()?.get(i);
-            let endereco = df.column("ifropacienteendereco")?.str()?.get(i);
-            let bairro = df.column("ifropacientebairro")?.str()?.get(i);
-            let queixa = df.column("ifropacientequeixaprincipal")?.str()?.get(i);
-            let latitude_opt = df.column("ifropacientelatitude")?.str()?.get(i);
-            let longitude_opt = df.column("ifropacientelongitude")?.str()?.get(i);
-            
-            // Verificar se todos os campos estão preenchidos
-            if let (Some(comp), Some(end), Some(b), Some(q), Some(lat_str), Some(long_str)) = 
-                   (competencia, endereco, bairro, queixa, latitude_opt, longitude_opt) {
-                
-                // Filtrar endereços "DO IPE"
-                if end != "DO IPE" {
-                    // Converter latitude e longitude para números
-                    if let (Ok(lat), Ok(long)) = (lat_str.parse::<f64>(), long_str.parse::<f64>()) {
-                        // Adicionar aos dados válidos
-                        dados_validos.push((
-                            comp.to_string(),
-                            b.to_string(),
-                            q.to_string(),
-                            lat,
-                            long
-                        ));
-                    }
-                }
-            }
-        }
-        
-        // Estrutura para armazenar dados por queixa, competência e bairro
-        // Uma abordagem alternativa sem usar f64 como chave
-        let mut dados_por_queixa: HashMap<String, HashMap<String, HashMap<String, (f64, f64, i64)>>> = HashMap::new();
-        
-        // Processar os dados
-        for (comp, bairro, queixa, lat, long) in dados_validos {
-            // Garantir que a queixa exista no mapa
-            if !dados_por_queixa.contains_key(&queixa) {
-                dados_por_queixa.insert(queixa.clone(), HashMap::new());
-            }
-            
-            // Obter o mapa de competências para esta queixa
-            let comp_map = dados_por_queixa.get_mut(&queixa).unwrap();
-            
-            // Garantir que a competência exista no mapa
-            if !comp_map.contains_key(&comp) {
-                comp_map.insert(comp.clone(), HashMap::new());
-            }
-            
-            // Obter o mapa de bairros para esta competência
-            let bairro_map = comp_map.get_mut(&comp).unwrap();
+    pub fn create_dictionary_with_location_and_number_per_disease(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Estrutura: queixa -> competência (e "todos") -> bairro -> (latitude, longitude, quantidade)
+        let mut dados_por_queixa: HashMap<String, HashMap<String, NeighborhoodPoints>> = HashMap::new();
+        
+        for count in counts {
+            let (queixa, bairro, (lat, long)) = match (count.keys.first(), count.keys.get(1), count.coordinates) {
+                (Some(queixa), Some(bairro), Some(coordinates)) => (queixa, bairro, coordinates),
+                _ => continue,
+            };
             
-            // Adicionar ou atualizar dados do bairro
-            let entry = bairro_map.entry(bairro.clone()).or_insert((lat, long, 0));
-            entry.2 += 1;
+            let comp_map = dados_por_queixa.entry(queixa.clone()).or_default();
             
-            // Também adicionar à competência "todos"
-            if !comp_map.contains_key("todos") {
-                comp_map.insert("todos".to_string(), HashMap::new());
+            for comp in [count.competencia.as_str(), "todos"] {
+                let entry = comp_map.entry(comp.to_string())
+                    .or_default()
+                    .entry(bairro.clone())
+                    .or_insert((lat, long, 0));
+                entry.2 += count.quantidade;
             }
-            
-            let todos_map = comp_map.get_mut("todos").unwrap();
-            let todos_entry = todos_map.entry(bairro.clone()).or_insert((lat, long, 0));
-            todos_entry.2 += 1;
         }
         
         // Construir o JSON final
         let mut final_dict = HashMap::new();
         
         for (queixa, comp_map) in dados_por_queixa {
-            let mut illness_dict = HashMap::new();
-            
-            for (comp, bairro_map) in comp_map {
-                let mut comp_dict = HashMap::new();
-                
-                for (bairro, (lat, long, quantidade)) in bairro_map {
-                    let mut neighborhood_data = HashMap::new();
-                    neighborhood_data.insert("latitude".to_string(), json!(lat));
-                    neighborhood_data.insert("longitude".to_string(), json!(long));
-                    neighborhood_data.insert("quantidade".to_string(), json!(quantidade));
-                    
-                    comp_dict.insert(bairro, json!(neighborhood_data));
-                }
-                
-                illness_dict.insert(comp, comp_dict);
-            }
+            let illness_dict: HashMap<String, HashMap<String, Value>> = comp_map.into_iter()
+                .map(|(comp, bairro_map)| {
+                    let comp_dict = bairro_map.into_iter()
+                        .map(|(bairro, (lat, long, quantidade))| (bairro, location_entry(lat, long, quantidade)))
+                        .collect();
+                    (comp, comp_dict)
+                })
+                .collect();
             
             final_dict.insert(queixa, json!(illness_dict));
         }
@@ -843,52 +602,93 @@ impl DataProcessingForGraphPlotting {
     }
 
 
+    pub fn create_dict_to_disease_hotspots(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Casos por queixa e local ("latitude;longitude"), somáveis entre competências
+        let rows = counts.iter().filter_map(|count| match (count.keys.first(), count.coordinates) {
+            (Some(queixa), Some((lat, long))) => Some((queixa.clone(), point_key(lat, long), count)),
+            _ => None,
+        });
 
-    pub async fn create_dict_to_heat_map_with_the_number_of_medical_appointments_by_neighborhood(&self, df: &DataFrame) -> Result<Value, Box<dyn Error + Send + Sync>> {
-        println!("Processando mapa de calor com o número de atendimentos médicos por bairro");
-        
-        // Extrai todos os dados relevantes
-        let mut dados_validos = Vec::new();
-        
-        for i in 0..df.height() {
-            let bairro = df.column("ifropacientebairro")?.str()?.get(i);
-            let latitude_opt = df.column("ifropacientelatitude")?.str()?.get(i);
-            let longitude_opt = df.column("ifropacientelongitude")?.str()?.get(i);
-            let endereco = df.column("ifropacienteendereco")?.str()?.get(i);
-            
-            // Verificar se todos os campos necessários estão preenchidos
-            if let (Some(b), Some(lat_str), Some(long_str), Some(end)) = (bairro, latitude_opt, longitude_opt, endereco) {
-                // Filtrar endereços "DO IPE"
-                if end != "DO IPE" {
-                    // Converter latitude e longitude para números
-                    if let (Ok(lat), Ok(long)) = (lat_str.parse::<f64>(), long_str.parse::<f64>()) {
-                        // Adicionar aos dados válidos
-                        dados_validos.push((b.to_string(), lat, long));
-                    }
-                }
+        Ok(organize_counts_by_two_keys(rows))
+    }
+
+
+
+    pub fn create_dict_to_heat_map_with_the_number_of_medical_appointments_by_neighborhood(&self, counts: &[GroupedCount]) -> Result<Value, Box<dyn Error + Send + Sync>> {
+        // Soma as competências recebidas, mantendo as coordenadas da primeira ocorrência do bairro
+        let mut bairro_dados: NeighborhoodPoints = HashMap::new();
+        
+        for count in counts {
+            if let (Some(bairro), Some((lat, long))) = (count.keys.first(), count.coordinates) {
+                let entry = bairro_dados.entry(bairro.clone()).or_insert((lat, long, 0));
+                entry.2 += count.quantidade;
             }
         }
         
-        // Agrupa e contar por bairro
-        let mut bairro_dados: HashMap<String, (f64, f64, i64)> = HashMap::new();
-        
-        for (bairro, lat, long) in dados_validos {
-            let entry = bairro_dados.entry(bairro).or_insert((lat, long, 0));
-            entry.2 += 1;
-        }
-        
-        // Constroi o dicionário organizado final
-        let mut organized_data = HashMap::new();
-        
-        for (bairro, (lat, long, quantidade)) in bairro_dados {
-            let mut neighborhood_data = HashMap::new();
-            neighborhood_data.insert("latitude".to_string(), json!(lat));
-            neighborhood_data.insert("longitude".to_string(), json!(long));
-            neighborhood_data.insert("quantidade".to_string(), json!(quantidade));
-            
-            organized_data.insert(bairro, json!(neighborhood_data));
-        }
+        let organized_data: HashMap<String, Value> = bairro_dados.into_iter()
+            .map(|(bairro, (lat, long, quantidade))| (bairro, location_entry(lat, long, quantidade)))
+            .collect();
         
         Ok(json!(organized_data))
     }
+}
+
+// Agrupa as contagens por chave, com o total em "todos" e o valor de cada competência.
+// As chaves padrão aparecem mesmo sem registros
+fn organize_counts_by_key<'a>(rows: impl Iterator<Item = (String, &'a GroupedCount)>, default_keys: &[&str]) -> Value {
+    let mut counts: HashMap<String, HashMap<String, i64>> = default_keys.iter()
+        .map(|key| (key.to_string(), HashMap::new()))
+        .collect();
+    
+    for (key, count) in rows {
+        *counts.entry(key).or_default()
+            .entry(count.competencia.clone())
+            .or_insert(0) += count.quantidade;
+    }
+    
+    let mut organized_data = HashMap::new();
+    
+    for (key, comp_counts) in counts {
+        let total: i64 = comp_counts.values().sum();
+        
+        let mut key_data: HashMap<String, Value> = comp_counts.into_iter()
+            .map(|(competencia, count)| (competencia, json!(count)))
+            .collect();
+        key_data.insert("todos".to_string(), json!(total));
+        
+        organized_data.insert(key, key_data);
+    }
+    
+    json!(organized_data)
+}
+
+// Agrupa as contagens em dois níveis ({chave: {subchave: {competência, todos}}})
+fn organize_counts_by_two_keys<'a>(rows: impl Iterator<Item = (String, String, &'a GroupedCount)>) -> Value {
+    let mut grouped: HashMap<String, Vec<(String, &GroupedCount)>> = HashMap::new();
+    for (key, sub_key, count) in rows {
+        grouped.entry(key).or_default().push((sub_key, count));
+    }
+
+    let organized_data: HashMap<String, Value> = grouped.into_iter()
+        .map(|(key, rows)| (key, organize_counts_by_key(rows.into_iter(), &[])))
+        .collect();
+
+    json!(organized_data)
+}
+
+// Código de sexo do paciente (M/F) como exibido nos gráficos
+fn sex_label(code: &str) -> &'static str {
+    match code.trim().to_uppercase().as_str() {
+        "M" | "MASCULINO" => SEX_MALE,
+        "F" | "FEMININO" => SEX_FEMALE,
+        _ => SEX_NOT_INFORMED,
+    }
+}
+
+fn location_entry(lat: f64, long: f64, quantidade: i64) -> Value {
+    json!({
+        "latitude": lat,
+        "longitude": long,
+        "quantidade": quantidade
+    })
 }
\ No newline at end of file
@@ -0,0 +1,204 @@
+use serde_json::{Map, Number, Value};
+use std::collections::HashMap;
+
+use crate::domain::models::data_upa::GroupedCount;
+
+// Campos que identificam a localização e não devem ser somados
+const NON_SUMMABLE_KEYS: [&str; 2] = ["latitude", "longitude"];
+
+/// Separa os dados brutos (coluna -> valores) por competência
+pub fn split_by_competencia(raw_data: &HashMap<String, Vec<Value>>) -> HashMap<String, HashMap<String, Vec<Value>>> {
+    let mut slices: HashMap<String, HashMap<String, Vec<Value>>> = HashMap::new();
+
+    let competencias = match raw_data.get("ifrocompetencia") {
+        Some(values) => values,
+        None => return slices,
+    };
+
+    for (i, competencia) in competencias.iter().enumerate() {
+        // Linhas sem competência não pertencem a nenhuma fatia
+        let competencia = match competencia {
+            Value::String(s) if !s.is_empty() => s.clone(),
+            _ => continue,
+        };
+
+        let slice = slices.entry(competencia).or_insert_with(|| {
+            raw_data.keys().map(|column| (column.clone(), Vec::new())).collect()
+        });
+
+        for (column, values) in raw_data {
+            if let (Some(value), Some(slice_values)) = (values.get(i), slice.get_mut(column)) {
+                slice_values.push(value.clone());
+            }
+        }
+    }
+
+    slices
+}
+
+/// Separa as contagens agregadas no banco por competência
+pub fn split_counts_by_competencia(counts: Vec<GroupedCount>) -> HashMap<String, Vec<GroupedCount>> {
+    let mut slices: HashMap<String, Vec<GroupedCount>> = HashMap::new();
+
+    for count in counts {
+        if count.competencia.is_empty() {
+            continue;
+        }
+
+        slices.entry(count.competencia.clone()).or_default().push(count);
+    }
+
+    slices
+}
+
+/// Combina os dados de várias fatias em um único JSON
+pub fn merge_graph_data<'a>(slices: impl IntoIterator<Item = &'a Value>) -> Value {
+    let mut merged = Value::Object(Map::new());
+
+    for slice in slices {
+        merge_value(&mut merged, slice, None);
+    }
+
+    merged
+}
+
+// Rótulo de um item de lista: os seus campos de texto, booleanos e nulos. Vetores de contagens
+// (faixas, horas) e itens só com números não têm rótulo
+fn item_label(value: &Value) -> Option<Vec<(&String, &Value)>> {
+    let label: Vec<(&String, &Value)> = value.as_object()?.iter()
+        .filter(|(_, v)| matches!(v, Value::String(_) | Value::Bool(_) | Value::Null))
+        .collect();
+
+    (!label.is_empty()).then_some(label)
+}
+
+// Soma recursivamente os valores numéricos. Coordenadas e textos mantêm o primeiro valor, e os itens
+// de listas com rótulo são combinados pelo rótulo
+fn merge_value(target: &mut Value, other: &Value, key: Option<&str>) {
+    match (target, other) {
+        (Value::Object(target_map), Value::Object(other_map)) => {
+            for (k, v) in other_map {
+                match target_map.get_mut(k) {
+                    Some(existing) => merge_value(existing, v, Some(k)),
+                    None => {
+                        target_map.insert(k.clone(), v.clone());
+                    }
+                }
+            }
+        },
+        (Value::Array(target_items), Value::Array(other_items)) => {
+            for (i, v) in other_items.iter().enumerate() {
+                // Itens com rótulo podem vir em outra ordem em cada fatia; os demais são posicionais
+                let existing = match item_label(v) {
+                    Some(label) => target_items.iter_mut().find(|item| item_label(item).as_ref() == Some(&label)),
+                    None => target_items.get_mut(i),
+                };
+
+                match existing {
+                    Some(existing) => merge_value(existing, v, key),
+                    None => target_items.push(v.clone()),
+                }
+            }
+        },
+        (Value::Number(a), Value::Number(b)) => {
+            if key.map(|k| NON_SUMMABLE_KEYS.contains(&k)).unwrap_or(false) {
+                return;
+            }
+
+            let sum = match (a.as_i64(), b.as_i64()) {
+                (Some(x), Some(y)) => Some(Number::from(x + y)),
+                _ => Number::from_f64(a.as_f64().unwrap_or(0.0) + b.as_f64().unwrap_or(0.0)),
+            };
+
+            if let Some(n) = sum {
+                *a = n;
+            }
+        },
+        _ => {}
+    }
+}
+
+/// Multiplica os valores numéricos por `factor` (ex.: normalização por população). Coordenadas e textos são mantidos
+pub fn scale_graph_data(data: &Value, factor: f64) -> Value {
+    scale_value(data, factor, None)
+}
+
+fn scale_value(value: &Value, factor: f64, key: Option<&str>) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(map.iter()
+            .map(|(k, v)| (k.clone(), scale_value(v, factor, Some(k))))
+            .collect()),
+        Value::Array(items) => Value::Array(items.iter().map(|v| scale_value(v, factor, key)).collect()),
+        Value::Number(n) if !key.map(|k| NON_SUMMABLE_KEYS.contains(&k)).unwrap_or(false) => {
+            // Quatro casas decimais preservam taxas pequenas
+            let scaled = (n.as_f64().unwrap_or(0.0) * factor * 10_000.0).round() / 10_000.0;
+            Number::from_f64(scaled).map(Value::Number).unwrap_or_else(|| value.clone())
+        },
+        _ => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn counts_are_summed_and_missing_keys_added() {
+        let slices = [
+            json!({"2024-1": {"CENTRO": 3}, "todos": {"CENTRO": 3}}),
+            json!({"2024-2": {"NORTE": 2}, "todos": {"CENTRO": 4, "NORTE": 2}}),
+        ];
+
+        assert_eq!(merge_graph_data(&slices), json!({
+            "2024-1": {"CENTRO": 3},
+            "2024-2": {"NORTE": 2},
+            "todos": {"CENTRO": 7, "NORTE": 2},
+        }));
+    }
+
+    #[test]
+    fn coordinates_and_texts_are_kept_from_the_first_slice() {
+        let slices = [
+            json!({"CENTRO": {"latitude": -8.76, "longitude": -63.9, "quantidade": 2, "nome": "Centro"}}),
+            json!({"CENTRO": {"latitude": -8.77, "longitude": -63.8, "quantidade": 5, "nome": "CENTRO"}}),
+        ];
+
+        assert_eq!(merge_graph_data(&slices), json!({
+            "CENTRO": {"latitude": -8.76, "longitude": -63.9, "quantidade": 7, "nome": "Centro"},
+        }));
+    }
+
+    #[test]
+    fn integers_and_decimals_are_summed_as_decimals() {
+        let slices = [json!({"taxa": 1}), json!({"taxa": 0.5})];
+
+        assert_eq!(merge_graph_data(&slices), json!({"taxa": 1.5}));
+    }
+
+    #[test]
+    fn labelled_list_items_are_merged_by_label_in_any_order() {
+        let slices = [
+            json!({"todos": [{"codigo": "0301", "quantidade": 10}, {"codigo": "0214", "quantidade": 4}]}),
+            json!({"todos": [{"codigo": "0214", "quantidade": 6}, {"codigo": "0101", "quantidade": 1}, {"codigo": "0301", "quantidade": 2}]}),
+        ];
+
+        assert_eq!(merge_graph_data(&slices), json!({
+            "todos": [
+                {"codigo": "0301", "quantidade": 12},
+                {"codigo": "0214", "quantidade": 10},
+                {"codigo": "0101", "quantidade": 1},
+            ],
+        }));
+    }
+
+    #[test]
+    fn positional_lists_are_summed_by_index() {
+        let slices = [
+            json!({"todos": [[1, 0, 2], [0, 0, 1]]}),
+            json!({"todos": [[2, 1, 0], [1, 0, 0], [5, 5, 5]]}),
+        ];
+
+        assert_eq!(merge_graph_data(&slices), json!({"todos": [[3, 1, 2], [1, 0, 1], [5, 5, 5]]}));
+    }
+}
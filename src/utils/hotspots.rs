@@ -0,0 +1,416 @@
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use crate::domain::models::hotspots::{HotspotCluster, HotspotPoint, HotspotQuery, HotspotResult};
+use crate::AppError;
+
+pub const DEFAULT_RADIUS_METERS: f64 = 500.0;
+pub const DEFAULT_MIN_CASES: i64 = 5;
+pub const DEFAULT_GI_DISTANCE_METERS: f64 = 1000.0;
+
+pub const HOTSPOT_HOT: &str = "quente";
+pub const HOTSPOT_COLD: &str = "frio";
+pub const HOTSPOT_NOT_SIGNIFICANT: &str = "nao_significativo";
+
+const MAX_DISTANCE_METERS: f64 = 20_000.0;
+const EARTH_RADIUS_METERS: f64 = 6_371_000.0;
+
+// Valores críticos da normal padrão (bicaudal) para 99%, 95% e 90% de confiança
+const CONFIDENCE_LEVELS: [(f64, u32); 3] = [(2.576, 99), (1.960, 95), (1.645, 90)];
+
+/// Local com a quantidade de casos (coordenadas arredondadas na consulta)
+#[derive(Debug, Clone, Copy)]
+pub struct CasePoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub quantidade: i64,
+}
+
+/// Raio e mínimo de casos do DBSCAN e distância da vizinhança do Gi*
+pub struct HotspotParams {
+    pub radius_meters: f64,
+    pub min_cases: i64,
+    pub gi_distance_meters: f64,
+}
+
+impl HotspotParams {
+    pub fn from_query(query: &HotspotQuery) -> Result<Self, AppError> {
+        let distance = |field: &str, value: Option<f64>, default: f64| match value {
+            None => Ok(default),
+            Some(v) if v > 0.0 && v <= MAX_DISTANCE_METERS => Ok(v),
+            Some(v) => Err(AppError::BadRequest(format!(
+                "Error: '{}' is not a valid value for '{}'. Expected meters between 0 and {}",
+                v, field, MAX_DISTANCE_METERS
+            ))),
+        };
+
+        let min_cases = query.min_casos.unwrap_or(DEFAULT_MIN_CASES);
+        if min_cases < 1 {
+            return Err(AppError::BadRequest("Error: 'min_casos' must be greater than 0".to_string()));
+        }
+
+        Ok(Self {
+            radius_meters: distance("raio_metros", query.raio_metros, DEFAULT_RADIUS_METERS)?,
+            min_cases,
+            gi_distance_meters: distance("distancia_gi", query.distancia_gi, DEFAULT_GI_DISTANCE_METERS)?,
+        })
+    }
+
+    pub fn default_params() -> Self {
+        Self {
+            radius_meters: DEFAULT_RADIUS_METERS,
+            min_cases: DEFAULT_MIN_CASES,
+            gi_distance_meters: DEFAULT_GI_DISTANCE_METERS,
+        }
+    }
+}
+
+fn round4(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+/// Distância em metros entre dois pontos (fórmula de haversine)
+pub fn haversine_meters(a: &CasePoint, b: &CasePoint) -> f64 {
+    let (lat_a, lat_b) = (a.latitude.to_radians(), b.latitude.to_radians());
+    let d_lat = lat_b - lat_a;
+    let d_long = (b.longitude - a.longitude).to_radians();
+
+    let h = (d_lat / 2.0).sin().powi(2) + lat_a.cos() * lat_b.cos() * (d_long / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_METERS * h.sqrt().asin()
+}
+
+// Índices dos pontos a até `distance` metros de cada ponto (incluindo o próprio)
+fn neighborhoods(points: &[CasePoint], distance: f64) -> Vec<Vec<usize>> {
+    points.iter()
+        .map(|a| (0..points.len()).filter(|&j| haversine_meters(a, &points[j]) <= distance).collect())
+        .collect()
+}
+
+/// DBSCAN ponderado: um ponto é núcleo quando a vizinhança soma ao menos `min_cases` casos.
+/// Retorna o aglomerado de cada ponto (`None` para ruído)
+pub fn dbscan(points: &[CasePoint], radius_meters: f64, min_cases: i64) -> Vec<Option<usize>> {
+    let neighbors = neighborhoods(points, radius_meters);
+    let is_core = |i: usize| neighbors[i].iter().map(|&j| points[j].quantidade).sum::<i64>() >= min_cases;
+
+    let mut labels: Vec<Option<usize>> = vec![None; points.len()];
+    let mut visited = vec![false; points.len()];
+    let mut next_cluster = 0;
+
+    for i in 0..points.len() {
+        if visited[i] || !is_core(i) {
+            continue;
+        }
+
+        visited[i] = true;
+        labels[i] = Some(next_cluster);
+        let mut queue = neighbors[i].clone();
+
+        while let Some(j) = queue.pop() {
+            if labels[j].is_none() {
+                labels[j] = Some(next_cluster);
+            }
+
+            // Apenas pontos núcleo expandem o aglomerado; os demais ficam na borda
+            if !visited[j] && is_core(j) {
+                visited[j] = true;
+                queue.extend(neighbors[j].iter().copied());
+            }
+        }
+
+        next_cluster += 1;
+    }
+
+    labels
+}
+
+fn cross(o: [f64; 2], a: [f64; 2], b: [f64; 2]) -> f64 {
+    (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0])
+}
+
+/// Envoltória convexa (cadeia monótona) em [longitude, latitude], como anel fechado.
+/// Com menos de três pontos distintos, retorna os próprios pontos
+pub fn convex_hull(points: &[[f64; 2]]) -> Vec<[f64; 2]> {
+    let mut sorted = points.to_vec();
+    sorted.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    sorted.dedup();
+
+    if sorted.len() < 3 {
+        return sorted;
+    }
+
+    let mut lower: Vec<[f64; 2]> = Vec::new();
+    for point in &sorted {
+        while lower.len() >= 2 && cross(lower[lower.len() - 2], lower[lower.len() - 1], *point) <= 0.0 {
+            lower.pop();
+        }
+        lower.push(*point);
+    }
+
+    let mut upper: Vec<[f64; 2]> = Vec::new();
+    for point in sorted.iter().rev() {
+        while upper.len() >= 2 && cross(upper[upper.len() - 2], upper[upper.len() - 1], *point) <= 0.0 {
+            upper.pop();
+        }
+        upper.push(*point);
+    }
+
+    // Pontos colineares não formam polígono
+    if lower.len() + upper.len() < 5 {
+        return vec![sorted[0], sorted[sorted.len() - 1]];
+    }
+
+    lower.pop();
+    upper.pop();
+    lower.extend(upper);
+    lower.push(lower[0]);
+    lower
+}
+
+/// Estatística Gi* de Getis-Ord (escore z) de cada ponto, com pesos binários na distância informada
+pub fn getis_ord_gi_star(points: &[CasePoint], distance_meters: f64) -> Vec<f64> {
+    let n = points.len() as f64;
+    if points.len() < 2 {
+        return vec![0.0; points.len()];
+    }
+
+    let values: Vec<f64> = points.iter().map(|p| p.quantidade as f64).collect();
+    let mean = values.iter().sum::<f64>() / n;
+    let s = (values.iter().map(|x| x * x).sum::<f64>() / n - mean * mean).max(0.0).sqrt();
+
+    neighborhoods(points, distance_meters).into_iter()
+        .map(|neighbors| {
+            // Pesos binários: a soma dos pesos e a dos quadrados coincidem
+            let weights = neighbors.len() as f64;
+            let local: f64 = neighbors.iter().map(|&j| values[j]).sum();
+            let denominator = s * ((n * weights - weights * weights) / (n - 1.0)).max(0.0).sqrt();
+
+            if denominator > 0.0 {
+                (local - mean * weights) / denominator
+            } else {
+                0.0
+            }
+        })
+        .collect()
+}
+
+// Função erro complementar (Abramowitz e Stegun 7.1.26)
+fn erfc(x: f64) -> f64 {
+    let t = 1.0 / (1.0 + 0.3275911 * x.abs());
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let value = poly * (-x * x).exp();
+    if x >= 0.0 { value } else { 2.0 - value }
+}
+
+/// Valor p bicaudal de um escore z
+pub fn p_value(z: f64) -> f64 {
+    erfc(z.abs() / std::f64::consts::SQRT_2).clamp(0.0, 1.0)
+}
+
+// Maior nível de confiança atingido e classificação (quente, frio ou não significativo)
+fn significance(z: f64) -> (Option<u32>, &'static str) {
+    match CONFIDENCE_LEVELS.iter().find(|(critical, _)| z.abs() >= *critical) {
+        Some((_, confidence)) if z > 0.0 => (Some(*confidence), HOTSPOT_HOT),
+        Some((_, confidence)) => (Some(*confidence), HOTSPOT_COLD),
+        None => (None, HOTSPOT_NOT_SIGNIFICANT),
+    }
+}
+
+/// Aglomerados (DBSCAN) e significância local (Gi*) dos casos de uma doença em um período. A envoltória
+/// usa apenas os locais com ao menos `min_cell_size` casos, para não revelar os locais suprimidos
+pub fn detect_hotspots(points: &[CasePoint], params: &HotspotParams, min_cell_size: i64) -> HotspotResult {
+    let labels = dbscan(points, params.radius_meters, params.min_cases);
+    let scores = getis_ord_gi_star(points, params.gi_distance_meters);
+
+    let mut members: BTreeMap<usize, Vec<&CasePoint>> = BTreeMap::new();
+    for (point, label) in points.iter().zip(&labels) {
+        if let Some(cluster) = label {
+            members.entry(*cluster).or_default().push(point);
+        }
+    }
+
+    let aglomerados = members.into_iter()
+        .map(|(id, cluster_points)| {
+            let quantidade: i64 = cluster_points.iter().map(|p| p.quantidade).sum();
+            let weighted = |f: fn(&CasePoint) -> f64| {
+                cluster_points.iter().map(|p| f(p) * p.quantidade as f64).sum::<f64>() / quantidade.max(1) as f64
+            };
+            let corners: Vec<[f64; 2]> = cluster_points.iter()
+                .filter(|p| p.quantidade >= min_cell_size)
+                .map(|p| [p.longitude, p.latitude])
+                .collect();
+
+            HotspotCluster {
+                id,
+                quantidade,
+                pontos: cluster_points.len(),
+                latitude: round4(weighted(|p| p.latitude)),
+                longitude: round4(weighted(|p| p.longitude)),
+                envoltoria: convex_hull(&corners),
+            }
+        })
+        .collect();
+
+    let pontos = points.iter().zip(labels.iter().zip(&scores))
+        .map(|(point, (label, z))| {
+            let (confianca, classificacao) = significance(*z);
+            HotspotPoint {
+                latitude: point.latitude,
+                longitude: point.longitude,
+                quantidade: point.quantidade,
+                aglomerado: *label,
+                gi_z: round4(*z),
+                p_valor: round4(p_value(*z)),
+                confianca,
+                classificacao,
+            }
+        })
+        .collect();
+
+    let casos_fora_de_aglomerados = points.iter().zip(&labels)
+        .filter(|(_, label)| label.is_none())
+        .map(|(point, _)| point.quantidade)
+        .sum();
+
+    HotspotResult { aglomerados, pontos, casos_fora_de_aglomerados }
+}
+
+/// Chave armazenada de um local ("latitude;longitude")
+pub fn point_key(latitude: f64, longitude: f64) -> String {
+    format!("{:.4};{:.4}", latitude, longitude)
+}
+
+fn parse_point_key(key: &str) -> Option<(f64, f64)> {
+    let (latitude, longitude) = key.split_once(';')?;
+    Some((latitude.parse().ok()?, longitude.parse().ok()?))
+}
+
+/// Detecta os aglomerados a partir dos casos armazenados ({doença: {local: {período: n}}}),
+/// gerando {doença: {período: {aglomerados, pontos, casos_fora_de_aglomerados}}}
+pub fn disease_hotspots(data: &Map<String, Value>, params: &HotspotParams, min_cell_size: i64) -> Value {
+    let mut result = Map::new();
+
+    for (disease, locations) in data {
+        let mut periods: BTreeMap<String, Vec<CasePoint>> = BTreeMap::new();
+
+        for (location, counts) in locations.as_object().into_iter().flatten() {
+            let Some((latitude, longitude)) = parse_point_key(location) else {
+                continue;
+            };
+
+            for (period, count) in counts.as_object().into_iter().flatten() {
+                let quantidade = count.as_i64().unwrap_or(0);
+                if quantidade > 0 {
+                    periods.entry(period.clone()).or_default().push(CasePoint { latitude, longitude, quantidade });
+                }
+            }
+        }
+
+        let disease_result: Map<String, Value> = periods.into_iter()
+            .map(|(period, points)| (period, json!(detect_hotspots(&points, params, min_cell_size))))
+            .collect();
+
+        result.insert(disease.clone(), Value::Object(disease_result));
+    }
+
+    Value::Object(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(latitude: f64, longitude: f64, quantidade: i64) -> CasePoint {
+        CasePoint { latitude, longitude, quantidade }
+    }
+
+    fn params(radius_meters: f64, min_cases: i64) -> HotspotParams {
+        HotspotParams { radius_meters, min_cases, gi_distance_meters: DEFAULT_GI_DISTANCE_METERS }
+    }
+
+    #[test]
+    fn dbscan_expands_from_core_points_and_leaves_noise_unlabelled() {
+        // Locais a cerca de 111 m um do outro: só o do meio soma 5 casos no raio e vira núcleo
+        let points = [
+            point(0.0, 0.0, 2),
+            point(0.001, 0.0, 2),
+            point(0.002, 0.0, 2),
+            point(1.0, 1.0, 3),
+            point(-1.0, -1.0, 7),
+        ];
+
+        let labels = dbscan(&points, 150.0, 5);
+
+        assert_eq!(labels, vec![Some(0), Some(0), Some(0), None, Some(1)]);
+    }
+
+    #[test]
+    fn gi_star_compares_isolated_points_with_the_global_mean() {
+        // Sem vizinhos, z = (x - média) / desvio, com média 6 e desvio 4
+        let points = [point(0.0, 0.0, 10), point(1.0, 1.0, 2)];
+
+        let scores = getis_ord_gi_star(&points, 1000.0);
+
+        assert!((scores[0] - 1.0).abs() < 1e-9);
+        assert!((scores[1] + 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn gi_star_is_zero_when_every_point_is_a_neighbor() {
+        let points = [point(0.0, 0.0, 10), point(0.001, 0.0, 2), point(0.0, 0.001, 4)];
+
+        assert_eq!(getis_ord_gi_star(&points, 1000.0), vec![0.0, 0.0, 0.0]);
+        assert_eq!(getis_ord_gi_star(&points[..1], 1000.0), vec![0.0]);
+    }
+
+    #[test]
+    fn convex_hull_is_a_closed_ring_without_interior_points() {
+        let points = [[0.0, 0.0], [1.0, 1.0], [0.5, 0.5], [1.0, 0.0], [0.0, 1.0]];
+
+        assert_eq!(
+            convex_hull(&points),
+            vec![[0.0, 0.0], [1.0, 0.0], [1.0, 1.0], [0.0, 1.0], [0.0, 0.0]]
+        );
+    }
+
+    #[test]
+    fn convex_hull_of_collinear_points_keeps_the_ends() {
+        let points = [[1.0, 1.0], [0.0, 0.0], [2.0, 2.0], [3.0, 3.0]];
+
+        assert_eq!(convex_hull(&points), vec![[0.0, 0.0], [3.0, 3.0]]);
+    }
+
+    #[test]
+    fn convex_hull_ignores_duplicate_points() {
+        let points = [[1.0, 1.0], [1.0, 1.0], [2.0, 2.0], [2.0, 2.0]];
+
+        assert_eq!(convex_hull(&points), vec![[1.0, 1.0], [2.0, 2.0]]);
+        assert_eq!(convex_hull(&[[1.0, 1.0], [1.0, 1.0]]), vec![[1.0, 1.0]]);
+    }
+
+    #[test]
+    fn hull_leaves_out_locations_below_the_minimum_cell_size() {
+        // O local com 1 caso fica na borda do aglomerado, fora do triângulo dos demais
+        let points = [
+            point(0.0, 0.0, 6),
+            point(0.001, 0.0, 6),
+            point(0.0, 0.001, 6),
+            point(0.0015, 0.0015, 1),
+        ];
+
+        let result = detect_hotspots(&points, &params(200.0, 5), 5);
+
+        assert_eq!(result.aglomerados.len(), 1);
+        let cluster = &result.aglomerados[0];
+        assert_eq!((cluster.quantidade, cluster.pontos), (19, 4));
+        assert!(!cluster.envoltoria.contains(&[0.0015, 0.0015]));
+        assert_eq!(cluster.envoltoria, vec![[0.0, 0.0], [0.001, 0.0], [0.0, 0.001], [0.0, 0.0]]);
+    }
+
+    #[test]
+    fn point_key_round_trips_and_rejects_malformed_keys() {
+        assert_eq!(parse_point_key(&point_key(-8.76041, -63.89979)), Some((-8.7604, -63.8998)));
+        assert_eq!(parse_point_key("-8.7604"), None);
+        assert_eq!(parse_point_key("-8.7604;oeste"), None);
+        assert_eq!(parse_point_key(""), None);
+    }
+}
@@ -0,0 +1,405 @@
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::domain::models::incidence::{
+    NeighborhoodPopulation,
+    NeighborhoodPopulationEntry,
+    ALL_DISEASES,
+    MEASURE_COUNTS,
+    MEASURE_RATE,
+};
+use crate::domain::models::unit_comparison::PER_CAPITA_BASE;
+use crate::utils::age_bands::{group_ages_into_bands, AgeBands};
+use crate::AppError;
+
+// Nomes aceitos para cada coluna do CSV (sem acentos e em minúsculas)
+const NEIGHBORHOOD_HEADERS: [&str; 2] = ["bairro", "nome_do_bairro"];
+const AGE_BAND_HEADERS: [&str; 4] = ["faixa", "faixa_etaria", "grupo_de_idade", "idade"];
+const POPULATION_HEADERS: [&str; 3] = ["populacao", "pessoas", "total"];
+
+/// Indica se a medida pedida é a taxa por mil habitantes (padrão: casos)
+pub fn rate_requested(medida: Option<&str>) -> Result<bool, AppError> {
+    match medida.map(|m| m.trim().to_lowercase()).as_deref() {
+        None | Some("") | Some(MEASURE_COUNTS) => Ok(false),
+        Some(MEASURE_RATE) => Ok(true),
+        Some(other) => Err(AppError::BadRequest(format!(
+            "Error: '{}' is not a valid value for 'medida'. Expected: {} or {}",
+            other, MEASURE_COUNTS, MEASURE_RATE
+        ))),
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+// Bairros são armazenados sem espaços nas pontas e em maiúsculas
+fn neighborhood_key(bairro: &str) -> String {
+    bairro.trim().to_uppercase()
+}
+
+fn fold_header(header: &str) -> String {
+    header.trim().trim_matches('"').to_lowercase()
+        .chars()
+        .map(|c| match c {
+            'á' | 'à' | 'â' | 'ã' => 'a',
+            'é' | 'ê' => 'e',
+            'í' => 'i',
+            'ó' | 'ô' | 'õ' => 'o',
+            'ú' => 'u',
+            'ç' => 'c',
+            ' ' | '-' => '_',
+            other => other,
+        })
+        .collect()
+}
+
+/// Interpreta rótulos de faixa etária do IBGE ("0 a 4 anos", "Menos de 1 ano", "100 anos ou mais", "80+")
+pub fn parse_age_band(label: &str) -> Option<(i32, Option<i32>)> {
+    let lower = label.to_lowercase();
+    let numbers: Vec<i32> = lower.split(|c: char| !c.is_ascii_digit())
+        .filter(|part| !part.is_empty())
+        .filter_map(|part| part.parse().ok())
+        .collect();
+
+    match numbers.as_slice() {
+        [n] if lower.contains("menos") => Some((0, Some(n - 1))),
+        [n] if lower.contains("mais") || lower.contains('+') => Some((*n, None)),
+        [n] => Some((*n, Some(*n))),
+        [start, end] if start <= end => Some((*start, Some(*end))),
+        _ => None,
+    }
+}
+
+// Populações com separador de milhar; células suprimidas pelo IBGE ("-", "X") contam como zero
+fn parse_population(value: &str) -> Option<i64> {
+    let cleaned: String = value.trim().trim_matches('"').chars().filter(|c| !matches!(c, '.' | ' ')).collect();
+    match cleaned.as_str() {
+        "" | "-" | "X" | "x" | "..." => Some(0),
+        other => other.parse().ok().filter(|n: &i64| *n >= 0),
+    }
+}
+
+/// Lê o CSV de população por bairro e faixa etária (colunas bairro, faixa e populacao, separadas por
+/// ponto e vírgula ou vírgula). Linhas de total são ignoradas e linhas repetidas são somadas
+pub fn parse_population_csv(content: &str) -> Result<Vec<NeighborhoodPopulationEntry>, String> {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let header = lines.next().ok_or("the file is empty")?;
+    let delimiter = if header.contains(';') { ';' } else { ',' };
+
+    let headers: Vec<String> = header.trim_start_matches('\u{feff}').split(delimiter).map(fold_header).collect();
+    let column = |names: &[&str]| headers.iter().position(|h| names.contains(&h.as_str()));
+
+    let (Some(bairro_col), Some(faixa_col), Some(populacao_col)) = (
+        column(&NEIGHBORHOOD_HEADERS),
+        column(&AGE_BAND_HEADERS),
+        column(&POPULATION_HEADERS),
+    ) else {
+        return Err("the header must contain the columns bairro, faixa and populacao".to_string());
+    };
+
+    let mut totals: BTreeMap<(String, i32, Option<i32>), i64> = BTreeMap::new();
+
+    for (index, line) in lines.enumerate() {
+        let fields: Vec<&str> = line.split(delimiter).map(|f| f.trim().trim_matches('"')).collect();
+        let field = |col: usize| fields.get(col).copied().unwrap_or("");
+
+        let bairro = neighborhood_key(field(bairro_col));
+        let faixa = field(faixa_col);
+        if bairro.is_empty() || faixa.to_lowercase().starts_with("total") {
+            continue;
+        }
+
+        // Linha 1 é o cabeçalho
+        let (start, end) = parse_age_band(faixa)
+            .ok_or_else(|| format!("line {}: '{}' is not a valid age band", index + 2, faixa))?;
+        let populacao = parse_population(field(populacao_col))
+            .ok_or_else(|| format!("line {}: '{}' is not a valid population", index + 2, field(populacao_col)))?;
+
+        *totals.entry((bairro, start, end)).or_insert(0) += populacao;
+    }
+
+    if totals.is_empty() {
+        return Err("no population rows found".to_string());
+    }
+
+    let entries: Vec<NeighborhoodPopulationEntry> = totals.into_iter()
+        .map(|((bairro, faixa_inicio, faixa_fim), populacao)| NeighborhoodPopulationEntry { bairro, faixa_inicio, faixa_fim, populacao })
+        .collect();
+
+    population_limits(entries.iter().map(|e| (e.faixa_inicio, e.faixa_fim)))?;
+    Ok(entries)
+}
+
+/// Idades iniciais das faixas. Todas as linhas precisam usar as mesmas faixas contíguas, iniciadas em
+/// zero e com a última aberta
+pub fn population_limits(bands: impl Iterator<Item = (i32, Option<i32>)>) -> Result<Vec<i64>, String> {
+    let distinct: BTreeMap<i32, Option<i32>> = bands.fold(BTreeMap::new(), |mut acc, (start, end)| {
+        acc.entry(start).or_insert(end);
+        acc
+    });
+
+    let starts: Vec<i32> = distinct.keys().copied().collect();
+    for (i, (start, end)) in distinct.iter().enumerate() {
+        let expected = starts.get(i + 1).map(|next| next - 1);
+        if *end != expected {
+            return Err(format!(
+                "age band starting at {} does not match the other bands (bands must be contiguous and the last one open, e.g. '100 anos ou mais')",
+                start
+            ));
+        }
+    }
+
+    let limits: Vec<i64> = starts.iter().map(|s| *s as i64).collect();
+    AgeBands::from_limits(&limits)?;
+    Ok(limits)
+}
+
+/// População total de cada bairro
+pub fn neighborhood_totals(populations: &[NeighborhoodPopulation]) -> HashMap<String, i64> {
+    populations.iter().fold(HashMap::new(), |mut totals, p| {
+        *totals.entry(neighborhood_key(&p.bairro)).or_insert(0) += p.populacao;
+        totals
+    })
+}
+
+// Acrescenta a população e a taxa por mil habitantes a cada bairro ({..., bairro: {latitude, longitude, quantidade}})
+fn annotate_rates(value: &Value, key: Option<&str>, totals: &HashMap<String, i64>) -> Value {
+    let Some(map) = value.as_object() else {
+        return value.clone();
+    };
+
+    if map.contains_key("latitude") && map.contains_key("longitude") {
+        let mut entry = map.clone();
+        let populacao = key.and_then(|k| totals.get(&neighborhood_key(k))).copied().filter(|p| *p > 0);
+        let taxa = populacao.and_then(|p| {
+            map.get("quantidade").and_then(Value::as_f64).map(|q| round2(q / p as f64 * PER_CAPITA_BASE))
+        });
+
+        entry.insert("populacao".to_string(), json!(populacao));
+        entry.insert("taxa".to_string(), json!(taxa));
+        return Value::Object(entry);
+    }
+
+    Value::Object(map.iter()
+        .map(|(k, v)| (k.clone(), annotate_rates(v, Some(k), totals)))
+        .collect())
+}
+
+/// Mapas de calor com `populacao` e `taxa` (por mil habitantes) em cada bairro. Bairros sem
+/// população cadastrada ficam com os dois campos nulos
+pub fn with_rates(data: &Value, totals: &HashMap<String, i64>) -> Value {
+    annotate_rates(data, None, totals)
+}
+
+// Taxa bruta e taxa padronizada por idade (método direto, com pesos da população padrão)
+fn rates(cases: &[f64], population: &[i64], weights: &[f64]) -> Value {
+    let casos: f64 = cases.iter().sum();
+    let total: i64 = population.iter().sum();
+
+    let taxa = (total > 0).then(|| round2(casos / total as f64 * PER_CAPITA_BASE));
+    let taxa_padronizada = (total > 0).then(|| {
+        let standardized: f64 = cases.iter().zip(population).zip(weights)
+            .filter(|((_, p), _)| **p > 0)
+            .map(|((c, p), w)| c / *p as f64 * w)
+            .sum();
+        round2(standardized * PER_CAPITA_BASE)
+    });
+
+    json!({
+        "casos": casos.round() as i64,
+        "taxa": taxa,
+        "taxa_padronizada": taxa_padronizada,
+    })
+}
+
+/// Taxas de incidência por bairro, doença e período a partir dos atendimentos armazenados por idade
+/// ({bairro: {doença: {idade: {período: n}}}}). A população padrão da padronização é a soma dos
+/// bairros cadastrados da unidade
+pub fn incidence_rates(data: &Map<String, Value>, populations: &[NeighborhoodPopulation], doenca: Option<&str>) -> Result<Value, String> {
+    let limits = population_limits(populations.iter().map(|p| (p.faixa_inicio, p.faixa_fim)))?;
+    let bands = AgeBands::from_limits(&limits)?;
+    let band_count = limits.len();
+
+    let mut by_neighborhood: HashMap<String, Vec<i64>> = HashMap::new();
+    for p in populations {
+        if let Some(index) = bands.index_of(p.faixa_inicio as i64) {
+            by_neighborhood.entry(neighborhood_key(&p.bairro)).or_insert_with(|| vec![0; band_count])[index] += p.populacao;
+        }
+    }
+
+    let standard: Vec<f64> = (0..band_count)
+        .map(|k| by_neighborhood.values().map(|pops| pops[k]).sum::<i64>() as f64)
+        .collect();
+    let standard_total: f64 = standard.iter().sum();
+    let weights: Vec<f64> = standard.iter().map(|s| if standard_total > 0.0 { s / standard_total } else { 0.0 }).collect();
+
+    let doenca = doenca.map(|d| d.trim().to_lowercase()).filter(|d| !d.is_empty());
+
+    let mut bairros = Map::new();
+    let mut sem_populacao = Vec::new();
+
+    for (bairro, diseases) in data {
+        let Some(population) = by_neighborhood.get(&neighborhood_key(bairro)) else {
+            sem_populacao.push(bairro.clone());
+            continue;
+        };
+
+        let grouped = group_ages_into_bands(diseases.as_object().unwrap_or(&Map::new()), &bands);
+
+        // Casos por faixa de cada doença e período, com a soma das doenças
+        let mut cases: BTreeMap<String, BTreeMap<String, Vec<f64>>> = BTreeMap::new();
+        for (disease, periods) in grouped.as_object().into_iter().flatten() {
+            for (period, values) in periods.as_object().into_iter().flatten() {
+                let values: Vec<f64> = values.as_array().into_iter().flatten().map(|v| v.as_f64().unwrap_or(0.0)).collect();
+
+                let all = cases.entry(ALL_DISEASES.to_string()).or_default()
+                    .entry(period.clone()).or_insert_with(|| vec![0.0; band_count]);
+                for (total, value) in all.iter_mut().zip(&values) {
+                    *total += value;
+                }
+
+                cases.entry(disease.clone()).or_default().insert(period.clone(), values);
+            }
+        }
+
+        let doencas: Map<String, Value> = cases.into_iter()
+            .filter(|(disease, _)| doenca.as_ref().map(|d| disease.to_lowercase() == *d || disease == ALL_DISEASES).unwrap_or(true))
+            .map(|(disease, periods)| {
+                let periods: Map<String, Value> = periods.into_iter()
+                    .map(|(period, values)| (period, rates(&values, population, &weights)))
+                    .collect();
+                (disease, Value::Object(periods))
+            })
+            .collect();
+
+        bairros.insert(bairro.clone(), json!({
+            "populacao": population.iter().sum::<i64>(),
+            "doencas": doencas,
+        }));
+    }
+
+    sem_populacao.sort();
+
+    Ok(json!({
+        "base": PER_CAPITA_BASE,
+        "faixas": bands.labels(),
+        "bairros": bairros,
+        "sem_populacao": sem_populacao,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDateTime;
+
+    fn population(bairro: &str, faixa_inicio: i32, faixa_fim: Option<i32>, populacao: i64) -> NeighborhoodPopulation {
+        NeighborhoodPopulation {
+            ifrounidadeid: 1,
+            bairro: bairro.to_string(),
+            faixa_inicio,
+            faixa_fim,
+            populacao,
+            ano: None,
+            fonte: None,
+            updated_by: None,
+            updated_at: NaiveDateTime::default(),
+        }
+    }
+
+    #[test]
+    fn age_band_labels_from_ibge_tables() {
+        assert_eq!(parse_age_band("0 a 4 anos"), Some((0, Some(4))));
+        assert_eq!(parse_age_band("Menos de 1 ano"), Some((0, Some(0))));
+        assert_eq!(parse_age_band("100 anos ou mais"), Some((100, None)));
+        assert_eq!(parse_age_band("80+"), Some((80, None)));
+        assert_eq!(parse_age_band("5 anos"), Some((5, Some(5))));
+        assert_eq!(parse_age_band("10 a 5 anos"), None);
+        assert_eq!(parse_age_band("idade ignorada"), None);
+    }
+
+    #[test]
+    fn population_csv_sums_repeated_rows_and_skips_totals() {
+        let content = "\u{feff}Bairro;Faixa Etária;População\n\
+            centro;0 a 39 anos;1.200\n\
+            Centro;0 a 39 anos;300\n\
+            CENTRO;40 a 79 anos;-\n\
+            Centro;80+;45\n\
+            Centro;Total;1.545\n";
+
+        let entries = parse_population_csv(content).unwrap();
+
+        let entry = |faixa_inicio, faixa_fim, populacao| NeighborhoodPopulationEntry {
+            bairro: "CENTRO".to_string(),
+            faixa_inicio,
+            faixa_fim,
+            populacao,
+        };
+        assert_eq!(entries, vec![entry(0, Some(39), 1500), entry(40, Some(79), 0), entry(80, None, 45)]);
+    }
+
+    #[test]
+    fn population_csv_rejects_malformed_files() {
+        assert!(parse_population_csv("").is_err());
+        assert!(parse_population_csv("nome,idade,quantidade\nCentro,0 a 4,10\n").unwrap_err().contains("bairro, faixa and populacao"));
+        assert!(parse_population_csv("bairro,faixa,populacao\nCentro,jovens,10\n").unwrap_err().starts_with("line 2"));
+        assert!(parse_population_csv("bairro,faixa,populacao\nCentro,0 a 4,dez\n").unwrap_err().starts_with("line 2"));
+        assert_eq!(parse_population_csv("bairro,faixa,populacao\nCentro,Total,10\n").unwrap_err(), "no population rows found");
+    }
+
+    #[test]
+    fn population_limits_require_contiguous_bands_with_the_last_open() {
+        let bands = [(0, Some(39)), (40, Some(79)), (80, None), (0, Some(39))];
+        assert_eq!(population_limits(bands.into_iter()).unwrap(), vec![0, 40, 80]);
+
+        // Lacuna entre 39 e 50, última faixa fechada e início acima de zero
+        assert!(population_limits([(0, Some(39)), (50, None)].into_iter()).is_err());
+        assert!(population_limits([(0, Some(39)), (40, Some(79))].into_iter()).is_err());
+        assert!(population_limits([(5, Some(39)), (40, None)].into_iter()).is_err());
+    }
+
+    #[test]
+    fn incidence_rates_standardize_by_the_unit_population() {
+        let populations = [
+            population("Centro", 0, Some(39), 1000),
+            population("Centro", 40, None, 1000),
+            population("Setor 9", 0, Some(39), 3000),
+            population("Setor 9", 40, None, 1000),
+            population("Vazio", 0, Some(39), 0),
+            population("Vazio", 40, None, 0),
+        ];
+        let data = json!({
+            "CENTRO": {"dengue": {"10": {"todos": 10}, "50": {"todos": 20}}},
+            "VAZIO": {"dengue": {"30": {"todos": 3}}},
+            "RURAL": {"dengue": {"30": {"todos": 4}}},
+        });
+
+        let result = incidence_rates(data.as_object().unwrap(), &populations, None).unwrap();
+
+        // Pesos da população padrão: 4000 / 6000 e 2000 / 6000
+        // Taxa padronizada: (10 / 1000 * 2/3 + 20 / 1000 * 1/3) * 1000 = 13,33
+        let centro = &result["bairros"]["CENTRO"];
+        assert_eq!(centro["populacao"], json!(2000));
+        assert_eq!(centro["doencas"]["dengue"]["todos"], json!({"casos": 30, "taxa": 15.0, "taxa_padronizada": 13.33}));
+        assert_eq!(centro["doencas"][ALL_DISEASES]["todos"], centro["doencas"]["dengue"]["todos"]);
+
+        let vazio = &result["bairros"]["VAZIO"]["doencas"]["dengue"]["todos"];
+        assert_eq!(vazio, &json!({"casos": 3, "taxa": null, "taxa_padronizada": null}));
+
+        assert_eq!(result["sem_populacao"], json!(["RURAL"]));
+        assert_eq!(result["faixas"], json!(["0 a 39", "40 ou mais"]));
+    }
+
+    #[test]
+    fn incidence_rates_filter_the_requested_disease() {
+        let populations = [population("Centro", 0, None, 1000)];
+        let data = json!({"CENTRO": {"dengue": {"10": {"todos": 2}}, "gripe": {"10": {"todos": 5}}}});
+
+        let result = incidence_rates(data.as_object().unwrap(), &populations, Some(" Dengue ")).unwrap();
+
+        let doencas = result["bairros"]["CENTRO"]["doencas"].as_object().unwrap();
+        assert_eq!(doencas.keys().collect::<Vec<_>>(), vec!["dengue", ALL_DISEASES]);
+        assert_eq!(doencas[ALL_DISEASES]["todos"]["casos"], json!(7));
+    }
+}
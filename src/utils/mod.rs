@@ -5,3 +5,19 @@ pub mod validators;
 pub mod process_data;
 pub mod graph_data_processing;
 pub mod feedbacks_processing;
+pub mod competencia;
+pub mod graph_slices;
+pub mod time_series;
+pub mod surveillance;
+pub mod forecast;
+pub mod staffing;
+pub mod age_bands;
+pub mod service_time;
+pub mod period_comparison;
+pub mod geojson;
+pub mod hotspots;
+pub mod incidence;
+pub mod small_cells;
+pub mod procedures;
+pub mod municipalities;
+pub mod flow_funnel;
\ No newline at end of file
@@ -0,0 +1,116 @@
+use lazy_static::lazy_static;
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, HashMap};
+
+/// Município da tabela do IBGE distribuída com a API
+pub struct Municipality {
+    pub codigo: String,
+    pub nome: String,
+    pub uf: String,
+}
+
+// Tabela "codigo_ibge,nome,uf" com os códigos de 7 dígitos. Novas UFs podem ser acrescentadas ao arquivo
+const MUNICIPALITIES_CSV: &str = include_str!("../../data/ibge_municipios.csv");
+
+lazy_static! {
+    // Municípios pelo código sem o dígito verificador, como parte dos sistemas do SUS informa
+    static ref MUNICIPALITIES: HashMap<String, Municipality> = MUNICIPALITIES_CSV.lines()
+        .skip(1)
+        .filter_map(|line| {
+            let mut fields = line.splitn(3, ',');
+            let codigo = fields.next()?.trim();
+            let nome = fields.next()?.trim();
+            let uf = fields.next()?.trim();
+            (codigo.len() == 7).then(|| (codigo[..6].to_string(), Municipality {
+                codigo: codigo.to_string(),
+                nome: nome.to_string(),
+                uf: uf.to_string(),
+            }))
+        })
+        .collect();
+}
+
+/// Busca o município pelo código do IBGE com 6 ou 7 dígitos
+pub fn find_municipality(codigo: &str) -> Option<&'static Municipality> {
+    codigo.get(..6).and_then(|prefix| MUNICIPALITIES.get(prefix))
+}
+
+/// Código de 7 dígitos, com o dígito verificador do IBGE calculado para os municípios fora da tabela
+pub fn full_ibge_code(codigo: &str) -> String {
+    if let Some(municipality) = find_municipality(codigo) {
+        return municipality.codigo.clone();
+    }
+    if codigo.len() != 6 || !codigo.chars().all(|c| c.is_ascii_digit()) {
+        return codigo.to_string();
+    }
+
+    // Pesos 1 e 2 alternados, somando os algarismos dos produtos
+    let sum: u32 = codigo.chars()
+        .filter_map(|c| c.to_digit(10))
+        .enumerate()
+        .map(|(i, digit)| {
+            let product = digit * if i % 2 == 0 { 1 } else { 2 };
+            product / 10 + product % 10
+        })
+        .sum();
+
+    format!("{}{}", codigo, (10 - sum % 10) % 10)
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+// Contagens inteiras sem casas decimais; valores normalizados mantêm as casas
+fn count_value(value: f64) -> Value {
+    if value.fract() == 0.0 {
+        json!(value as i64)
+    } else {
+        json!(round2(value))
+    }
+}
+
+/// Atendimentos por município de residência em cada período, a partir dos dados armazenados
+/// ({código: {período: n}}), do maior para o menor, com nome e UF da tabela do IBGE (nulos para
+/// códigos fora da tabela) e a participação sobre os atendimentos com município informado
+pub fn attendances_by_municipality(data: &Map<String, Value>) -> Value {
+    let mut periods: BTreeMap<String, Vec<(&String, f64)>> = BTreeMap::new();
+
+    for (codigo, counts) in data {
+        for (period, count) in counts.as_object().into_iter().flatten() {
+            let quantidade = count.as_f64().unwrap_or(0.0);
+            if quantidade > 0.0 {
+                periods.entry(period.clone()).or_default().push((codigo, quantidade));
+            }
+        }
+    }
+
+    let periods: Map<String, Value> = periods.into_iter()
+        .map(|(period, mut municipalities)| {
+            let total: f64 = municipalities.iter().map(|(_, quantidade)| quantidade).sum();
+
+            // Maiores quantidades primeiro; empates pelo código
+            municipalities.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+            let municipios: Vec<Value> = municipalities.iter()
+                .map(|(codigo, quantidade)| {
+                    let municipality = find_municipality(codigo);
+                    json!({
+                        "codigo": full_ibge_code(codigo),
+                        "nome": municipality.map(|m| m.nome.as_str()),
+                        "uf": municipality.map(|m| m.uf.as_str()),
+                        "quantidade": count_value(*quantidade),
+                        "participacao": round2(quantidade / total * 100.0),
+                    })
+                })
+                .collect();
+
+            (period, json!({
+                "total": count_value(total),
+                "municipios": municipios,
+            }))
+        })
+        .collect();
+
+    Value::Object(periods)
+}
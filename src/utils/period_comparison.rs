@@ -0,0 +1,240 @@
+use serde_json::{json, Map, Value};
+
+use crate::utils::competencia::parse_competencia;
+use crate::AppError;
+
+/// Compara cada competência com a anterior e o total do período com o período anterior de mesma duração
+pub const COMPARE_PREVIOUS_PERIOD: &str = "previous_period";
+/// Compara cada competência (e o total do período) com o mesmo intervalo do ano anterior
+pub const COMPARE_PREVIOUS_YEAR: &str = "previous_year";
+
+// Campos que identificam a localização e não são comparados
+const NON_COMPARABLE_KEYS: [&str; 2] = ["latitude", "longitude"];
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum ComparisonMode {
+    PreviousPeriod,
+    PreviousYear,
+}
+
+impl ComparisonMode {
+    pub fn from_query(compare: Option<&str>) -> Result<Option<Self>, AppError> {
+        match compare.map(|c| c.trim().to_lowercase()).as_deref() {
+            None | Some("") => Ok(None),
+            Some(COMPARE_PREVIOUS_PERIOD) => Ok(Some(Self::PreviousPeriod)),
+            Some(COMPARE_PREVIOUS_YEAR) => Ok(Some(Self::PreviousYear)),
+            Some(other) => Err(AppError::BadRequest(format!(
+                "Error: '{}' is not a valid value for 'compare'. Allowed values are: {}, {}",
+                other, COMPARE_PREVIOUS_PERIOD, COMPARE_PREVIOUS_YEAR
+            ))),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::PreviousPeriod => COMPARE_PREVIOUS_PERIOD,
+            Self::PreviousYear => COMPARE_PREVIOUS_YEAR,
+        }
+    }
+
+    /// Competência de comparação de uma competência
+    pub fn reference_competencia(&self, competencia: (i32, u32)) -> (i32, u32) {
+        match self {
+            Self::PreviousPeriod => shift_months(competencia, -1),
+            Self::PreviousYear => (competencia.0 - 1, competencia.1),
+        }
+    }
+
+    /// Período de comparação de um período fechado (início e fim informados)
+    pub fn reference_window(&self, (start, end): ((i32, u32), (i32, u32))) -> ((i32, u32), (i32, u32)) {
+        match self {
+            Self::PreviousPeriod => {
+                let length = months_between(start, end) + 1;
+                (shift_months(start, -length), shift_months(end, -length))
+            },
+            Self::PreviousYear => ((start.0 - 1, start.1), (end.0 - 1, end.1)),
+        }
+    }
+}
+
+/// Soma (ou subtrai) meses de uma competência
+pub fn shift_months((year, month): (i32, u32), months: i32) -> (i32, u32) {
+    let index = year * 12 + month as i32 - 1 + months;
+    (index.div_euclid(12), index.rem_euclid(12) as u32 + 1)
+}
+
+/// Quantidade de meses de `start` até `end`
+pub fn months_between(start: (i32, u32), end: (i32, u32)) -> i32 {
+    (end.0 - start.0) * 12 + end.1 as i32 - start.1 as i32
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+// Valor com a comparação, a variação absoluta e a percentual (nula quando a comparação é zero).
+// Contagens inteiras permanecem inteiras
+fn compared_number(value: &Value, reference: Option<&Value>) -> Value {
+    let Some(reference) = reference else {
+        return json!({ "valor": value, "comparacao": null, "variacao": null, "variacao_percentual": null });
+    };
+
+    let (current, previous) = (value.as_f64().unwrap_or(0.0), reference.as_f64().unwrap_or(0.0));
+    let variacao = match (value.as_i64(), reference.as_i64()) {
+        (Some(a), Some(b)) => json!(a - b),
+        _ => json!(round2(current - previous)),
+    };
+
+    json!({
+        "valor": value,
+        "comparacao": reference,
+        "variacao": variacao,
+        "variacao_percentual": (previous != 0.0).then(|| round2((current - previous) / previous * 100.0)),
+    })
+}
+
+// Anota os números de `value` com os valores do mesmo caminho em `reference`. Com a referência
+// disponível, valores ausentes nela contam como zero
+fn annotate(value: &Value, reference: Option<&Value>, key: Option<&str>) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(map.iter()
+            .map(|(k, v)| {
+                let reference = reference.map(|r| r.get(k).unwrap_or(&Value::Null));
+                (k.clone(), annotate(v, reference, Some(k)))
+            })
+            .collect()),
+        Value::Array(items) => Value::Array(items.iter().enumerate()
+            .map(|(i, v)| annotate(v, reference.map(|r| r.get(i).unwrap_or(&Value::Null)), key))
+            .collect()),
+        Value::Number(_) if !key.map(|k| NON_COMPARABLE_KEYS.contains(&k)).unwrap_or(false) => {
+            let zero = json!(0);
+            compared_number(value, reference.map(|r| if r.is_number() { r } else { &zero }))
+        },
+        _ => value.clone(),
+    }
+}
+
+// Valor da competência procurada entre as chaves do objeto ("AAAA-M" ou "AAAA-MM")
+fn find_competencia(map: &Map<String, Value>, target: (i32, u32)) -> Option<&Value> {
+    map.iter()
+        .find(|(key, _)| parse_competencia(key) == Some(target))
+        .map(|(_, value)| value)
+}
+
+fn compare_object(
+    current: &Map<String, Value>,
+    all: Option<&Map<String, Value>>,
+    previous_window: Option<&Map<String, Value>>,
+    mode: ComparisonMode,
+) -> Value {
+    let mut result = Map::new();
+
+    for (key, value) in current {
+        let compared = if key == "todos" {
+            // O total só é comparado quando o período de comparação foi calculado
+            annotate(value, previous_window.map(|w| w.get(key).unwrap_or(&Value::Null)), Some(key))
+        } else if let Some(competencia) = parse_competencia(key) {
+            let reference = all.and_then(|a| find_competencia(a, mode.reference_competencia(competencia)));
+            annotate(value, reference, Some(key))
+        } else {
+            match value.as_object() {
+                Some(map) => compare_object(
+                    map,
+                    all.and_then(|a| a.get(key)).and_then(Value::as_object),
+                    previous_window.and_then(|w| w.get(key)).and_then(Value::as_object),
+                    mode,
+                ),
+                None => value.clone(),
+            }
+        };
+
+        result.insert(key.clone(), compared);
+    }
+
+    Value::Object(result)
+}
+
+/// Acrescenta a comparação aos valores de um gráfico organizado por competência.
+/// `all` contém todas as competências armazenadas (para a comparação de cada competência)
+/// e `previous_window`, quando informado, os dados do período de comparação (para os totais)
+pub fn with_comparison(
+    current: &Map<String, Value>,
+    all: &Map<String, Value>,
+    previous_window: Option<&Map<String, Value>>,
+    mode: ComparisonMode,
+) -> Value {
+    compare_object(current, Some(all), previous_window, mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(value: Value) -> Map<String, Value> {
+        value.as_object().cloned().unwrap()
+    }
+
+    #[test]
+    fn months_are_shifted_across_years() {
+        assert_eq!(shift_months((2024, 1), -1), (2023, 12));
+        assert_eq!(shift_months((2024, 12), 1), (2025, 1));
+        assert_eq!(shift_months((2024, 3), -15), (2022, 12));
+        assert_eq!(months_between((2023, 11), (2024, 2)), 3);
+    }
+
+    #[test]
+    fn reference_competencia_of_each_mode() {
+        assert_eq!(ComparisonMode::PreviousPeriod.reference_competencia((2024, 1)), (2023, 12));
+        assert_eq!(ComparisonMode::PreviousYear.reference_competencia((2024, 1)), (2023, 1));
+    }
+
+    #[test]
+    fn previous_period_has_the_same_length_right_before_the_window() {
+        let window = ((2024, 2), (2024, 4));
+
+        assert_eq!(ComparisonMode::PreviousPeriod.reference_window(window), ((2023, 11), (2024, 1)));
+        assert_eq!(ComparisonMode::PreviousYear.reference_window(window), ((2023, 2), (2023, 4)));
+        assert_eq!(ComparisonMode::PreviousPeriod.reference_window(((2024, 1), (2024, 1))), ((2023, 12), (2023, 12)));
+    }
+
+    #[test]
+    fn compare_parameter_is_validated() {
+        assert!(ComparisonMode::from_query(None).unwrap().is_none());
+        assert!(ComparisonMode::from_query(Some(" ")).unwrap().is_none());
+        assert!(ComparisonMode::from_query(Some("Previous_Year")).unwrap() == Some(ComparisonMode::PreviousYear));
+        assert!(ComparisonMode::from_query(Some("last_month")).is_err());
+    }
+
+    #[test]
+    fn competencias_are_compared_with_the_aligned_reference() {
+        // Chaves com e sem zero à esquerda no mês são alinhadas
+        let all = object(json!({"2023-03": 8, "2024-2": 10, "2024-3": 15}));
+        let current = object(json!({"2024-3": 15, "2024-2": 10}));
+
+        let previous_period = with_comparison(&current, &all, None, ComparisonMode::PreviousPeriod);
+        assert_eq!(previous_period["2024-3"], json!({"valor": 15, "comparacao": 10, "variacao": 5, "variacao_percentual": 50.0}));
+        assert_eq!(previous_period["2024-2"], json!({"valor": 10, "comparacao": null, "variacao": null, "variacao_percentual": null}));
+
+        let previous_year = with_comparison(&current, &all, None, ComparisonMode::PreviousYear);
+        assert_eq!(previous_year["2024-3"], json!({"valor": 15, "comparacao": 8, "variacao": 7, "variacao_percentual": 87.5}));
+    }
+
+    #[test]
+    fn totals_use_the_previous_window_and_missing_values_count_as_zero() {
+        let current = object(json!({"CENTRO": {"todos": {"quantidade": 6, "latitude": -8.76}, "2024-2": {"quantidade": 4}}}));
+        let all = object(json!({"CENTRO": {"2024-1": {}}}));
+        let previous_window = object(json!({"CENTRO": {"todos": {"quantidade": 3}}}));
+
+        let compared = with_comparison(&current, &all, Some(&previous_window), ComparisonMode::PreviousPeriod);
+
+        assert_eq!(compared["CENTRO"]["todos"], json!({
+            "quantidade": {"valor": 6, "comparacao": 3, "variacao": 3, "variacao_percentual": 100.0},
+            "latitude": -8.76,
+        }));
+        assert_eq!(compared["CENTRO"]["2024-2"]["quantidade"], json!({"valor": 4, "comparacao": 0, "variacao": 4, "variacao_percentual": null}));
+
+        // Sem o período de comparação, o total não é comparado
+        let compared = with_comparison(&current, &all, None, ComparisonMode::PreviousPeriod);
+        assert_eq!(compared["CENTRO"]["todos"]["quantidade"]["comparacao"], Value::Null);
+    }
+}
@@ -0,0 +1,88 @@
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use crate::domain::models::visualization_data_graph::ProcedureRankingQuery;
+use crate::AppError;
+
+pub const DEFAULT_TOP_PROCEDURES: usize = 10;
+
+const MAX_TOP_PROCEDURES: usize = 100;
+
+// Chave com o nome do procedimento nos dados armazenados
+const NAME_KEY: &str = "nome";
+
+// Código, nome e quantidade de um procedimento no período
+type RankedProcedure<'a> = (&'a String, Option<&'a Value>, f64);
+
+/// Quantidade de procedimentos do ranking informada na consulta
+pub fn top_from_query(query: &ProcedureRankingQuery) -> Result<usize, AppError> {
+    match query.top {
+        None => Ok(DEFAULT_TOP_PROCEDURES),
+        Some(top) if (1..=MAX_TOP_PROCEDURES).contains(&top) => Ok(top),
+        Some(top) => Err(AppError::BadRequest(format!(
+            "Error: '{}' is not a valid value for 'top'. Expected a number between 1 and {}",
+            top, MAX_TOP_PROCEDURES
+        ))),
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+// Contagens inteiras sem casas decimais; valores normalizados mantêm as casas
+fn count_value(value: f64) -> Value {
+    if value.fract() == 0.0 {
+        json!(value as i64)
+    } else {
+        json!(round2(value))
+    }
+}
+
+/// Os `top` procedimentos mais realizados em cada período, a partir dos dados armazenados
+/// ({código: {nome, período: n}}). A participação é o percentual sobre todos os procedimentos
+/// do período, não apenas os listados
+pub fn procedure_ranking(data: &Map<String, Value>, top: usize) -> Value {
+    let mut periods: BTreeMap<String, Vec<RankedProcedure>> = BTreeMap::new();
+
+    for (codigo, procedure) in data {
+        let Some(procedure) = procedure.as_object() else {
+            continue;
+        };
+
+        for (period, count) in procedure.iter().filter(|(key, _)| key.as_str() != NAME_KEY) {
+            let quantidade = count.as_f64().unwrap_or(0.0);
+            if quantidade > 0.0 {
+                periods.entry(period.clone()).or_default().push((codigo, procedure.get(NAME_KEY), quantidade));
+            }
+        }
+    }
+
+    let periods: Map<String, Value> = periods.into_iter()
+        .map(|(period, mut procedures)| {
+            let total: f64 = procedures.iter().map(|(_, _, quantidade)| quantidade).sum();
+
+            // Maiores quantidades primeiro; empates pelo código
+            procedures.sort_by(|a, b| b.2.total_cmp(&a.2).then_with(|| a.0.cmp(b.0)));
+
+            let ranking: Vec<Value> = procedures.iter().take(top)
+                .map(|(codigo, nome, quantidade)| json!({
+                    "codigo": codigo,
+                    "nome": nome,
+                    "quantidade": count_value(*quantidade),
+                    "participacao": round2(quantidade / total * 100.0),
+                }))
+                .collect();
+
+            (period, json!({
+                "total": count_value(total),
+                "procedimentos": ranking,
+            }))
+        })
+        .collect();
+
+    json!({
+        "top": top,
+        "periodos": periods,
+    })
+}
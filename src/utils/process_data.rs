@@ -4,6 +4,8 @@ use polars::prelude::*;
 use polars::lazy::dsl::{col, lit, when};
 use polars::datatypes::DataType;
 use regex::Regex;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use linfa::prelude::*;
@@ -15,23 +17,18 @@ use rust_stemmers::{Algorithm, Stemmer};
 /// Retorna os nomes das colunas a serem removidas
 pub fn columns_names() -> Vec<&'static str> {
     vec![
-        "IfroId",
         "IfroTabelaId",
         "IfroOrigem",
         "IfroMunicipioId",
         "IfroAlocacaoId",
-        "IfroMunicipioIBGE",
         "IfroUnidadeCNES",
         "IfroUnidadeCNPJ",
         "IfroProfissionalCBOCd",
-        "IfroProcedimentoId",
         "IfroProfissionalCNS",
-        "IfroProcedimentoSUSCd",
         "IfroPacienteId",
         "IfroPacienteCNS",
         "IfroPacienteCNSTipo",
         "IfroPacienteCNSValido",
-        "IfroPacienteSexoCd",
         "IfroPacienteRacaCorCd",
         "IfroPacienteEtniaCd",
         "IfroPacienteNacionalidadeCd",
@@ -53,6 +50,17 @@ pub fn remove_unnecessary_columns(df: DataFrame, columns_to_remove: &[&str]) ->
 
 
 
+/// Separa o texto original da conduta e a confiança da classificação, que são mantidos na tabela auxiliar de acesso restrito
+pub fn extract_conduta_records(df: &DataFrame) -> PolarsResult<DataFrame> {
+    let required_columns = ["IfroId", "IfroCompetencia", "IfroUnidadeId", "IfroConsultaConduta", "IfroClassificacaoConfianca"];
+    verify_required_columns(df, &required_columns)?;
+
+    df.select(required_columns)?
+        .lazy()
+        .select([all().cast(DataType::String).fill_null(lit(""))])
+        .collect()
+}
+
 /// Verifica se as colunas necessárias existem no DataFrame
 fn verify_required_columns(df: &DataFrame, columns: &[&str]) -> PolarsResult<()> {
     for col in columns {
@@ -90,6 +98,54 @@ fn add_competencia_column(df: &mut DataFrame) -> PolarsResult<()> {
     Ok(())
 }
 
+/// Adiciona a chave pseudônima da visita (HMAC-SHA256 da unidade, do paciente e da data do atendimento com
+/// o segredo `VISIT_KEY_SECRET`), que liga os registros de uma mesma visita entre os fluxos depois que o
+/// identificador do paciente é removido. Sem o segredo, a chave não pode ser recalculada a partir do paciente.
+/// Arquivos sem `IfroPacienteId` seguem sem a chave
+pub fn add_visit_key_column(mut df: DataFrame, secret: &str) -> PolarsResult<DataFrame> {
+    if verify_required_columns(&df, &["IfroUnidadeId", "IfroPacienteId", "IfroDataAtendimento"]).is_err() {
+        return Ok(df);
+    }
+
+    let keyed_mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .map_err(|e| PolarsError::ComputeError(format!("Segredo da chave de visita inválido: {}", e).into()))?;
+
+    let unidade = df.column("IfroUnidadeId")?.cast(&DataType::String)?;
+    let paciente = df.column("IfroPacienteId")?.cast(&DataType::String)?;
+    let data = df.column("IfroDataAtendimento")?.cast(&DataType::String)?;
+
+    let mut visit_keys: Vec<Option<String>> = Vec::with_capacity(df.height());
+    for i in 0..df.height() {
+        let unidade_val = match unidade.get(i) {
+            Ok(AnyValue::String(s)) => s.trim().to_string(),
+            _ => String::new(),
+        };
+
+        let paciente_val = match paciente.get(i) {
+            Ok(AnyValue::String(s)) => s.trim().to_string(),
+            _ => String::new(),
+        };
+
+        // Apenas a data, antes da separação da hora
+        let data_val = match data.get(i) {
+            Ok(AnyValue::String(s)) => s.split_whitespace().next().unwrap_or("").to_string(),
+            _ => String::new(),
+        };
+
+        if paciente_val.is_empty() || data_val.is_empty() {
+            visit_keys.push(None);
+        } else {
+            let mut mac = keyed_mac.clone();
+            mac.update(format!("{}|{}|{}", unidade_val, paciente_val, data_val).as_bytes());
+            let digest = mac.finalize().into_bytes();
+            visit_keys.push(Some(digest.iter().map(|byte| format!("{:02x}", byte)).collect()));
+        }
+    }
+
+    df.with_column(Series::new("IfroVisitaChave".into(), visit_keys))?;
+    Ok(df)
+}
+
 /// Separa data e hora da coluna IfroDataAtendimento
 fn split_date_and_time(lf: LazyFrame) -> LazyFrame {
     lf.with_columns([
@@ -680,6 +736,9 @@ pub fn extract_keyword_hybrid(df: &DataFrame) -> PolarsResult<DataFrame> {
     // Vetor para armazenar as queixas diagnosticadas
     let mut queixas = Vec::with_capacity(condutas.len());
 
+    // Pontuação do diagnóstico por sintomas, usada como confiança da classificação (0 a 100)
+    let mut confiancas = Vec::with_capacity(condutas.len());
+
     // Coleta índices e condutas que precisam do K-means
     let mut condutas_kmeans = Vec::new();
     let mut indices_kmeans = Vec::new();
@@ -688,11 +747,13 @@ pub fn extract_keyword_hybrid(df: &DataFrame) -> PolarsResult<DataFrame> {
     for (i, conduta) in condutas.iter().enumerate() {
         if is_not_specified(conduta) {
             queixas.push("não especificado".to_string());
+            confiancas.push(0.0);
             continue;
         }
 
         // Tenta diagnosticar com base nos sintomas
         let (doenca, pontuacao) = diagnosticar_doenca_com_pontuacao(conduta);
+        confiancas.push(pontuacao.clamp(0.0, 100.0));
         let doenca_normalizada = apply_decision_rules(conduta, &doenca);
         if pontuacao >= 50.0 {
             // Alta confiança: usar o diagnóstico diretamente
@@ -838,17 +899,42 @@ pub fn extract_keyword_hybrid(df: &DataFrame) -> PolarsResult<DataFrame> {
         }
     }
 
-    // Adiciona a coluna ao DataFrame
+    // Adiciona as colunas ao DataFrame
     let queixas_series = Series::new("IfroPacienteQueixaPrincipal".into(), queixas);
     df_result.with_column(queixas_series)?;
 
+    let confiancas_series = Series::new("IfroClassificacaoConfianca".into(), confiancas);
+    df_result.with_column(confiancas_series)?;
+
     Ok(df_result)
 }
 
 
 
-/// Função para diagnosticar_doenca_com_pontuacao com detecção mais precisa
+/// Classifica uma lista de condutas (já normalizadas) com o mesmo classificador da importação,
+/// retornando a queixa principal e a confiança de cada uma
+pub fn classify_condutas(condutas: Vec<String>) -> PolarsResult<Vec<(String, f64)>> {
+    let df = DataFrame::new(vec![Series::new("IfroConsultaConduta".into(), condutas).into()])?;
+    let df_classificado = extract_keyword_hybrid(&df)?;
+
+    let queixas = df_classificado.column("IfroPacienteQueixaPrincipal")?.str()?;
+    let confiancas = df_classificado.column("IfroClassificacaoConfianca")?.f64()?;
+
+    let resultado = queixas
+        .into_iter()
+        .zip(confiancas)
+        .map(|(queixa, confianca)| (
+            queixa.unwrap_or("não especificado").to_string(),
+            confianca.unwrap_or(0.0),
+        ))
+        .collect();
+
+    Ok(resultado)
+}
+
+
 
+/// Função para diagnosticar_doenca_com_pontuacao com detecção mais precisa
 fn diagnosticar_doenca_com_pontuacao(sintomas: &str) -> (String, f64) {
     let sintomas_normalizados = sintomas.to_lowercase();
 
@@ -1004,6 +1090,30 @@ pub fn get_unique_values(df: &DataFrame, column_name: &str) -> PolarsResult<Vec<
     Ok(unique_values)
 }
 
+/// Obtém as competências distintas de cada unidade presentes no DataFrame
+pub fn get_competencias_by_unit(df: &DataFrame) -> PolarsResult<HashMap<i32, Vec<String>>> {
+    verify_required_columns(df, &["ifrounidadeid", "ifrocompetencia"])?;
+
+    let unidades = df.column("ifrounidadeid")?.cast(&DataType::String)?;
+    let competencias = df.column("ifrocompetencia")?.cast(&DataType::String)?;
+
+    let mut unique_pairs: HashSet<(i32, String)> = HashSet::new();
+    for (unidade, competencia) in unidades.str()?.into_iter().zip(competencias.str()?) {
+        if let (Some(unidade), Some(competencia)) = (unidade, competencia) {
+            if let Ok(unidade_id) = unidade.trim().parse::<i32>() {
+                unique_pairs.insert((unidade_id, competencia.to_string()));
+            }
+        }
+    }
+
+    let mut result: HashMap<i32, Vec<String>> = HashMap::new();
+    for (unidade_id, competencia) in unique_pairs {
+        result.entry(unidade_id).or_default().push(competencia);
+    }
+
+    Ok(result)
+}
+
 // Função para ler um DataFrame a partir de um arquivo CSV
 pub fn read_df_with_read_csv_options(path: &str) -> PolarsResult<DataFrame> {
     CsvReadOptions::default()
@@ -1077,7 +1187,7 @@ pub fn convert_keys_to_str(data: Value) -> Value {
         },
         Value::Array(arr) => {
             let new_arr = arr.into_iter()
-                .map(|item| convert_keys_to_str(item))
+                .map(convert_keys_to_str)
                 .collect();
             Value::Array(new_arr)
         },
@@ -1,12 +1,13 @@
 use actix_web::{HttpResponse, http::StatusCode};
 use serde::Serialize;
-use serde_json::json;
+use serde_json::{json, Value};
 
 #[derive(Debug)]
 pub struct ApiResponse<T: Serialize> {
     message: String,
     status_code: StatusCode,
     data: Option<T>,
+    meta: Option<Value>,
 }
 
 impl<T: Serialize> ApiResponse<T> {
@@ -15,6 +16,7 @@ impl<T: Serialize> ApiResponse<T> {
             message,
             status_code,
             data,
+            meta: None,
         }
     }
 
@@ -23,6 +25,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "Operation successful".to_string(),
             status_code: StatusCode::OK,
             data: Some(data),
+            meta: None,
         }
     }
 
@@ -31,6 +34,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "User not found".to_string(),
             status_code: StatusCode::NOT_FOUND,
             data: None,
+            meta: None,
         }
     }
 
@@ -39,6 +43,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "Users not found".to_string(),
             status_code: StatusCode::NOT_FOUND,
             data: None,
+            meta: None,
         }
     }
 
@@ -47,6 +52,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "Feedbacks not found".to_string(),
             status_code: StatusCode::NOT_FOUND,
             data: None,
+            meta: None,
         }
     }
 
@@ -55,6 +61,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "Resource created successfully".to_string(),
             status_code: StatusCode::CREATED,
             data: Some(data),
+            meta: None,
         }
     }
 
@@ -63,6 +70,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "Resource updated successfully".to_string(),
             status_code: StatusCode::OK,
             data: Some(data),
+            meta: None,
         }
     }
 
@@ -71,6 +79,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "Resource updated successfully".to_string(),
             status_code: StatusCode::OK,
             data: None,
+            meta: None,
         }
     }
 
@@ -79,6 +88,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "Resource updated successfully".to_string(),
             status_code: StatusCode::OK,
             data: None,
+            meta: None,
         }
     }
 
@@ -87,6 +97,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "Resource deleted successfully".to_string(),
             status_code: StatusCode::OK,
             data: None,
+            meta: None,
         }
     }
 
@@ -95,6 +106,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "Verification code resent successfully".to_string(),
             status_code: StatusCode::OK,
             data: None,
+            meta: None,
         }
     }
 
@@ -103,6 +115,7 @@ impl<T: Serialize> ApiResponse<T> {
             message: "Application not found".to_string(),
             status_code: StatusCode::NOT_FOUND,
             data: None,
+            meta: None,
         }
     }
 
@@ -111,16 +124,28 @@ impl<T: Serialize> ApiResponse<T> {
             message: "code not found".to_string(),
             status_code: StatusCode::NOT_FOUND,
             data: None,
+            meta: None,
         }
     }
 
+    // Informações adicionais sobre os dados, como a supressão de células pequenas
+    pub fn with_meta(mut self, meta: Value) -> Self {
+        self.meta = Some(meta);
+        self
+    }
+
     pub fn into_response(self) -> HttpResponse {
-        HttpResponse::build(self.status_code)
-            .json(json!({
-                "message": self.message,
-                "status": self.status_code.as_u16(),
-                "data": self.data
-            }))
+        let mut body = json!({
+            "message": self.message,
+            "status": self.status_code.as_u16(),
+            "data": self.data
+        });
+
+        if let Some(meta) = self.meta {
+            body["meta"] = meta;
+        }
+
+        HttpResponse::build(self.status_code).json(body)
     }
 
     
@@ -0,0 +1,338 @@
+use serde_json::{json, Map, Value};
+use std::collections::BTreeMap;
+
+use crate::domain::models::service_time::{
+    DurationSummary,
+    HistogramBin,
+    ServiceTimeQuery,
+    OUTLIERS_IQR,
+    OUTLIERS_NONE,
+    OUTLIERS_PERCENTILE,
+    VIEW_HISTOGRAM,
+    VIEW_STATS,
+};
+use crate::AppError;
+
+pub const DEFAULT_CUTOFF_PERCENTILE: f64 = 99.0;
+pub const DEFAULT_BIN_WIDTH: u32 = 5;
+
+const IQR_MULTIPLIER: f64 = 1.5;
+const MAX_BIN_WIDTH: u32 = 240;
+
+/// Quantidade de atendimentos por duração, em minutos inteiros
+pub type DurationHistogram = BTreeMap<u32, i64>;
+
+pub enum OutlierMethod {
+    Iqr,
+    Percentile(f64),
+    None,
+}
+
+/// Regra de remoção de valores extremos, aplicada separadamente a cada médico e período
+pub struct OutlierRule {
+    pub method: OutlierMethod,
+    pub max_minutes: Option<u32>,
+}
+
+impl OutlierRule {
+    pub fn from_query(query: &ServiceTimeQuery) -> Result<Self, AppError> {
+        let method = query.outliers.as_deref().map(|m| m.trim().to_lowercase())
+            .unwrap_or_else(|| OUTLIERS_IQR.to_string());
+
+        let method = match method.as_str() {
+            OUTLIERS_IQR => OutlierMethod::Iqr,
+            OUTLIERS_NONE => OutlierMethod::None,
+            OUTLIERS_PERCENTILE => {
+                let percentile = query.percentil_corte.unwrap_or(DEFAULT_CUTOFF_PERCENTILE);
+                if !(50.0..=100.0).contains(&percentile) {
+                    return Err(AppError::BadRequest(format!(
+                        "Error: '{}' is not a valid value for 'percentil_corte'. Expected a number between 50 and 100",
+                        percentile
+                    )));
+                }
+                OutlierMethod::Percentile(percentile)
+            },
+            other => return Err(AppError::BadRequest(format!(
+                "Error: '{}' is not a valid value for 'outliers'. Allowed values are: {}",
+                other, [OUTLIERS_IQR, OUTLIERS_PERCENTILE, OUTLIERS_NONE].join(", ")
+            ))),
+        };
+
+        if query.max_minutos == Some(0) {
+            return Err(AppError::BadRequest("Error: 'max_minutos' must be greater than 0".to_string()));
+        }
+
+        Ok(Self { method, max_minutes: query.max_minutos })
+    }
+
+    pub fn default_rule() -> Self {
+        Self { method: OutlierMethod::Iqr, max_minutes: None }
+    }
+
+    /// Durações mantidas e quantidade de atendimentos removidos
+    pub fn apply(&self, histogram: &DurationHistogram) -> (DurationHistogram, i64) {
+        let capped: DurationHistogram = histogram.iter()
+            .filter(|(minutes, _)| self.max_minutes.map(|max| **minutes <= max).unwrap_or(true))
+            .map(|(minutes, count)| (*minutes, *count))
+            .collect();
+
+        // Cercas inferior e superior; o percentil remove apenas as durações longas
+        let (lower, upper) = match self.method {
+            OutlierMethod::None => (f64::MIN, f64::MAX),
+            OutlierMethod::Percentile(p) => (f64::MIN, percentile(&capped, p).unwrap_or(f64::MAX)),
+            OutlierMethod::Iqr => match (percentile(&capped, 25.0), percentile(&capped, 75.0)) {
+                (Some(q1), Some(q3)) => (q1 - IQR_MULTIPLIER * (q3 - q1), q3 + IQR_MULTIPLIER * (q3 - q1)),
+                _ => (f64::MIN, f64::MAX),
+            },
+        };
+
+        let kept: DurationHistogram = capped.into_iter()
+            .filter(|(minutes, _)| (lower..=upper).contains(&(*minutes as f64)))
+            .collect();
+
+        let removed = total(histogram) - total(&kept);
+        (kept, removed)
+    }
+}
+
+/// Indica se a visão pedida é a dos histogramas (padrão: estatísticas)
+pub fn histogram_requested(query: &ServiceTimeQuery) -> Result<bool, AppError> {
+    match query.visao.as_deref().map(|v| v.trim().to_lowercase()).as_deref() {
+        None | Some("") | Some(VIEW_STATS) => Ok(false),
+        Some(VIEW_HISTOGRAM) => Ok(true),
+        Some(other) => Err(AppError::BadRequest(format!(
+            "Error: '{}' is not a valid value for 'visao'. Expected: {} or {}",
+            other, VIEW_STATS, VIEW_HISTOGRAM
+        ))),
+    }
+}
+
+/// Largura das faixas do histograma informada na consulta
+pub fn bin_width_from_query(query: &ServiceTimeQuery) -> Result<u32, AppError> {
+    match query.largura_faixa {
+        None => Ok(DEFAULT_BIN_WIDTH),
+        Some(width) if (1..=MAX_BIN_WIDTH).contains(&width) => Ok(width),
+        Some(width) => Err(AppError::BadRequest(format!(
+            "Error: '{}' is not a valid value for 'largura_faixa'. Expected minutes between 1 and {}",
+            width, MAX_BIN_WIDTH
+        ))),
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn total(histogram: &DurationHistogram) -> i64 {
+    histogram.values().sum()
+}
+
+// Duração na posição `rank` (a partir de zero) das durações ordenadas
+fn value_at_rank(histogram: &DurationHistogram, rank: i64) -> Option<f64> {
+    let mut seen = 0;
+    for (minutes, count) in histogram {
+        seen += count;
+        if rank < seen {
+            return Some(*minutes as f64);
+        }
+    }
+    None
+}
+
+/// Percentil com interpolação linear entre as posições vizinhas
+pub fn percentile(histogram: &DurationHistogram, p: f64) -> Option<f64> {
+    let n = total(histogram);
+    if n == 0 {
+        return None;
+    }
+
+    let position = (n - 1) as f64 * p / 100.0;
+    let (below, above) = (position.floor() as i64, position.ceil() as i64);
+
+    let low = value_at_rank(histogram, below)?;
+    let high = value_at_rank(histogram, above)?;
+
+    Some(low + (high - low) * (position - below as f64))
+}
+
+/// Estatísticas após a remoção de valores extremos. Sem durações mantidas, retorna `None`
+pub fn summarize(histogram: &DurationHistogram, rule: &OutlierRule) -> Option<DurationSummary> {
+    let (kept, removed) = rule.apply(histogram);
+    let n = total(&kept);
+    if n == 0 {
+        return None;
+    }
+
+    let sum: f64 = kept.iter().map(|(minutes, count)| *minutes as f64 * *count as f64).sum();
+
+    Some(DurationSummary {
+        quantidade: n,
+        removidos: removed,
+        media: round2(sum / n as f64),
+        mediana: round2(percentile(&kept, 50.0)?),
+        p75: round2(percentile(&kept, 75.0)?),
+        p90: round2(percentile(&kept, 90.0)?),
+    })
+}
+
+/// Histograma contínuo (faixas vazias incluídas) das durações mantidas
+pub fn histogram_bins(histogram: &DurationHistogram, rule: &OutlierRule, width: u32) -> Vec<HistogramBin> {
+    let (kept, _) = rule.apply(histogram);
+    let last = match kept.keys().next_back() {
+        Some(last) => last / width,
+        None => return Vec::new(),
+    };
+
+    let mut bins: Vec<HistogramBin> = (0..=last)
+        .map(|i| HistogramBin { inicio: i * width, fim: (i + 1) * width, quantidade: 0 })
+        .collect();
+
+    for (minutes, count) in kept {
+        bins[(minutes / width) as usize].quantidade += count;
+    }
+
+    bins
+}
+
+// Converte {minutos: quantidade} armazenado no gráfico
+fn histogram_from_json(value: &Value) -> DurationHistogram {
+    value.as_object().into_iter().flatten()
+        .filter_map(|(minutes, count)| Some((minutes.parse::<u32>().ok()?, count.as_i64()?)))
+        .collect()
+}
+
+// Aplica `f` ao histograma de cada médico e período ({médico: {período: {minutos: n}}}),
+// omitindo os períodos sem resultado
+fn map_histograms<T: serde::Serialize>(data: &Map<String, Value>, f: impl Fn(&DurationHistogram) -> Option<T>) -> Value {
+    let mut result = Map::new();
+
+    for (doctor, periods) in data {
+        let mut doctor_data = Map::new();
+        for (period, histogram) in periods.as_object().into_iter().flatten() {
+            if let Some(value) = f(&histogram_from_json(histogram)) {
+                doctor_data.insert(period.clone(), json!(value));
+            }
+        }
+
+        if !doctor_data.is_empty() {
+            result.insert(doctor.clone(), Value::Object(doctor_data));
+        }
+    }
+
+    Value::Object(result)
+}
+
+/// Estatísticas por médico e período ({médico: {período: {quantidade, media, mediana, p75, p90}}})
+pub fn service_time_summaries(data: &Map<String, Value>, rule: &OutlierRule) -> Value {
+    map_histograms(data, |histogram| summarize(histogram, rule))
+}
+
+/// Histogramas por médico e período ({médico: {período: [{inicio, fim, quantidade}]}})
+pub fn service_time_histograms(data: &Map<String, Value>, rule: &OutlierRule, width: u32) -> Value {
+    map_histograms(data, |histogram| {
+        let bins = histogram_bins(histogram, rule, width);
+        (!bins.is_empty()).then_some(bins)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(durations: &[(u32, i64)]) -> DurationHistogram {
+        durations.iter().copied().collect()
+    }
+
+    fn rule(method: OutlierMethod, max_minutes: Option<u32>) -> OutlierRule {
+        OutlierRule { method, max_minutes }
+    }
+
+    #[test]
+    fn percentile_interpolates_between_neighbouring_durations() {
+        let durations = histogram(&[(10, 1), (20, 1), (30, 1), (40, 1)]);
+
+        assert_eq!(percentile(&durations, 0.0), Some(10.0));
+        assert_eq!(percentile(&durations, 50.0), Some(25.0));
+        assert_eq!(percentile(&durations, 100.0), Some(40.0));
+        assert_eq!(percentile(&histogram(&[(7, 3)]), 90.0), Some(7.0));
+        assert_eq!(percentile(&DurationHistogram::new(), 50.0), None);
+    }
+
+    #[test]
+    fn iqr_rule_removes_durations_outside_the_tukey_fences() {
+        // Q1 = 10 e Q3 = 14: cercas em 4 e 20 minutos
+        let durations = histogram(&[(10, 4), (12, 4), (14, 4), (200, 1)]);
+
+        let (kept, removed) = OutlierRule::default_rule().apply(&durations);
+
+        assert_eq!(kept, histogram(&[(10, 4), (12, 4), (14, 4)]));
+        assert_eq!(removed, 1);
+    }
+
+    #[test]
+    fn percentile_rule_removes_only_long_durations() {
+        let durations: DurationHistogram = (1..=10).map(|minutes| (minutes, 1)).collect();
+
+        // O percentil 90 fica em 9,1 minutos
+        let (kept, removed) = rule(OutlierMethod::Percentile(90.0), None).apply(&durations);
+
+        assert_eq!(kept.keys().copied().collect::<Vec<_>>(), (1..=9).collect::<Vec<_>>());
+        assert_eq!(removed, 1);
+    }
+
+    #[test]
+    fn max_minutes_is_applied_before_the_outlier_rule() {
+        let durations = histogram(&[(10, 1), (40, 2)]);
+
+        let (kept, removed) = rule(OutlierMethod::None, Some(30)).apply(&durations);
+
+        assert_eq!(kept, histogram(&[(10, 1)]));
+        assert_eq!(removed, 2);
+    }
+
+    #[test]
+    fn summary_of_the_kept_durations() {
+        let summary = summarize(&histogram(&[(10, 1), (20, 1), (30, 1)]), &rule(OutlierMethod::None, None)).unwrap();
+
+        assert_eq!((summary.quantidade, summary.removidos), (3, 0));
+        assert_eq!((summary.media, summary.mediana, summary.p75, summary.p90), (20.0, 20.0, 25.0, 28.0));
+        assert!(summarize(&histogram(&[(50, 2)]), &rule(OutlierMethod::None, Some(30))).is_none());
+    }
+
+    #[test]
+    fn histogram_bins_include_empty_bins() {
+        let bins = histogram_bins(&histogram(&[(3, 1), (12, 2)]), &rule(OutlierMethod::None, None), 5);
+
+        let bins: Vec<(u32, u32, i64)> = bins.iter().map(|b| (b.inicio, b.fim, b.quantidade)).collect();
+        assert_eq!(bins, vec![(0, 5, 1), (5, 10, 0), (10, 15, 2)]);
+    }
+
+    #[test]
+    fn query_parameters_are_validated() {
+        let query = |outliers: Option<&str>, percentil_corte: Option<f64>, max_minutos: Option<u32>| ServiceTimeQuery {
+            outliers: outliers.map(String::from),
+            percentil_corte,
+            max_minutos,
+            ..Default::default()
+        };
+
+        assert!(matches!(OutlierRule::from_query(&query(None, None, None)).unwrap().method, OutlierMethod::Iqr));
+        assert!(matches!(
+            OutlierRule::from_query(&query(Some("PERCENTIL"), None, None)).unwrap().method,
+            OutlierMethod::Percentile(p) if p == DEFAULT_CUTOFF_PERCENTILE
+        ));
+        assert!(OutlierRule::from_query(&query(Some("percentil"), Some(40.0), None)).is_err());
+        assert!(OutlierRule::from_query(&query(Some("media"), None, None)).is_err());
+        assert!(OutlierRule::from_query(&query(None, None, Some(0))).is_err());
+
+        let width = |largura_faixa| bin_width_from_query(&ServiceTimeQuery { largura_faixa, ..Default::default() });
+        assert_eq!(width(None).unwrap(), DEFAULT_BIN_WIDTH);
+        assert!(width(Some(0)).is_err());
+        assert!(width(Some(MAX_BIN_WIDTH + 1)).is_err());
+
+        let view = |visao: &str| histogram_requested(&ServiceTimeQuery { visao: Some(visao.to_string()), ..Default::default() });
+        assert!(!view("estatisticas").unwrap());
+        assert!(view(" Histograma ").unwrap());
+        assert!(view("tabela").is_err());
+    }
+}
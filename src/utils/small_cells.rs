@@ -0,0 +1,350 @@
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// Chave em que os profissionais com contagens pequenas são somados
+pub const OTHERS_KEY: &str = "outros";
+
+// Chaves que guardam a contagem de uma célula (bairro, ponto, faixa de histograma)
+const COUNT_KEYS: [&str; 2] = ["quantidade", "casos"];
+
+// Números que não são contagens de pessoas (coordenadas, populações, taxas e parâmetros)
+const NON_COUNT_KEYS: [&str; 11] = [
+    "latitude", "longitude", "populacao", "base", "taxa", "taxa_padronizada",
+    "largura_faixa", "inicio", "fim", "gi_z", "top",
+];
+
+// Campos da comparação com o período de referência
+const COMPARISON_KEYS: [&str; 3] = ["comparacao", "variacao", "variacao_percentual"];
+
+// Campos de uma etapa do funil, que permitem deduzir as visitas umas das outras
+const STAGE_KEYS: [&str; 4] = ["visitas", "conversao", "perda", "perda_percentual"];
+
+// Agrupamentos do funil de cada período
+const FUNNEL_BREAKDOWNS: [&str; 2] = ["por_dia_semana", "por_hora"];
+
+/// Resumo da supressão de células pequenas, enviado em `meta` nas respostas por bairro ou profissional
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct CellSuppression {
+    pub supressao_aplicada: bool,
+    pub tamanho_minimo_celula: i64,
+    pub celulas_suprimidas: usize,
+}
+
+impl CellSuppression {
+    pub fn new(min_cell_size: i64, suppressed: usize) -> Self {
+        Self {
+            supressao_aplicada: suppressed > 0,
+            tamanho_minimo_celula: min_cell_size,
+            celulas_suprimidas: suppressed,
+        }
+    }
+}
+
+// Contagens entre 1 e k - 1; zeros não identificam ninguém
+fn is_small(count: f64, min_cell_size: i64) -> bool {
+    count > 0.0 && count < min_cell_size as f64
+}
+
+// Contagem direta ou anotada com a comparação ({valor, comparacao, ...})
+fn count_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Number(n) => n.as_f64(),
+        Value::Object(map) => map.get("valor").and_then(Value::as_f64),
+        _ => None,
+    }
+}
+
+// Contagem da célula e o valor que pode trazer a comparação; `None` para agrupamentos
+fn cell_mut<'a>(key: Option<&str>, value: &'a mut Value) -> Option<(f64, &'a mut Value)> {
+    let count_key = value.as_object()
+        .and_then(|map| COUNT_KEYS.into_iter().find(|k| map.get(*k).and_then(count_value).is_some()));
+
+    match (count_key, &*value) {
+        (Some(count_key), _) => {
+            let annotated = value.get_mut(count_key)?;
+            Some((count_value(annotated)?, annotated))
+        },
+        (None, Value::Object(map)) => Some((map.get("valor").and_then(Value::as_f64)?, value)),
+        (None, Value::Number(n)) if !key.map(|k| NON_COUNT_KEYS.contains(&k)).unwrap_or(false) => Some((n.as_f64()?, value)),
+        _ => None,
+    }
+}
+
+// Oculta a contagem do período de referência quando ela é pequena
+fn mask_comparison(value: &mut Value, min_cell_size: i64) -> usize {
+    let Some(map) = value.as_object_mut() else {
+        return 0;
+    };
+
+    match map.get("comparacao").and_then(Value::as_f64) {
+        Some(reference) if is_small(reference, min_cell_size) => {
+            for key in COMPARISON_KEYS {
+                map.insert(key.to_string(), Value::Null);
+            }
+            1
+        },
+        _ => 0,
+    }
+}
+
+// Trata um valor da resposta, indicando se ele deve ser removido do agrupamento que o contém
+fn suppress_entry(key: Option<&str>, value: &mut Value, min_cell_size: i64, suppressed: &mut usize) -> bool {
+    if let Some((count, annotated)) = cell_mut(key, value) {
+        if is_small(count, min_cell_size) {
+            *suppressed += 1;
+            return true;
+        }
+        *suppressed += mask_comparison(annotated, min_cell_size);
+        return false;
+    }
+
+    // Agrupamentos esvaziados pela supressão também saem da resposta
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            map.retain(|key, child| !suppress_entry(Some(key), child, min_cell_size, suppressed));
+            map.is_empty()
+        },
+        Value::Array(items) if !items.is_empty() => {
+            items.retain_mut(|item| !suppress_entry(None, item, min_cell_size, suppressed));
+            items.is_empty()
+        },
+        _ => false,
+    }
+}
+
+/// Remove as células por bairro ou local com contagem entre 1 e `min_cell_size - 1`: objetos com
+/// `quantidade` ou `casos`, contagens simples e itens de listas. A contagem pequena do período de
+/// comparação é ocultada nas células mantidas. Retorna a quantidade de células suprimidas
+pub fn suppress_small_cells(data: &mut Value, min_cell_size: i64) -> usize {
+    if min_cell_size <= 1 {
+        return 0;
+    }
+
+    let mut suppressed = 0;
+    if let Value::Object(map) = data {
+        map.retain(|key, child| !suppress_entry(Some(key), child, min_cell_size, &mut suppressed));
+    } else if let Value::Array(items) = data {
+        items.retain_mut(|item| !suppress_entry(None, item, min_cell_size, &mut suppressed));
+    }
+
+    suppressed
+}
+
+/// Soma em "outros" as contagens pequenas de cada profissional e período ({nome: {período: n}}),
+/// removendo os profissionais que ficarem sem períodos. Resumos e histogramas por profissional
+/// (objetos ou listas no lugar de `n`) têm as células pequenas removidas, pois não podem ser somados.
+/// Períodos de "outros" que continuem abaixo do mínimo também são suprimidos
+pub fn merge_small_professionals(data: &mut Value, min_cell_size: i64) -> usize {
+    let Some(professionals) = data.as_object_mut() else {
+        return 0;
+    };
+    if min_cell_size <= 1 {
+        return 0;
+    }
+
+    let mut suppressed = 0;
+    let mut others: Map<String, Value> = Map::new();
+
+    professionals.retain(|_, periods| {
+        let Some(periods) = periods.as_object_mut() else {
+            return true;
+        };
+
+        periods.retain(|period, value| match value.as_i64() {
+            Some(count) if is_small(count as f64, min_cell_size) => {
+                let total = others.get(period).and_then(Value::as_i64).unwrap_or(0);
+                others.insert(period.clone(), Value::from(total + count));
+                suppressed += 1;
+                false
+            },
+            Some(_) => true,
+            None => !suppress_entry(Some(period), value, min_cell_size, &mut suppressed),
+        });
+
+        !periods.is_empty()
+    });
+
+    // "outros" com um único profissional pequeno ainda o identificaria
+    others.retain(|_, total| !is_small(total.as_f64().unwrap_or(0.0), min_cell_size));
+    if !others.is_empty() {
+        professionals.insert(OTHERS_KEY.to_string(), Value::Object(others));
+    }
+
+    suppressed
+}
+
+// Oculta as visitas de um funil ({visitas, funil: [...]}) a partir da primeira etapa com contagem pequena
+fn suppress_funnel_steps(group: &mut Value, min_cell_size: i64) -> usize {
+    let small = |value: &Value| value.as_i64().map(|count| is_small(count as f64, min_cell_size)).unwrap_or(false);
+
+    let mut suppressed = 0;
+    let first = if small(&group["visitas"]) {
+        group["visitas"] = Value::Null;
+        suppressed += 1;
+        Some(0)
+    } else {
+        group["funil"].as_array()
+            .and_then(|steps| steps.iter().position(|step| small(&step["visitas"]) || small(&step["perda"])))
+    };
+
+    let (Some(first), Some(steps)) = (first, group["funil"].as_array_mut()) else {
+        return suppressed;
+    };
+
+    // A etapa seguinte seria deduzida da oculta pela perda, por isso todas as seguintes são ocultadas
+    for step in steps.iter_mut().skip(first) {
+        for key in STAGE_KEYS {
+            step[key] = Value::Null;
+        }
+        suppressed += 1;
+    }
+
+    suppressed
+}
+
+/// Oculta, em cada período do funil ({periodos: {período: {visitas, funil, por_dia_semana, por_hora}}}),
+/// as etapas a partir da primeira com visitas ou perda entre 1 e `min_cell_size - 1`. Dias e horas com
+/// poucas visitas são removidos. Retorna a quantidade de células suprimidas
+pub fn suppress_small_stages(data: &mut Value, min_cell_size: i64) -> usize {
+    if min_cell_size <= 1 {
+        return 0;
+    }
+    let Some(periods) = data.get_mut("periodos").and_then(Value::as_object_mut) else {
+        return 0;
+    };
+
+    let mut suppressed = 0;
+    for period in periods.values_mut() {
+        suppressed += suppress_funnel_steps(period, min_cell_size);
+
+        for breakdown in FUNNEL_BREAKDOWNS {
+            let Some(groups) = period.get_mut(breakdown).and_then(Value::as_object_mut) else {
+                continue;
+            };
+
+            groups.retain(|_, group| {
+                match group["visitas"].as_i64() {
+                    Some(visitas) if is_small(visitas as f64, min_cell_size) => {
+                        suppressed += 1;
+                        false
+                    },
+                    _ => {
+                        suppressed += suppress_funnel_steps(group, min_cell_size);
+                        true
+                    },
+                }
+            });
+        }
+    }
+
+    suppressed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const K: i64 = 5;
+
+    fn neighborhood(quantidade: i64) -> Value {
+        json!({"latitude": -8.76, "longitude": -63.9, "quantidade": quantidade})
+    }
+
+    #[test]
+    fn cells_below_the_minimum_are_removed_and_zeros_kept() {
+        let mut data = json!({
+            "2024-1": {"CENTRO": neighborhood(0), "NORTE": neighborhood(K - 1), "SUL": neighborhood(K)},
+            "2024-2": {"NORTE": neighborhood(1)},
+        });
+
+        let suppressed = suppress_small_cells(&mut data, K);
+
+        assert_eq!(suppressed, 2);
+        assert_eq!(data, json!({"2024-1": {"CENTRO": neighborhood(0), "SUL": neighborhood(K)}}));
+    }
+
+    #[test]
+    fn plain_counts_and_list_items_are_cells() {
+        let mut data = json!({"segunda": 3, "terca": 12, "horas": [1, 0, 7]});
+
+        assert_eq!(suppress_small_cells(&mut data, K), 2);
+        assert_eq!(data, json!({"terca": 12, "horas": [0, 7]}));
+    }
+
+    #[test]
+    fn non_count_numbers_are_never_removed() {
+        let mut data = json!({
+            "base": 1000,
+            "top": 3,
+            "CENTRO": {"latitude": 1.0, "longitude": 2.0, "populacao": 4, "taxa": 2.5, "taxa_padronizada": 1.5, "gi_z": 1.2},
+            "faixa": {"inicio": 0, "fim": 4, "largura_faixa": 2},
+        });
+        let expected = data.clone();
+
+        assert_eq!(suppress_small_cells(&mut data, K), 0);
+        assert_eq!(data, expected);
+    }
+
+    #[test]
+    fn comparison_cells_use_the_current_value_and_mask_a_small_reference() {
+        let mut data = json!({
+            "2024-1": {"valor": 10, "comparacao": 3, "variacao": 7, "variacao_percentual": 233.33},
+            "2024-2": {"valor": 3, "comparacao": 10, "variacao": -7, "variacao_percentual": -70.0},
+            "2024-3": {"valor": 8, "comparacao": 6, "variacao": 2, "variacao_percentual": 33.33},
+            "CENTRO": {"latitude": 1.0, "longitude": 2.0, "quantidade": {"valor": 9, "comparacao": 1, "variacao": 8, "variacao_percentual": 800.0}},
+        });
+
+        let suppressed = suppress_small_cells(&mut data, K);
+
+        assert_eq!(suppressed, 3);
+        assert_eq!(data, json!({
+            "2024-1": {"valor": 10, "comparacao": null, "variacao": null, "variacao_percentual": null},
+            "2024-3": {"valor": 8, "comparacao": 6, "variacao": 2, "variacao_percentual": 33.33},
+            "CENTRO": {"latitude": 1.0, "longitude": 2.0, "quantidade": {"valor": 9, "comparacao": null, "variacao": null, "variacao_percentual": null}},
+        }));
+    }
+
+    #[test]
+    fn mask_comparison_keeps_large_or_missing_references() {
+        let mut kept = json!({"valor": 10, "comparacao": K, "variacao": 5});
+        let mut without = json!({"valor": 10});
+
+        assert_eq!(mask_comparison(&mut kept, K), 0);
+        assert_eq!(mask_comparison(&mut without, K), 0);
+        assert_eq!(kept, json!({"valor": 10, "comparacao": K, "variacao": 5}));
+    }
+
+    #[test]
+    fn small_professionals_are_rolled_up_into_others() {
+        let mut data = json!({
+            "ANA": {"2024-1": 2, "2024-2": 10},
+            "BIA": {"2024-1": 4},
+            "CAIO": {"2024-2": 3},
+        });
+
+        let suppressed = merge_small_professionals(&mut data, K);
+
+        // "outros" de 2024-2 teria apenas os 3 atendimentos de CAIO e também é suprimido
+        assert_eq!(suppressed, 3);
+        assert_eq!(data, json!({"ANA": {"2024-2": 10}, OTHERS_KEY: {"2024-1": 6}}));
+    }
+
+    #[test]
+    fn others_below_the_minimum_is_not_created() {
+        let mut data = json!({"ANA": {"2024-1": 2}, "BIA": {"2024-1": 2}, "CAIO": {"2024-1": K}});
+
+        assert_eq!(merge_small_professionals(&mut data, K), 2);
+        assert_eq!(data, json!({"CAIO": {"2024-1": K}}));
+    }
+
+    #[test]
+    fn minimum_of_one_disables_suppression() {
+        let mut data = json!({"CENTRO": neighborhood(1), "ANA": {"2024-1": 1}});
+        let expected = data.clone();
+
+        assert_eq!(suppress_small_cells(&mut data, 1), 0);
+        assert_eq!(merge_small_professionals(&mut data, 1), 0);
+        assert_eq!(data, expected);
+    }
+}
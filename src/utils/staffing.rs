@@ -0,0 +1,162 @@
+use chrono::{Datelike, NaiveDate, Weekday};
+use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+use crate::domain::models::staffing::{HourlyArrivalCount, StaffingSlot};
+
+/// Tempo médio de espera desejado, em minutos
+pub const TARGET_WAIT_MINUTES: f64 = 30.0;
+
+const SLOT_HOURS: u32 = 2;
+
+// Limite de segurança na busca pelo número de médicos
+const MAX_DOCTORS: u32 = 100;
+
+pub const WEEKDAYS: [(Weekday, &str); 7] = [
+    (Weekday::Mon, "segunda-feira"),
+    (Weekday::Tue, "terça-feira"),
+    (Weekday::Wed, "quarta-feira"),
+    (Weekday::Thu, "quinta-feira"),
+    (Weekday::Fri, "sexta-feira"),
+    (Weekday::Sat, "sábado"),
+    (Weekday::Sun, "domingo"),
+];
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+fn slot_label(slot: u32) -> String {
+    format!("{:02}h-{:02}h", slot * SLOT_HOURS, (slot + 1) * SLOT_HOURS)
+}
+
+/// Probabilidade de espera no modelo M/M/c (Erlang C), calculada pela recursão de Erlang B.
+/// `load` é a carga oferecida em Erlangs (chegadas × tempo médio de atendimento)
+pub fn erlang_c(servers: u32, load: f64) -> f64 {
+    if load <= 0.0 {
+        return 0.0;
+    }
+    if load >= servers as f64 {
+        return 1.0;
+    }
+
+    let mut erlang_b = 1.0;
+    for k in 1..=servers {
+        erlang_b = load * erlang_b / (k as f64 + load * erlang_b);
+    }
+
+    let c = servers as f64;
+    c * erlang_b / (c - load * (1.0 - erlang_b))
+}
+
+/// Menor número de médicos cuja espera média fica dentro do alvo.
+/// Retorna (médicos, espera média em minutos, probabilidade de espera)
+pub fn recommend_servers(arrivals_per_minute: f64, service_minutes: f64, target_wait_minutes: f64) -> (u32, f64, f64) {
+    let load = arrivals_per_minute * service_minutes;
+
+    // Ao menos um médico por faixa, mesmo sem chegadas registradas
+    let mut servers = load.floor() as u32 + 1;
+
+    loop {
+        let wait_probability = erlang_c(servers, load);
+        let capacity = servers as f64 / service_minutes - arrivals_per_minute;
+        let mean_wait = if arrivals_per_minute > 0.0 { wait_probability / capacity } else { 0.0 };
+
+        if mean_wait <= target_wait_minutes || servers >= MAX_DOCTORS {
+            return (servers, mean_wait, wait_probability);
+        }
+
+        servers += 1;
+    }
+}
+
+/// Recomendação de médicos por dia da semana e faixa de duas horas. A taxa de chegada de
+/// cada faixa é a média das consultas nos dias daquele dia da semana presentes no histórico
+pub fn staffing_by_weekday(arrivals: &[HourlyArrivalCount], service_minutes: f64) -> Value {
+    // Dias com atendimento de cada dia da semana
+    let mut days_per_weekday: HashMap<Weekday, HashSet<NaiveDate>> = HashMap::new();
+    let mut counts: HashMap<(Weekday, u32), i64> = HashMap::new();
+
+    for arrival in arrivals.iter().filter(|a| a.hora < 24) {
+        let weekday = arrival.data.weekday();
+        let slot = arrival.hora / SLOT_HOURS;
+
+        days_per_weekday.entry(weekday).or_default().insert(arrival.data);
+        *counts.entry((weekday, slot)).or_insert(0) += arrival.quantidade;
+    }
+
+    let mut days = serde_json::Map::new();
+    for (weekday, name) in WEEKDAYS {
+        let observed_days = match days_per_weekday.get(&weekday) {
+            Some(dates) => dates.len() as f64,
+            None => continue,
+        };
+
+        let slots: Vec<StaffingSlot> = (0..24 / SLOT_HOURS)
+            .map(|slot| {
+                let total = counts.get(&(weekday, slot)).copied().unwrap_or(0) as f64;
+                let arrivals_per_minute = total / observed_days / (SLOT_HOURS as f64 * 60.0);
+                let (medicos, espera, probabilidade) = recommend_servers(arrivals_per_minute, service_minutes, TARGET_WAIT_MINUTES);
+
+                StaffingSlot {
+                    faixa_horaria: slot_label(slot),
+                    chegadas_por_hora: round2(arrivals_per_minute * 60.0),
+                    carga_erlangs: round2(arrivals_per_minute * service_minutes),
+                    medicos_recomendados: medicos,
+                    espera_media_minutos: round2(espera),
+                    probabilidade_espera: round2(probabilidade),
+                }
+            })
+            .collect();
+
+        days.insert(name.to_string(), json!(slots));
+    }
+
+    json!({
+        "tempo_medio_atendimento_minutos": round2(service_minutes),
+        "tempo_espera_alvo_minutos": TARGET_WAIT_MINUTES,
+        "dias": days,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{} != {}", actual, expected);
+    }
+
+    #[test]
+    fn erlang_c_matches_closed_forms() {
+        // M/M/1: a probabilidade de espera é a própria ocupação
+        assert_close(erlang_c(1, 0.5), 0.5);
+        // Dois médicos com carga de 1 Erlang: (1/2 · 2) / (1 + 1 + 1)
+        assert_close(erlang_c(2, 1.0), 1.0 / 3.0);
+        // Tabela de Erlang C: 10 servidores e 8 Erlangs
+        assert!((erlang_c(10, 8.0) - 0.4092).abs() < 1e-4);
+    }
+
+    #[test]
+    fn erlang_c_handles_empty_and_saturated_loads() {
+        assert_eq!(erlang_c(3, 0.0), 0.0);
+        assert_eq!(erlang_c(3, 3.0), 1.0);
+        assert_eq!(erlang_c(3, 4.5), 1.0);
+    }
+
+    #[test]
+    fn recommend_servers_returns_the_smallest_staffing_within_target() {
+        // 0,2 chegadas por minuto e 15 minutos de atendimento (3 Erlangs): com 4 médicos a espera
+        // média é de 7,6 minutos, e com 5, de 1,8
+        let (servers, mean_wait, wait_probability) = recommend_servers(0.2, 15.0, 5.0);
+
+        assert_eq!(servers, 5);
+        assert!((mean_wait - 1.7711).abs() < 1e-4);
+        assert_close(wait_probability, erlang_c(5, 3.0));
+    }
+
+    #[test]
+    fn recommend_servers_keeps_one_doctor_without_arrivals() {
+        assert_eq!(recommend_servers(0.0, 15.0, TARGET_WAIT_MINUTES), (1, 0.0, 0.0));
+    }
+}
@@ -0,0 +1,363 @@
+use chrono::{Duration, NaiveDate};
+use std::collections::{BTreeMap, HashMap};
+
+use crate::domain::models::surveillance::{
+    AlertBaseline,
+    AlertSignal,
+    NeighborhoodDailyCount,
+    ALERT_SEVERITY_HIGH,
+    ALERT_SEVERITY_LOW,
+    ALERT_SEVERITY_MEDIUM,
+};
+use crate::utils::process_data::list_of_most_common_diseases;
+use crate::utils::time_series::{epi_week, epi_week_bounds, epi_week_label};
+
+// Parâmetros dos métodos C1, C2 e C3 do EARS (Early Aberration Reporting System, CDC),
+// aplicados a contagens semanais
+const BASELINE_WEEKS: usize = 7;
+const C2_GUARD_WEEKS: usize = 2;
+const C3_WEEKS: usize = 3;
+const C1_C2_THRESHOLD: f64 = 3.0;
+const C3_THRESHOLD: f64 = 2.0;
+
+/// Semanas anteriores usadas no cálculo de uma semana: as linhas de base do C2 nas semanas acumuladas pelo C3
+pub const LOOKBACK_WEEKS: usize = BASELINE_WEEKS + C2_GUARD_WEEKS + C3_WEEKS - 1;
+
+// Desvio padrão mínimo, evitando divisão por zero em linhas de base constantes
+const MIN_STD_DEV: f64 = 0.2;
+
+// Semanas com menos casos que isso não geram alerta
+const MIN_OBSERVED: i64 = 3;
+
+// Casos por semana epidemiológica (ano, semana)
+type WeeklyCounts = HashMap<(i32, u32), i64>;
+
+// Estatística calculada para uma semana, com a linha de base usada
+struct EarsStatistic {
+    algoritmo: &'static str,
+    valor: f64,
+    limiar: f64,
+    baseline_start: usize,
+    baseline_end: usize,
+    media: f64,
+    desvio: f64,
+}
+
+impl EarsStatistic {
+    fn flagged(&self) -> bool {
+        self.valor > self.limiar
+    }
+}
+
+// Média e desvio padrão amostral das semanas start..=end
+fn mean_and_std_dev(values: &[i64], start: usize, end: usize) -> (f64, f64) {
+    let window = &values[start..=end];
+    let n = window.len() as f64;
+    let mean = window.iter().sum::<i64>() as f64 / n;
+    let variance = window.iter().map(|v| (*v as f64 - mean).powi(2)).sum::<f64>() / (n - 1.0);
+
+    (mean, variance.sqrt().max(MIN_STD_DEV))
+}
+
+// C1 e C2 diferem apenas no intervalo entre a linha de base e a semana avaliada
+fn c_statistic(values: &[i64], t: usize, algoritmo: &'static str, guard: usize) -> Option<EarsStatistic> {
+    if t < BASELINE_WEEKS + guard {
+        return None;
+    }
+
+    let baseline_end = t - guard - 1;
+    let baseline_start = baseline_end + 1 - BASELINE_WEEKS;
+    let (media, desvio) = mean_and_std_dev(values, baseline_start, baseline_end);
+
+    Some(EarsStatistic {
+        algoritmo,
+        valor: (values[t] as f64 - media) / desvio,
+        limiar: C1_C2_THRESHOLD,
+        baseline_start,
+        baseline_end,
+        media,
+        desvio,
+    })
+}
+
+// C3 acumula os excessos do C2 na semana avaliada e nas anteriores
+fn c3_statistic(values: &[i64], t: usize) -> Option<EarsStatistic> {
+    let current = c_statistic(values, t, "C3", C2_GUARD_WEEKS)?;
+
+    // Sem excesso na semana avaliada, o acúmulo das semanas anteriores não gera novo sinal
+    if current.valor <= 1.0 {
+        return None;
+    }
+
+    let mut valor = 0.0;
+    for week in t + 1 - C3_WEEKS..=t {
+        let c2 = c_statistic(values, week, "C2", C2_GUARD_WEEKS)?;
+        valor += (c2.valor - 1.0).max(0.0);
+    }
+
+    Some(EarsStatistic { valor, limiar: C3_THRESHOLD, ..current })
+}
+
+// Retorna, para cada semana com sinal, o método mais rigoroso que o disparou
+fn detect_aberrations(values: &[i64]) -> Vec<(usize, EarsStatistic)> {
+    let mut aberrations = Vec::new();
+
+    for (t, observed) in values.iter().enumerate() {
+        if *observed < MIN_OBSERVED {
+            continue;
+        }
+
+        let strongest = [
+            c3_statistic(values, t),
+            c_statistic(values, t, "C2", C2_GUARD_WEEKS),
+            c_statistic(values, t, "C1", 0),
+        ]
+        .into_iter()
+        .flatten()
+        .find(EarsStatistic::flagged);
+
+        if let Some(statistic) = strongest {
+            aberrations.push((t, statistic));
+        }
+    }
+
+    aberrations
+}
+
+/// Semanas recalculadas após a alteração dos atendimentos entre duas datas: os alertas podem mudar
+/// da semana da primeira data até `LOOKBACK_WEEKS` semanas após a da última, e o cálculo usa as
+/// contagens desde `LOOKBACK_WEEKS` semanas antes da primeira
+#[derive(Debug, Clone, Copy)]
+pub struct SurveillanceWindow {
+    pub counts_start: NaiveDate,
+    pub alerts_start: NaiveDate,
+    pub end: NaiveDate,
+}
+
+impl SurveillanceWindow {
+    pub fn for_dates(start: NaiveDate, end: NaiveDate) -> Self {
+        let lookback = Duration::weeks(LOOKBACK_WEEKS as i64);
+        let (alerts_start, _) = epi_week_bounds(epi_week(start));
+        let (_, last_week_end) = epi_week_bounds(epi_week(end));
+
+        Self {
+            counts_start: alerts_start - lookback,
+            alerts_start,
+            end: last_week_end + lookback,
+        }
+    }
+
+    /// Não usa semanas anteriores ao primeiro atendimento da unidade, onde o cálculo completo também começa
+    pub fn starting_from(mut self, first_day: NaiveDate) -> Self {
+        let (first_week_start, _) = epi_week_bounds(epi_week(first_day));
+        self.counts_start = self.counts_start.max(first_week_start);
+        self
+    }
+}
+
+fn severity(algoritmo: &str) -> &'static str {
+    match algoritmo {
+        "C3" => ALERT_SEVERITY_HIGH,
+        "C2" => ALERT_SEVERITY_MEDIUM,
+        _ => ALERT_SEVERITY_LOW,
+    }
+}
+
+/// Aplica os métodos EARS às séries semanais de cada doença, na unidade inteira e por bairro.
+/// As semanas sem atendimentos entre o primeiro e o último dia informados contam como zero. Com
+/// `window`, as semanas vão de `counts_start` a `end`, para que as posições das linhas de base sejam
+/// as mesmas do cálculo completo mesmo quando as primeiras semanas não têm atendimentos, e as
+/// contagens anteriores a `alerts_start` servem apenas de linha de base
+pub fn detect_signals(counts: &[NeighborhoodDailyCount], window: Option<&SurveillanceWindow>) -> Vec<AlertSignal> {
+    let (start, end) = match window {
+        Some(window) => (window.counts_start, window.end),
+        None => match (counts.iter().map(|c| c.data).min(), counts.iter().map(|c| c.data).max()) {
+            (Some(start), Some(end)) => (start, end),
+            _ => return Vec::new(),
+        },
+    };
+
+    let known_diseases: Vec<&str> = list_of_most_common_diseases().iter().map(|d| d.trim()).collect();
+
+    let counts: Vec<&NeighborhoodDailyCount> = counts.iter()
+        .filter(|c| known_diseases.contains(&c.queixa.as_str()))
+        .collect();
+
+    if counts.is_empty() {
+        return Vec::new();
+    }
+
+    let mut weeks = Vec::new();
+    let (mut week_start, _) = epi_week_bounds(epi_week(start));
+    while week_start <= end {
+        weeks.push(epi_week(week_start));
+        week_start += Duration::weeks(1);
+    }
+
+    // Séries por doença: uma para a unidade (bairro None) e uma para cada bairro
+    let mut series: BTreeMap<(String, Option<String>), WeeklyCounts> = BTreeMap::new();
+    for count in &counts {
+        let week = epi_week(count.data);
+
+        *series.entry((count.queixa.clone(), None)).or_default().entry(week).or_insert(0) += count.quantidade;
+
+        if !count.bairro.is_empty() {
+            *series.entry((count.queixa.clone(), Some(count.bairro.clone()))).or_default().entry(week).or_insert(0) += count.quantidade;
+        }
+    }
+
+    let mut signals = Vec::new();
+    for ((doenca, bairro), weekly) in series {
+        let values: Vec<i64> = weeks.iter().map(|week| weekly.get(week).copied().unwrap_or(0)).collect();
+
+        for (t, statistic) in detect_aberrations(&values) {
+            let (semana_inicio, semana_fim) = epi_week_bounds(weeks[t]);
+            if window.is_some_and(|window| semana_inicio < window.alerts_start) {
+                continue;
+            }
+
+            signals.push(AlertSignal {
+                doenca: doenca.clone(),
+                bairro: bairro.clone(),
+                semana: epi_week_label(weeks[t]),
+                semana_inicio,
+                semana_fim,
+                algoritmo: statistic.algoritmo.to_string(),
+                severidade: severity(statistic.algoritmo).to_string(),
+                observado: values[t],
+                baseline: AlertBaseline {
+                    semana_inicio: epi_week_label(weeks[statistic.baseline_start]),
+                    semana_fim: epi_week_label(weeks[statistic.baseline_end]),
+                    media: statistic.media,
+                    desvio_padrao: statistic.desvio,
+                },
+                estatistica: statistic.valor,
+                limiar: statistic.limiar,
+            });
+        }
+    }
+
+    signals
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flagged_weeks(values: &[i64]) -> Vec<(usize, &'static str)> {
+        detect_aberrations(values).into_iter()
+            .map(|(t, statistic)| (t, statistic.algoritmo))
+            .collect()
+    }
+
+    #[test]
+    fn stable_series_has_no_aberrations() {
+        let values = [4, 5, 4, 5, 4, 5, 4, 5, 4, 5, 4, 5, 4, 5];
+        assert!(flagged_weeks(&values).is_empty());
+    }
+
+    #[test]
+    fn spike_is_flagged_by_the_strictest_method() {
+        let values = [4, 5, 4, 5, 4, 5, 4, 5, 4, 5, 4, 20];
+        assert_eq!(flagged_weeks(&values), vec![(11, "C3")]);
+    }
+
+    #[test]
+    fn early_spike_uses_c1_while_c2_has_no_baseline() {
+        // Na semana 8 só há linha de base para o C1 (7 semanas imediatamente anteriores)
+        let values = [4, 5, 4, 5, 4, 5, 4, 20];
+        assert_eq!(flagged_weeks(&values), vec![(7, "C1")]);
+    }
+
+    #[test]
+    fn weeks_below_minimum_cases_are_ignored() {
+        let values = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 2];
+        assert!(flagged_weeks(&values).is_empty());
+    }
+
+    #[test]
+    fn lookback_covers_every_week_used_by_a_statistic() {
+        let values: Vec<i64> = (0..40).map(|t| [4, 6, 5, 3, 7][t % 5] + if t % 13 == 12 { 15 } else { 0 }).collect();
+        let full = flagged_weeks(&values);
+
+        // Recalcular a partir de LOOKBACK_WEEKS semanas antes dá o mesmo resultado nas semanas seguintes
+        for first in LOOKBACK_WEEKS..values.len() {
+            let partial: Vec<(usize, &str)> = flagged_weeks(&values[first - LOOKBACK_WEEKS..]).into_iter()
+                .map(|(t, algoritmo)| (t + first - LOOKBACK_WEEKS, algoritmo))
+                .filter(|(t, _)| *t >= first)
+                .collect();
+            let expected: Vec<(usize, &str)> = full.iter().copied().filter(|(t, _)| *t >= first).collect();
+
+            assert_eq!(partial, expected, "recálculo a partir da semana {}", first);
+        }
+    }
+
+    #[test]
+    fn window_spans_whole_epi_weeks() {
+        let window = SurveillanceWindow::for_dates(
+            NaiveDate::from_ymd_opt(2024, 3, 1).unwrap(),
+            NaiveDate::from_ymd_opt(2024, 3, 31).unwrap(),
+        );
+
+        assert_eq!(window.alerts_start, NaiveDate::from_ymd_opt(2024, 2, 25).unwrap());
+        assert_eq!(window.counts_start, window.alerts_start - Duration::weeks(LOOKBACK_WEEKS as i64));
+        assert_eq!(window.end, NaiveDate::from_ymd_opt(2024, 4, 6).unwrap() + Duration::weeks(LOOKBACK_WEEKS as i64));
+    }
+
+    #[test]
+    fn window_never_starts_before_the_first_attendance() {
+        let first_day = NaiveDate::from_ymd_opt(2024, 1, 10).unwrap();
+        let window = SurveillanceWindow::for_dates(first_day, first_day).starting_from(first_day);
+
+        assert_eq!(window.counts_start, NaiveDate::from_ymd_opt(2024, 1, 7).unwrap());
+        assert_eq!(window.alerts_start, NaiveDate::from_ymd_opt(2024, 1, 7).unwrap());
+    }
+
+    #[test]
+    fn incremental_run_matches_full_run_with_leading_empty_weeks() {
+        let first_week = NaiveDate::from_ymd_opt(2024, 1, 7).unwrap();
+        let doenca = list_of_most_common_diseases()[0];
+
+        // Atendimentos nas semanas 0 a 5 e 20 a 39, com picos; as semanas 6 a 19 não têm registros
+        let counts: Vec<NeighborhoodDailyCount> = (0..40)
+            .filter(|week| !(6..20).contains(week))
+            .map(|week| NeighborhoodDailyCount {
+                data: first_week + Duration::weeks(week as i64) + Duration::days(2),
+                queixa: doenca.to_string(),
+                bairro: "Centro".to_string(),
+                quantidade: [4, 6, 5, 3, 7][week % 5] + if week % 9 == 8 { 15 } else { 0 },
+            })
+            .collect();
+
+        let key = |signal: &AlertSignal| (
+            signal.semana.clone(),
+            signal.bairro.clone(),
+            signal.algoritmo.clone(),
+            signal.observado,
+            signal.baseline.semana_inicio.clone(),
+        );
+        let full = detect_signals(&counts, None);
+        assert!(!full.is_empty());
+
+        // Recalcular a partir de cada semana, inclusive quando a linha de base começa em semanas vazias ou
+        // antes do primeiro atendimento, dá o mesmo resultado do cálculo completo
+        for week in 0..40 {
+            let day = first_week + Duration::weeks(week);
+            let window = SurveillanceWindow::for_dates(day, day).starting_from(counts[0].data);
+            let fetched: Vec<NeighborhoodDailyCount> = counts.iter()
+                .filter(|c| c.data >= window.counts_start && c.data <= window.end)
+                .cloned()
+                .collect();
+
+            let mut incremental: Vec<_> = detect_signals(&fetched, Some(&window)).iter().map(key).collect();
+            let mut expected: Vec<_> = full.iter()
+                .filter(|s| s.semana_inicio >= window.alerts_start && s.semana_inicio <= window.end)
+                .map(key)
+                .collect();
+            incremental.sort();
+            expected.sort();
+
+            assert_eq!(incremental, expected, "recálculo a partir da semana {}", week);
+        }
+    }
+}
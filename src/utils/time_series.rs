@@ -0,0 +1,383 @@
+use chrono::{Datelike, Duration, NaiveDate};
+use serde_json::{json, Map, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::domain::models::time_series::{
+    DailyCount,
+    NeighborhoodTrendQuery,
+    TimeSeries,
+    TimeSeriesPoint,
+    GRANULARITY_DAILY,
+    GRANULARITY_EPI_WEEK,
+    VIEW_MAP,
+    VIEW_TREND,
+};
+use crate::utils::competencia::parse_competencia;
+use crate::AppError;
+
+const MAX_MOVING_AVERAGE_MONTHS: usize = 12;
+
+/// Primeiro dia (domingo) da semana epidemiológica 1 do ano
+pub fn epi_year_start(year: i32) -> NaiveDate {
+    let jan_4 = NaiveDate::from_ymd_opt(year, 1, 4).expect("4 de janeiro é sempre uma data válida");
+    jan_4 - Duration::days(jan_4.weekday().num_days_from_sunday() as i64)
+}
+
+/// Semana epidemiológica (ano, semana) de uma data, segundo o calendário do SINAN:
+/// semanas de domingo a sábado, sendo a semana 1 aquela com ao menos quatro dias em janeiro
+pub fn epi_week(date: NaiveDate) -> (i32, u32) {
+    let year = date.year();
+
+    let (epi_year, start) = if date >= epi_year_start(year + 1) {
+        (year + 1, epi_year_start(year + 1))
+    } else if date < epi_year_start(year) {
+        (year - 1, epi_year_start(year - 1))
+    } else {
+        (year, epi_year_start(year))
+    };
+
+    (epi_year, ((date - start).num_days() / 7) as u32 + 1)
+}
+
+/// Domingo e sábado de uma semana epidemiológica
+pub fn epi_week_bounds((year, week): (i32, u32)) -> (NaiveDate, NaiveDate) {
+    let start = epi_year_start(year) + Duration::weeks(week as i64 - 1);
+    (start, start + Duration::days(6))
+}
+
+/// Rótulo da semana epidemiológica, ex.: "2024-SE09"
+pub fn epi_week_label((year, week): (i32, u32)) -> String {
+    format!("{}-SE{:02}", year, week)
+}
+
+/// Valida uma data recebida pela API no formato "AAAA-MM-DD"
+pub fn validate_date(field: &str, value: &str) -> Result<NaiveDate, AppError> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| AppError::BadRequest(format!(
+        "Error: '{}' is not a valid value for '{}'. Expected format: YYYY-MM-DD",
+        value, field
+    )))
+}
+
+/// Data do atendimento armazenada como texto (AAAA-MM-DD). Datas inexistentes no calendário, como 2024-02-30,
+/// passam pelo filtro de formato do banco e são descartadas aqui
+pub fn parse_attendance_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok()
+}
+
+// Série diária preenchida com zero nos dias sem atendimento
+fn daily_points(counts: &HashMap<NaiveDate, i64>, start: NaiveDate, end: NaiveDate) -> Vec<TimeSeriesPoint> {
+    start.iter_days()
+        .take_while(|day| *day <= end)
+        .map(|day| TimeSeriesPoint {
+            periodo: day.format("%Y-%m-%d").to_string(),
+            inicio: day,
+            fim: day,
+            quantidade: counts.get(&day).copied().unwrap_or(0),
+        })
+        .collect()
+}
+
+// Série por semana epidemiológica. As semanas das extremidades podem estar incompletas
+fn epi_week_points(counts: &HashMap<NaiveDate, i64>, start: NaiveDate, end: NaiveDate) -> Vec<TimeSeriesPoint> {
+    let mut weekly: HashMap<(i32, u32), i64> = HashMap::new();
+    for (day, quantidade) in counts {
+        *weekly.entry(epi_week(*day)).or_insert(0) += quantidade;
+    }
+
+    let mut points = Vec::new();
+    let (mut week_start, _) = epi_week_bounds(epi_week(start));
+
+    while week_start <= end {
+        let week = epi_week(week_start);
+        points.push(TimeSeriesPoint {
+            periodo: epi_week_label(week),
+            inicio: week_start,
+            fim: week_start + Duration::days(6),
+            quantidade: weekly.get(&week).copied().unwrap_or(0),
+        });
+        week_start += Duration::weeks(1);
+    }
+
+    points
+}
+
+/// Monta as séries de total de atendimentos e de cada queixa classificada no período
+pub fn build_time_series(counts: &[DailyCount], granularity: &str, start: NaiveDate, end: NaiveDate) -> TimeSeries {
+    let mut total: HashMap<NaiveDate, i64> = HashMap::new();
+    let mut per_disease: BTreeMap<String, HashMap<NaiveDate, i64>> = BTreeMap::new();
+
+    for count in counts.iter().filter(|c| c.data >= start && c.data <= end) {
+        *total.entry(count.data).or_insert(0) += count.quantidade;
+
+        // Atendimentos sem queixa classificada entram apenas no total
+        if !count.queixa.is_empty() {
+            *per_disease.entry(count.queixa.clone())
+                .or_default()
+                .entry(count.data)
+                .or_insert(0) += count.quantidade;
+        }
+    }
+
+    let points = |counts: &HashMap<NaiveDate, i64>| match granularity {
+        GRANULARITY_EPI_WEEK => epi_week_points(counts, start, end),
+        _ => daily_points(counts, start, end),
+    };
+
+    TimeSeries {
+        granularidade: if granularity == GRANULARITY_EPI_WEEK { GRANULARITY_EPI_WEEK } else { GRANULARITY_DAILY }.to_string(),
+        inicio: start,
+        fim: end,
+        total: points(&total),
+        doencas: per_disease.iter().map(|(disease, counts)| (disease.clone(), points(counts))).collect(),
+    }
+}
+
+/// Indica se a visão pedida do mapa de calor por doença é a série mensal por bairro (padrão: mapa)
+pub fn trend_requested(query: &NeighborhoodTrendQuery) -> Result<bool, AppError> {
+    match query.visao.as_deref().map(|v| v.trim().to_lowercase()).as_deref() {
+        None | Some("") | Some(VIEW_MAP) => Ok(false),
+        Some(VIEW_TREND) => Ok(true),
+        Some(other) => Err(AppError::BadRequest(format!(
+            "Error: '{}' is not a valid value for 'visao'. Expected: {} or {}",
+            other, VIEW_MAP, VIEW_TREND
+        ))),
+    }
+}
+
+/// Doença, bairros e modos da série mensal por bairro
+pub struct TrendOptions {
+    pub doenca: String,
+    pub bairros: Vec<String>,
+    pub media_movel: Option<usize>,
+    pub acumulado: bool,
+}
+
+impl TrendOptions {
+    pub fn from_query(query: &NeighborhoodTrendQuery) -> Result<Self, AppError> {
+        let doenca = query.doenca.as_deref()
+            .map(|d| d.trim().to_lowercase())
+            .filter(|d| !d.is_empty())
+            .ok_or_else(|| AppError::BadRequest("Error: 'doenca' is required".to_string()))?;
+
+        let bairros = query.bairros.as_deref().unwrap_or("")
+            .split(',')
+            .map(|b| b.trim().to_string())
+            .filter(|b| !b.is_empty())
+            .collect();
+
+        let media_movel = match query.media_movel {
+            None => None,
+            Some(months) if (2..=MAX_MOVING_AVERAGE_MONTHS).contains(&months) => Some(months),
+            Some(months) => return Err(AppError::BadRequest(format!(
+                "Error: '{}' is not a valid value for 'media_movel'. Expected a number of months between 2 and {}",
+                months, MAX_MOVING_AVERAGE_MONTHS
+            ))),
+        };
+
+        Ok(Self {
+            doenca,
+            bairros,
+            media_movel,
+            acumulado: query.acumulado.unwrap_or(false),
+        })
+    }
+}
+
+fn round2(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+// Competências de `start` a `end`, inclusive
+fn months_between(start: (i32, u32), end: (i32, u32)) -> Vec<(i32, u32)> {
+    let mut months = Vec::new();
+    let mut current = start;
+
+    while current <= end {
+        months.push(current);
+        current = if current.1 == 12 { (current.0 + 1, 1) } else { (current.0, current.1 + 1) };
+    }
+
+    months
+}
+
+// Pontos da série de um bairro. Contagens suprimidas (`None`) também anulam as médias móveis que
+// as incluem e o acumulado a partir delas, que permitiriam recalculá-las
+fn trend_points(months: &[(i32, u32)], values: &[Option<i64>], options: &TrendOptions) -> Vec<Value> {
+    let mut acumulado = Some(0);
+
+    months.iter().zip(values).enumerate()
+        .map(|(i, ((year, month), quantidade))| {
+            let mut point = Map::new();
+            point.insert("competencia".to_string(), json!(format!("{}-{:02}", year, month)));
+            point.insert("quantidade".to_string(), json!(quantidade));
+
+            if let Some(window) = options.media_movel {
+                // Média dos últimos `window` meses, nula até a janela se completar
+                let average = (i + 1 >= window)
+                    .then(|| values[i + 1 - window..=i].iter().copied().sum::<Option<i64>>())
+                    .flatten()
+                    .map(|sum| round2(sum as f64 / window as f64));
+                point.insert("media_movel".to_string(), json!(average));
+            }
+
+            if options.acumulado {
+                acumulado = acumulado.zip(*quantidade).map(|(total, quantidade)| total + quantidade);
+                point.insert("acumulado".to_string(), json!(acumulado));
+            }
+
+            Value::Object(point)
+        })
+        .collect()
+}
+
+/// Série mensal de uma doença por bairro, a partir do mapa de calor armazenado
+/// ({doença: {competência: {bairro: {quantidade, ...}}}}). Meses sem casos entram com zero, e as
+/// contagens entre 1 e `min_cell_size - 1` ficam nulas. Sem período, a série vai da primeira à última
+/// competência com casos da doença. Retorna `None` quando a doença não tem casos na unidade,
+/// ou a série e a quantidade de células suprimidas
+pub fn neighborhood_disease_trend(
+    data: &Map<String, Value>,
+    options: &TrendOptions,
+    window: Option<((i32, u32), (i32, u32))>,
+    min_cell_size: i64,
+) -> Option<(Value, usize)> {
+    let disease = data.iter()
+        .find(|(name, _)| name.trim().to_lowercase() == options.doenca)
+        .and_then(|(_, competencias)| competencias.as_object())?;
+
+    // Casos por competência e bairro, e todos os bairros com casos da doença
+    let mut counts: BTreeMap<(i32, u32), HashMap<&str, i64>> = BTreeMap::new();
+    let mut known: BTreeSet<&str> = BTreeSet::new();
+
+    for (competencia, bairros) in disease {
+        let Some(bairros) = bairros.as_object() else {
+            continue;
+        };
+        known.extend(bairros.keys().map(String::as_str));
+
+        let Some(parsed) = parse_competencia(competencia) else {
+            continue;
+        };
+        let month = counts.entry(parsed).or_default();
+        for (bairro, cell) in bairros {
+            *month.entry(bairro.as_str()).or_insert(0) += cell.get("quantidade").and_then(Value::as_i64).unwrap_or(0);
+        }
+    }
+
+    // Limites em aberto seguem as competências com casos
+    let first = counts.keys().next().copied();
+    let last = counts.keys().next_back().copied();
+    let (start, end) = match window {
+        Some((start, end)) => (
+            if start.0 == i32::MIN { first? } else { start },
+            if end.0 == i32::MAX { last? } else { end },
+        ),
+        None => (first?, last?),
+    };
+    let months = months_between(start, end);
+
+    // Bairros pedidos são comparados sem diferenciar maiúsculas e mantêm o nome armazenado
+    let (selected, missing): (Vec<&str>, Vec<&String>) = if options.bairros.is_empty() {
+        (known.iter().copied().collect(), Vec::new())
+    } else {
+        let mut selected = Vec::new();
+        let mut missing = Vec::new();
+        for requested in &options.bairros {
+            match known.iter().find(|bairro| bairro.trim().to_uppercase() == requested.to_uppercase()) {
+                Some(bairro) if !selected.contains(bairro) => selected.push(*bairro),
+                Some(_) => {},
+                None => missing.push(requested),
+            }
+        }
+        (selected, missing)
+    };
+
+    let mut suppressed = 0;
+    let bairros: Map<String, Value> = selected.into_iter()
+        .map(|bairro| {
+            let values: Vec<Option<i64>> = months.iter()
+                .map(|month| {
+                    let quantidade = counts.get(month).and_then(|m| m.get(bairro)).copied().unwrap_or(0);
+                    if min_cell_size > 1 && quantidade > 0 && quantidade < min_cell_size {
+                        suppressed += 1;
+                        None
+                    } else {
+                        Some(quantidade)
+                    }
+                })
+                .collect();
+
+            (bairro.to_string(), Value::Array(trend_points(&months, &values, options)))
+        })
+        .collect();
+
+    let trend = json!({
+        "doenca": options.doenca,
+        "inicio": format!("{}-{:02}", start.0, start.1),
+        "fim": format!("{}-{:02}", end.0, end.1),
+        "media_movel": options.media_movel,
+        "acumulado": options.acumulado,
+        "bairros": bairros,
+        "bairros_nao_encontrados": missing,
+    });
+
+    Some((trend, suppressed))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn attendance_dates_outside_the_calendar_are_discarded() {
+        assert_eq!(parse_attendance_date("2024-02-29"), Some(date(2024, 2, 29)));
+        assert_eq!(parse_attendance_date(" 2024-03-01 "), Some(date(2024, 3, 1)));
+        assert_eq!(parse_attendance_date("2024-02-30"), None);
+        assert_eq!(parse_attendance_date("2023-02-29"), None);
+        assert_eq!(parse_attendance_date("2024-13-01"), None);
+        assert_eq!(parse_attendance_date("2024-00-10"), None);
+        assert_eq!(parse_attendance_date(""), None);
+    }
+
+    #[test]
+    fn epi_year_starts_on_the_sunday_of_the_week_of_january_4() {
+        assert_eq!(epi_year_start(2024), date(2023, 12, 31));
+        assert_eq!(epi_year_start(2021), date(2021, 1, 3));
+        assert_eq!(epi_year_start(2020), date(2019, 12, 29));
+        assert_eq!(epi_year_start(2026), date(2026, 1, 4));
+    }
+
+    #[test]
+    fn epi_week_crosses_calendar_years() {
+        // Dias de janeiro antes da semana 1 pertencem à última semana do ano anterior
+        assert_eq!(epi_week(date(2021, 1, 2)), (2020, 53));
+        assert_eq!(epi_week(date(2021, 1, 3)), (2021, 1));
+        assert_eq!(epi_week(date(2023, 12, 31)), (2024, 1));
+        assert_eq!(epi_week(date(2024, 4, 28)), (2024, 18));
+        assert_eq!(epi_week(date(2024, 5, 4)), (2024, 18));
+        assert_eq!(epi_week(date(2024, 12, 28)), (2024, 52));
+    }
+
+    #[test]
+    fn epi_week_bounds_round_trip() {
+        let mut day = date(2019, 12, 1);
+        while day <= date(2026, 2, 1) {
+            let week = epi_week(day);
+            let (start, end) = epi_week_bounds(week);
+
+            assert!(start <= day && day <= end, "{} fora de {:?}", day, week);
+            assert_eq!(start.weekday(), chrono::Weekday::Sun);
+            day += Duration::days(1);
+        }
+    }
+
+    #[test]
+    fn epi_week_label_pads_the_week() {
+        assert_eq!(epi_week_label((2024, 9)), "2024-SE09");
+        assert_eq!(epi_week_label((2020, 53)), "2020-SE53");
+    }
+}
@@ -131,10 +131,29 @@ pub fn routes_for_users_common(path: &str) -> bool {
         "number-of-visits-per-doctor",
         "average-time-in-minutes-per-doctor",
         "heat-map-with-disease-indication",
-        "heat-map-with-the-number-of-medical-appointments-by-neighborhood"
+        "heat-map-with-the-number-of-medical-appointments-by-neighborhood",
+        // Rota genérica dos gráficos registrados
+        "/chart/",
+        // Comparação de um gráfico entre unidades
+        "/compare/",
+        "/time-series/daily",
+        "/time-series/epi-week"
     ];
 
-    // Verifica rotas estáticas OU rotas dinâmicas de usuário
-    static_routes.iter().any(|route| path == *route) ||
-    (path.starts_with("/api/data/user/") && dynamic_endpoints.iter().any(|endpoint| path.contains(endpoint)))
+    // Endpoints por unidade (/api/data/unit/{unit_id}/...) que usuários comuns podem consultar. O caminho
+    // precisa ser exato, para que as ações sobre os alertas continuem restritas aos administradores, e o
+    // acesso à unidade é verificado no handler
+    let unit_endpoints = [
+        "alerts"
+    ];
+
+    let unit_endpoint = path.strip_prefix("/api/data/unit/")
+        .and_then(|rest| rest.split_once('/'))
+        .filter(|(unit_id, _)| unit_id.parse::<i32>().is_ok())
+        .map(|(_, endpoint)| endpoint);
+
+    // Verifica rotas estáticas OU rotas dinâmicas de usuário OU rotas de consulta por unidade
+    static_routes.contains(&path) ||
+    (path.starts_with("/api/data/user/") && dynamic_endpoints.iter().any(|endpoint| path.contains(endpoint))) ||
+    unit_endpoint.is_some_and(|endpoint| unit_endpoints.contains(&endpoint))
 }